@@ -0,0 +1,385 @@
+// =================================================================================
+// MODULE: Alert Rules
+// DESCRIPTION:
+// Policy layer between alert producers and the sinks. During a sustained spike
+// the same condition fires every tick; this module decides what actually
+// reaches a human.
+//
+// Key components:
+// - Deduplication of identical alerts within a window
+// - Cooldowns per (rule, symbol)
+// - Digest batching of low-severity alerts
+// - Escalation when a condition persists, once per severity level
+// - Audit trail of every decision
+// =================================================================================
+
+use crate::monad::{MResult, BotError, log_info};
+use crate::alerts::{Alert, AlertKind, AlertSeverity};
+use crate::json::JsonValue;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Display;
+use std::fs::OpenOptions;
+use std::io::Write;
+
+/// In-memory audit entries kept for inspection.
+const AUDIT_CAPACITY: usize = 500;
+
+/// Timing and threshold parameters for the rules layer.
+#[derive(Debug, Clone)]
+pub struct AlertRulesConfig {
+    pub dedup_window_secs: u64,     // Identical alerts inside this window are dropped
+    pub cooldown_secs: u64,         // Min gap between deliveries per (rule, symbol)
+    pub digest_interval_secs: u64,  // How often batched alerts are flushed
+    pub digest_max_severity: AlertSeverity, // Alerts at or below this are batched
+    pub escalate_after: u32,        // Consecutive occurrences before escalating
+    pub persistence_gap_secs: u64,  // A longer silence ends the streak
+    pub audit_path: Option<String>, // JSON-lines audit file
+}
+
+impl AlertRulesConfig {
+    pub fn standard() -> Self {
+        AlertRulesConfig {
+            dedup_window_secs: 600,
+            cooldown_secs: 60,
+            digest_interval_secs: 300,
+            digest_max_severity: AlertSeverity::Info,
+            escalate_after: 5,
+            persistence_gap_secs: 120,
+            audit_path: None,
+        }
+    }
+}
+
+/// Outcome of running an alert through the rules.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AlertDecision {
+    Deliver,
+    Escalated { from: AlertSeverity, to: AlertSeverity },
+    Duplicate,
+    Cooldown { remaining_secs: u64 },
+    Batched,
+}
+
+impl AlertDecision {
+    pub fn is_delivered(&self) -> bool {
+        matches!(self, AlertDecision::Deliver | AlertDecision::Escalated { .. })
+    }
+}
+
+impl Display for AlertDecision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AlertDecision::Deliver => write!(f, "deliver"),
+            AlertDecision::Escalated { from, to } => write!(f, "escalated {}->{}", from, to),
+            AlertDecision::Duplicate => write!(f, "duplicate"),
+            AlertDecision::Cooldown { remaining_secs } => write!(f, "cooldown {}s", remaining_secs),
+            AlertDecision::Batched => write!(f, "batched"),
+        }
+    }
+}
+
+/// One line of the audit trail.
+#[derive(Debug, Clone)]
+pub struct AuditRecord {
+    pub timestamp: u64,
+    pub rule: String,
+    pub symbol: Option<String>,
+    pub severity: AlertSeverity,
+    pub title: String,
+    pub decision: AlertDecision,
+}
+
+impl AuditRecord {
+    pub fn to_json(&self) -> JsonValue {
+        JsonValue::object()
+            .with("timestamp", self.timestamp)
+            .with("rule", self.rule.as_str())
+            .with("symbol", self.symbol.clone())
+            .with("severity", self.severity.to_string())
+            .with("title", self.title.as_str())
+            .with("decision", self.decision.to_string())
+    }
+}
+
+/// Persistence tracking for one (rule, symbol).
+struct Streak {
+    count: u32,
+    last_seen: u64,
+    escalated: Option<AlertSeverity>, // Highest severity this streak was raised to
+}
+
+/// Stateful rules engine. Feed every alert through `evaluate`; call
+/// `flush_digest` periodically to collect batched alerts.
+pub struct AlertRules {
+    config: AlertRulesConfig,
+    recent_fingerprints: HashMap<String, u64>,  // fingerprint -> last seen
+    last_delivery: HashMap<String, u64>,        // (rule, symbol) -> last delivered
+    streaks: HashMap<String, Streak>,
+    digest: Vec<Alert>,
+    last_digest: u64,
+    audit: VecDeque<AuditRecord>,
+}
+
+impl AlertRules {
+    pub fn new(config: AlertRulesConfig) -> Self {
+        AlertRules {
+            config,
+            recent_fingerprints: HashMap::new(),
+            last_delivery: HashMap::new(),
+            streaks: HashMap::new(),
+            digest: Vec::new(),
+            last_digest: 0,
+            audit: VecDeque::with_capacity(AUDIT_CAPACITY),
+        }
+    }
+
+    /// Decides what happens to `alert` at time `now` (unix seconds).
+    /// Returns the alert to deliver (possibly with raised severity), if any.
+    pub fn evaluate(&mut self, mut alert: Alert, now: u64) -> (AlertDecision, Option<Alert>) {
+        let key = format!("{}|{}", alert.rule, alert.symbol.as_deref().unwrap_or("*"));
+        let fingerprint = format!("{}|{:?}|{}|{}", key, alert.severity, alert.title, alert.message);
+        let escalation = self.track_streak(&key, alert.severity, now);
+
+        let decision = if let Some(to) = escalation {
+            let from = alert.severity;
+            alert.severity = to;
+            AlertDecision::Escalated { from, to }
+        } else if self.recent_fingerprints.get(&fingerprint).is_some_and(|t| now.saturating_sub(*t) < self.config.dedup_window_secs) {
+            AlertDecision::Duplicate
+        } else if let Some(remaining) = self.cooldown_remaining(&key, now) {
+            AlertDecision::Cooldown { remaining_secs: remaining }
+        } else if alert.severity <= self.config.digest_max_severity {
+            AlertDecision::Batched
+        } else {
+            AlertDecision::Deliver
+        };
+
+        self.recent_fingerprints.insert(fingerprint, now);
+        self.record(&alert, decision.clone(), now);
+
+        match decision {
+            AlertDecision::Deliver | AlertDecision::Escalated { .. } => {
+                self.last_delivery.insert(key, now);
+                (decision, Some(alert))
+            }
+            AlertDecision::Batched => {
+                self.digest.push(alert);
+                (decision, None)
+            }
+            _ => (decision, None),
+        }
+    }
+
+    /// Collapses batched alerts into one digest once the interval has passed.
+    pub fn flush_digest(&mut self, now: u64) -> Option<Alert> {
+        if self.last_digest == 0 {
+            self.last_digest = now;
+        }
+        if self.digest.is_empty() || now.saturating_sub(self.last_digest) < self.config.digest_interval_secs {
+            return None;
+        }
+        self.last_digest = now;
+
+        let batch: Vec<Alert> = self.digest.drain(..).collect();
+        let mut counts: Vec<(String, usize)> = Vec::new();
+        for alert in &batch {
+            let label = match &alert.symbol {
+                Some(symbol) => format!("{} {}", alert.title, symbol),
+                None => alert.title.clone(),
+            };
+            match counts.iter_mut().find(|(l, _)| *l == label) {
+                Some((_, n)) => *n += 1,
+                None => counts.push((label, 1)),
+            }
+        }
+        let lines: Vec<String> = counts.iter().map(|(label, n)| format!("{} x{}", label, n)).collect();
+
+        let mut digest = Alert::new(
+            AlertKind::Digest,
+            AlertSeverity::Info,
+            None,
+            &format!("Digest ({} alerts)", batch.len()),
+            lines.join("; "),
+        );
+        digest.timestamp = now;
+        self.record(&digest, AlertDecision::Deliver, now);
+        Some(digest)
+    }
+
+    /// Returns the severity to escalate to when the streak for `key` crosses a
+    /// multiple of `escalate_after` and that raises `severity` above what the
+    /// streak already reached. Each level fires once; past the top one the
+    /// condition is deduplicated and cooled down like any other.
+    fn track_streak(&mut self, key: &str, severity: AlertSeverity, now: u64) -> Option<AlertSeverity> {
+        let gap = self.config.persistence_gap_secs;
+        let streak = self.streaks.entry(key.to_string()).or_insert(Streak { count: 0, last_seen: now, escalated: None });
+        if now.saturating_sub(streak.last_seen) > gap {
+            streak.count = 0;
+            streak.escalated = None;
+        }
+        streak.count += 1;
+        streak.last_seen = now;
+
+        let every = self.config.escalate_after;
+        if every == 0 || !streak.count.is_multiple_of(every) {
+            return None;
+        }
+        let to = raise(severity, streak.count / every);
+        if to <= severity || streak.escalated.is_some_and(|reached| to <= reached) {
+            return None;
+        }
+        streak.escalated = Some(to);
+        Some(to)
+    }
+
+    fn cooldown_remaining(&self, key: &str, now: u64) -> Option<u64> {
+        let last = *self.last_delivery.get(key)?;
+        let elapsed = now.saturating_sub(last);
+        if elapsed < self.config.cooldown_secs {
+            Some(self.config.cooldown_secs - elapsed)
+        } else {
+            None
+        }
+    }
+
+    fn record(&mut self, alert: &Alert, decision: AlertDecision, now: u64) {
+        let record = AuditRecord {
+            timestamp: now,
+            rule: alert.rule.clone(),
+            symbol: alert.symbol.clone(),
+            severity: alert.severity,
+            title: alert.title.clone(),
+            decision,
+        };
+
+        if let Some(path) = &self.config.audit_path {
+            if let Err(e) = append_line(path, &record.to_json().to_string()) {
+                log_info(&format!("Alert audit write failed: {:?}", e));
+            }
+        }
+
+        if self.audit.len() >= AUDIT_CAPACITY {
+            self.audit.pop_front();
+        }
+        self.audit.push_back(record);
+    }
+
+    /// Recent decisions, oldest first.
+    pub fn audit_trail(&self) -> impl Iterator<Item = &AuditRecord> {
+        self.audit.iter()
+    }
+
+    /// Drops bookkeeping older than any window that could still use it.
+    pub fn prune(&mut self, now: u64) {
+        let horizon = self.config.dedup_window_secs
+            .max(self.config.cooldown_secs)
+            .max(self.config.persistence_gap_secs);
+        self.recent_fingerprints.retain(|_, t| now.saturating_sub(*t) <= horizon);
+        self.last_delivery.retain(|_, t| now.saturating_sub(*t) <= horizon);
+        self.streaks.retain(|_, s| now.saturating_sub(s.last_seen) <= horizon);
+    }
+}
+
+/// Raises severity by `levels`, saturating at Critical.
+fn raise(severity: AlertSeverity, levels: u32) -> AlertSeverity {
+    let mut result = severity;
+    for _ in 0..levels {
+        result = match result {
+            AlertSeverity::Info => AlertSeverity::Warning,
+            _ => AlertSeverity::Critical,
+        };
+    }
+    result
+}
+
+fn append_line(path: &str, line: &str) -> MResult<()> {
+    let io_err = |e: std::io::Error| BotError::InternalStateError(format!("Audit log {}: {}", path, e));
+    let mut file = OpenOptions::new().create(true).append(true).open(path).map_err(io_err)?;
+    writeln!(file, "{}", line).map_err(io_err)
+}
+
+// =================================================================================
+// UNIT TESTS
+// =================================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spike(severity: AlertSeverity, message: &str) -> Alert {
+        Alert::new(AlertKind::VolumeSpike, severity, Some("MON/USDT"), "Volume spike", message.to_string())
+    }
+
+    #[test]
+    fn test_dedup_cooldown_and_escalation() {
+        let mut config = AlertRulesConfig::standard();
+        config.escalate_after = 3;
+        let mut rules = AlertRules::new(config);
+
+        let (first, _) = rules.evaluate(spike(AlertSeverity::Warning, "x3"), 1_000);
+        assert_eq!(first, AlertDecision::Deliver);
+
+        let (dup, _) = rules.evaluate(spike(AlertSeverity::Warning, "x3"), 1_010);
+        assert_eq!(dup, AlertDecision::Duplicate);
+
+        // Third consecutive occurrence escalates and bypasses the cooldown
+        let (third, alert) = rules.evaluate(spike(AlertSeverity::Warning, "x4"), 1_020);
+        assert_eq!(third, AlertDecision::Escalated { from: AlertSeverity::Warning, to: AlertSeverity::Critical });
+        assert_eq!(alert.unwrap().severity, AlertSeverity::Critical);
+
+        let (fourth, _) = rules.evaluate(spike(AlertSeverity::Warning, "x5"), 1_030);
+        assert_eq!(fourth, AlertDecision::Cooldown { remaining_secs: 50 });
+
+        // Critical is the top level: the next multiples stay in cooldown, and
+        // once it has passed the sustained condition is delivered as itself
+        let decisions: Vec<AlertDecision> = (5..=12)
+            .map(|i| rules.evaluate(spike(AlertSeverity::Warning, &format!("x{}", i)), 1_000 + i * 10).0)
+            .collect();
+        assert!(!decisions.iter().any(|d| matches!(d, AlertDecision::Escalated { .. })), "{:?}", decisions);
+        assert_eq!(decisions[1], AlertDecision::Cooldown { remaining_secs: 20 }); // 6th occurrence
+        assert_eq!(decisions[3], AlertDecision::Deliver);                        // a minute after the escalation
+        assert_eq!(decisions[4], AlertDecision::Cooldown { remaining_secs: 50 }); // 9th occurrence
+
+        // A silence ends the streak, so a new one may escalate again
+        let (fresh, _) = rules.evaluate(spike(AlertSeverity::Warning, "y"), 1_500);
+        assert_eq!(fresh, AlertDecision::Deliver);
+        rules.evaluate(spike(AlertSeverity::Warning, "y1"), 1_510);
+        let (again, _) = rules.evaluate(spike(AlertSeverity::Warning, "y2"), 1_570);
+        assert_eq!(again, AlertDecision::Escalated { from: AlertSeverity::Warning, to: AlertSeverity::Critical });
+
+        assert_eq!(rules.audit_trail().count(), 15);
+    }
+
+    #[test]
+    fn test_escalation_raises_one_level_at_a_time() {
+        let mut config = AlertRulesConfig::standard();
+        config.escalate_after = 2;
+        config.cooldown_secs = 0;
+        let mut rules = AlertRules::new(config);
+
+        let decisions: Vec<AlertDecision> = (0..8)
+            .map(|i| rules.evaluate(spike(AlertSeverity::Info, &format!("#{}", i)), 1_000 + i * 10).0)
+            .collect();
+        let escalations: Vec<&AlertDecision> = decisions.iter().filter(|d| matches!(d, AlertDecision::Escalated { .. })).collect();
+        assert_eq!(escalations, vec![
+            &AlertDecision::Escalated { from: AlertSeverity::Info, to: AlertSeverity::Warning },
+            &AlertDecision::Escalated { from: AlertSeverity::Info, to: AlertSeverity::Critical },
+        ]);
+    }
+
+    #[test]
+    fn test_low_severity_alerts_are_digested() {
+        let mut rules = AlertRules::new(AlertRulesConfig::standard());
+        assert!(rules.flush_digest(1_000).is_none());
+
+        for (i, symbol) in ["A", "B", "A"].iter().enumerate() {
+            let mut alert = spike(AlertSeverity::Info, &format!("#{}", i));
+            alert.symbol = Some(symbol.to_string());
+            let (decision, _) = rules.evaluate(alert, 1_000 + i as u64 * 200);
+            assert_eq!(decision, AlertDecision::Batched);
+        }
+
+        let digest = rules.flush_digest(1_400).unwrap();
+        assert_eq!(digest.kind, AlertKind::Digest);
+        assert_eq!(digest.message, "Volume spike A x2; Volume spike B x1");
+    }
+}
@@ -0,0 +1,505 @@
+// =================================================================================
+// MODULE: Alerting
+// DESCRIPTION:
+// Turns engine events (signals, volume spikes, whales, errors, state changes)
+// into `Alert`s and fans them out to pluggable notification sinks.
+//
+// Key components:
+// - `Alert` model with kind and severity
+// - `AlertSink` trait: stdout, JSON-lines file, HTTP webhook, Telegram Bot API
+// - `AlertRouter` with a minimum-severity filter per sink
+// =================================================================================
+
+use crate::monad::{MResult, unit, fail, BotError, Bind, log_info};
+use crate::exchange::OrderSide;
+use crate::trader::Signal;
+use crate::volume::{VolumeSpike, SpikeSeverity};
+use crate::flow::WhaleAlert;
+use crate::expr::ExpressionHit;
+use crate::json::JsonValue;
+use crate::http;
+use crate::alert_rules::{AlertRules, AlertRulesConfig};
+use std::fmt::Display;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Timeout applied to every outbound HTTP notification.
+const HTTP_TIMEOUT: Duration = Duration::from_secs(5);
+
+// --- Alert Model ---
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AlertSeverity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl AlertSeverity {
+    /// Parses a config value such as `info`, `warning` or `critical`.
+    pub fn parse(value: &str) -> MResult<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "info" => unit(AlertSeverity::Info),
+            "warn" | "warning" => unit(AlertSeverity::Warning),
+            "critical" | "crit" => unit(AlertSeverity::Critical),
+            other => fail(BotError::ConfigurationError(format!("Unknown alert severity '{}'", other))),
+        }
+    }
+}
+
+impl Display for AlertSeverity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AlertSeverity::Info => write!(f, "INFO"),
+            AlertSeverity::Warning => write!(f, "WARNING"),
+            AlertSeverity::Critical => write!(f, "CRITICAL"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AlertKind {
+    Signal,
+    VolumeSpike,
+    Whale,
+    Error,
+    StateChange,
+    Expression,
+    Digest,
+}
+
+impl Display for AlertKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AlertKind::Signal => write!(f, "signal"),
+            AlertKind::VolumeSpike => write!(f, "volume_spike"),
+            AlertKind::Whale => write!(f, "whale"),
+            AlertKind::Error => write!(f, "error"),
+            AlertKind::StateChange => write!(f, "state_change"),
+            AlertKind::Expression => write!(f, "expression"),
+            AlertKind::Digest => write!(f, "digest"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Alert {
+    pub kind: AlertKind,
+    pub rule: String, // Identity for cooldowns and escalation; defaults to the kind
+    pub severity: AlertSeverity,
+    pub symbol: Option<String>,
+    pub title: String,
+    pub message: String,
+    pub timestamp: u64,
+}
+
+impl Alert {
+    pub fn new(kind: AlertKind, severity: AlertSeverity, symbol: Option<&str>, title: &str, message: String) -> Self {
+        Alert {
+            kind,
+            rule: kind.to_string(),
+            severity,
+            symbol: symbol.map(|s| s.to_string()),
+            title: title.to_string(),
+            message,
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
+        }
+    }
+
+    pub fn from_signal(signal: &Signal) -> Self {
+        let mut alert = Alert::new(
+            AlertKind::Signal,
+            AlertSeverity::Warning,
+            Some(&signal.symbol),
+            &format!("{:?} signal", signal.side),
+            format!("{} | strength {:.2} | {:?}", signal.reason, signal.strength, signal.regime),
+        );
+        alert.timestamp = signal.timestamp;
+        alert
+    }
+
+    pub fn from_spike(spike: &VolumeSpike) -> Self {
+        let severity = match spike.severity {
+            SpikeSeverity::Extreme => AlertSeverity::Critical,
+            SpikeSeverity::High => AlertSeverity::Warning,
+            _ => AlertSeverity::Info,
+        };
+        let mut alert = Alert::new(
+            AlertKind::VolumeSpike,
+            severity,
+            Some(&spike.symbol),
+            &format!("{} volume spike", spike.severity),
+            format!(
+                "Vol {:.0} | x{:.2} avg | z {:.2} | robust {:.2}",
+                spike.volume, spike.relative_volume, spike.z_score, spike.robust_score
+            ),
+        );
+        alert.timestamp = spike.timestamp;
+        alert
+    }
+
+    pub fn from_whale(whale: &WhaleAlert) -> Self {
+        let side = if whale.side == OrderSide::Buy { "buy" } else { "sell" };
+        let mut alert = Alert::new(
+            AlertKind::Whale,
+            AlertSeverity::Warning,
+            Some(&whale.symbol),
+            &format!("Whale {} {:?}", side, whale.kind),
+            whale.to_string(),
+        );
+        alert.timestamp = whale.timestamp_ms / 1000;
+        alert
+    }
+
+    /// User-defined expressions use their own name as the rule identity,
+    /// so cooldowns and escalation apply per expression.
+    pub fn from_expression(hit: &ExpressionHit) -> Self {
+        let readings: Vec<String> = hit.values.iter().map(|(label, v)| format!("{}={:.2}", label, v)).collect();
+        let mut alert = Alert::new(
+            AlertKind::Expression,
+            AlertSeverity::Warning,
+            Some(&hit.symbol),
+            &hit.name,
+            if readings.is_empty() { hit.source.clone() } else { format!("{} | {}", hit.source, readings.join(" ")) },
+        );
+        alert.rule = format!("expr:{}", hit.name);
+        alert.timestamp = hit.timestamp;
+        alert
+    }
+
+    /// Network hiccups are expected and only warn; anything else is critical.
+    pub fn from_error(error: &BotError, symbol: Option<&str>) -> Self {
+        let severity = match error {
+            BotError::NetworkFailure(_) => AlertSeverity::Warning,
+            _ => AlertSeverity::Critical,
+        };
+        Alert::new(AlertKind::Error, severity, symbol, "Engine error", format!("{:?}", error))
+    }
+
+    pub fn state_change(from: &str, to: &str, severity: AlertSeverity) -> Self {
+        Alert::new(AlertKind::StateChange, severity, None, "State change", format!("{} -> {}", from, to))
+    }
+
+    /// Single-line human readable rendering, used by text sinks.
+    pub fn render(&self) -> String {
+        match &self.symbol {
+            Some(symbol) => format!("[{}] {} {}: {}", self.severity, symbol, self.title, self.message),
+            None => format!("[{}] {}: {}", self.severity, self.title, self.message),
+        }
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        JsonValue::object()
+            .with("timestamp", self.timestamp)
+            .with("kind", self.kind.to_string())
+            .with("rule", self.rule.as_str())
+            .with("severity", self.severity.to_string())
+            .with("symbol", self.symbol.clone())
+            .with("title", self.title.as_str())
+            .with("message", self.message.as_str())
+    }
+}
+
+// --- Sinks ---
+
+/// A destination for alerts.
+pub trait AlertSink {
+    fn name(&self) -> &str;
+    fn send(&mut self, alert: &Alert) -> MResult<()>;
+}
+
+/// Prints alerts to the console.
+pub struct StdoutSink;
+
+impl AlertSink for StdoutSink {
+    fn name(&self) -> &str {
+        "stdout"
+    }
+
+    fn send(&mut self, alert: &Alert) -> MResult<()> {
+        println!("[ALERT] {}", alert.render());
+        unit(())
+    }
+}
+
+/// Appends one JSON object per line to a file.
+pub struct JsonLinesSink {
+    path: String,
+}
+
+impl JsonLinesSink {
+    pub fn new(path: &str) -> Self {
+        JsonLinesSink { path: path.to_string() }
+    }
+}
+
+impl AlertSink for JsonLinesSink {
+    fn name(&self) -> &str {
+        "jsonl"
+    }
+
+    fn send(&mut self, alert: &Alert) -> MResult<()> {
+        let io_err = |e: std::io::Error| BotError::InternalStateError(format!("Alert log {}: {}", self.path, e));
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path).map_err(io_err)?;
+        writeln!(file, "{}", alert.to_json()).map_err(io_err)
+    }
+}
+
+/// POSTs the alert as JSON to a generic webhook.
+pub struct WebhookSink {
+    url: String,
+}
+
+impl WebhookSink {
+    pub fn new(url: &str) -> Self {
+        WebhookSink { url: url.to_string() }
+    }
+}
+
+impl AlertSink for WebhookSink {
+    fn name(&self) -> &str {
+        "webhook"
+    }
+
+    fn send(&mut self, alert: &Alert) -> MResult<()> {
+        http::post_json(&self.url, &alert.to_json().to_string(), HTTP_TIMEOUT)
+            .bind(|resp| expect_success("Webhook", resp))
+    }
+}
+
+/// Sends alerts through the Telegram Bot API `sendMessage` method.
+/// `api_base` is a plain `http://` TLS relay in front of `api.telegram.org`
+/// (the std-only client cannot speak HTTPS); tests use a local stand-in.
+pub struct TelegramSink {
+    api_base: String,
+    token: String,
+    chat_id: String,
+}
+
+impl TelegramSink {
+    pub fn new(api_base: &str, token: &str, chat_id: &str) -> Self {
+        TelegramSink {
+            api_base: api_base.trim_end_matches('/').to_string(),
+            token: token.to_string(),
+            chat_id: chat_id.to_string(),
+        }
+    }
+}
+
+impl AlertSink for TelegramSink {
+    fn name(&self) -> &str {
+        "telegram"
+    }
+
+    fn send(&mut self, alert: &Alert) -> MResult<()> {
+        let url = format!("{}/bot{}/sendMessage", self.api_base, self.token);
+        let payload = JsonValue::object()
+            .with("chat_id", self.chat_id.as_str())
+            .with("text", alert.render());
+        http::post_json(&url, &payload.to_string(), HTTP_TIMEOUT)
+            .bind(|resp| expect_success("Telegram", resp))
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
+
+fn expect_success(target: &str, resp: http::HttpResponse) -> MResult<()> {
+    if resp.is_success() {
+        unit(())
+    } else {
+        fail(BotError::NetworkFailure(format!("{} responded {}: {}", target, resp.status, resp.body)))
+    }
+}
+
+// --- Router ---
+
+/// Sink configuration, usually loaded from the environment.
+#[derive(Clone)]
+pub struct AlertConfig {
+    pub stdout_min: AlertSeverity,
+    pub jsonl_path: Option<String>,
+    pub jsonl_min: AlertSeverity,
+    pub webhook_url: Option<String>,
+    pub webhook_min: AlertSeverity,
+    pub telegram_api_base: String,
+    pub telegram_token: Option<String>,
+    pub telegram_chat_id: Option<String>,
+    pub telegram_min: AlertSeverity,
+    pub rules: Option<AlertRulesConfig>,
+}
+
+struct Route {
+    sink: Box<dyn AlertSink>,
+    min_severity: AlertSeverity,
+}
+
+/// Fans alerts out to every sink whose severity filter they pass.
+/// When a rules layer is attached, alerts go through it first.
+/// A failing sink is logged and skipped; it never blocks the others.
+pub struct AlertRouter {
+    routes: Vec<Route>,
+    rules: Option<AlertRules>,
+}
+
+impl AlertRouter {
+    pub fn new() -> Self {
+        AlertRouter { routes: Vec::new(), rules: None }
+    }
+
+    pub fn from_config(config: &AlertConfig) -> Self {
+        let mut router = AlertRouter::new();
+        router.add_sink(Box::new(StdoutSink), config.stdout_min);
+        if let Some(path) = &config.jsonl_path {
+            router.add_sink(Box::new(JsonLinesSink::new(path)), config.jsonl_min);
+        }
+        if let Some(url) = &config.webhook_url {
+            router.add_sink(Box::new(WebhookSink::new(url)), config.webhook_min);
+        }
+        if let (Some(token), Some(chat_id)) = (&config.telegram_token, &config.telegram_chat_id) {
+            router.add_sink(Box::new(TelegramSink::new(&config.telegram_api_base, token, chat_id)), config.telegram_min);
+        }
+        if let Some(rules) = &config.rules {
+            router.set_rules(AlertRules::new(rules.clone()));
+        }
+        router
+    }
+
+    pub fn add_sink(&mut self, sink: Box<dyn AlertSink>, min_severity: AlertSeverity) {
+        self.routes.push(Route { sink, min_severity });
+    }
+
+    pub fn set_rules(&mut self, rules: AlertRules) {
+        self.rules = Some(rules);
+    }
+
+    pub fn rules(&self) -> Option<&AlertRules> {
+        self.rules.as_ref()
+    }
+
+    /// Runs the alert through the rules (if any) and returns how many sinks accepted it.
+    pub fn route(&mut self, alert: &Alert) -> usize {
+        let rules = match self.rules.as_mut() {
+            Some(rules) => rules,
+            None => return self.deliver(alert),
+        };
+        match rules.evaluate(alert.clone(), now_secs()) {
+            (_, Some(approved)) => self.deliver(&approved),
+            (_, None) => 0,
+        }
+    }
+
+    /// Periodic housekeeping: sends a pending digest and prunes rule state.
+    pub fn tick(&mut self) -> usize {
+        let now = now_secs();
+        let digest = match self.rules.as_mut() {
+            Some(rules) => {
+                rules.prune(now);
+                rules.flush_digest(now)
+            }
+            None => None,
+        };
+        digest.map_or(0, |d| self.deliver(&d))
+    }
+
+    /// Sends straight to the sinks, bypassing the rules.
+    fn deliver(&mut self, alert: &Alert) -> usize {
+        let mut delivered = 0;
+        for route in self.routes.iter_mut() {
+            if alert.severity < route.min_severity {
+                continue;
+            }
+            match route.sink.send(alert) {
+                Ok(()) => delivered += 1,
+                Err(e) => log_info(&format!("Alert sink '{}' failed: {:?}", route.sink.name(), e)),
+            }
+        }
+        delivered
+    }
+
+    pub fn sink_names(&self) -> Vec<&str> {
+        self.routes.iter().map(|r| r.sink.name()).collect()
+    }
+}
+
+// =================================================================================
+// UNIT TESTS
+// =================================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read};
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// Local HTTP stand-in: accepts one request, replies `status`, returns the raw request.
+    fn stand_in(status: u16) -> (String, thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut head = String::new();
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(v) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                    content_length = v.trim().parse().unwrap();
+                }
+                head.push_str(&line);
+                if line == "\r\n" { break; }
+            }
+            let mut body = vec![0u8; content_length];
+            reader.read_exact(&mut body).unwrap();
+            let reply = format!("HTTP/1.1 {} OK\r\nContent-Length: 2\r\n\r\n{{}}", status);
+            reader.get_mut().write_all(reply.as_bytes()).unwrap();
+            head + &String::from_utf8(body).unwrap()
+        });
+        (base, handle)
+    }
+
+    fn sample_alert() -> Alert {
+        Alert::new(AlertKind::VolumeSpike, AlertSeverity::Critical, Some("MON/USDT"), "EXTREME volume spike", "Vol \"9000\"".to_string())
+    }
+
+    #[test]
+    fn test_webhook_sink_posts_json() {
+        let (base, server) = stand_in(200);
+        let mut sink = WebhookSink::new(&format!("{}/hooks/alerts", base));
+        sink.send(&sample_alert()).unwrap();
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /hooks/alerts HTTP/1.1"));
+        assert!(request.contains(r#""kind":"volume_spike""#));
+        assert!(request.contains(r#""message":"Vol \"9000\"""#));
+    }
+
+    #[test]
+    fn test_telegram_sink_uses_bot_api() {
+        let (base, server) = stand_in(200);
+        let mut sink = TelegramSink::new(&base, "123:ABC", "-1001");
+        sink.send(&sample_alert()).unwrap();
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /bot123:ABC/sendMessage HTTP/1.1"));
+        assert!(request.contains(r#""chat_id":"-1001""#));
+    }
+
+    #[test]
+    fn test_router_applies_severity_filters() {
+        let (base, server) = stand_in(500);
+        let mut router = AlertRouter::new();
+        router.add_sink(Box::new(WebhookSink::new(&base)), AlertSeverity::Warning);
+        router.add_sink(Box::new(StdoutSink), AlertSeverity::Critical);
+
+        let info = Alert::new(AlertKind::StateChange, AlertSeverity::Info, None, "State change", "A -> B".to_string());
+        assert_eq!(router.route(&info), 0);
+
+        // Webhook answers 500, so only stdout counts as delivered
+        assert_eq!(router.route(&sample_alert()), 1);
+        server.join().unwrap();
+    }
+}
@@ -12,10 +12,32 @@
 // =================================================================================
 
 use crate::monad::{MResult, unit, fail, BotError, Bind, log_info};
-use crate::exchange::{ExchangeClient, BinanceClient, OrderType, OrderSide};
-use crate::trader::{Strategy, VolumeBreakoutStrategy, RiskManager, TradeInstruction};
+use crate::exchange::{ExchangeClient, BinanceClient, OrderType, OrderSide, OrderRequest, Order, TradeStream, Ticker};
+use crate::trader::{Strategy, VolumeBreakoutStrategy, IndicatorPanel, RiskManager, RegimeAdjustment, MarketRegime, TradeInstruction, OrderAction, RsiSmoothing, format_components};
+use crate::volume::{VolumeSpikeDetector, VolumeSpike, SpikeThresholds, SpikeSeverity, SeasonalBaseline, weekday_name};
+use crate::scanner::{MarketScanner, Leaderboard};
+use crate::alerts::{AlertRouter, AlertConfig, Alert, AlertSeverity};
+use crate::expr::{AlertExpression, ExpressionMonitor};
+use crate::commands::Command;
+use crate::profile::{VolumeProfileTracker, ProfileSummary, BinSize};
+use crate::candles::{CandleAggregator, Candle, Timeframe};
+use crate::snapshot::StateSnapshot;
+use crate::regime::{RegimeClassifier, RegimeConfig, RegimeReading};
+use crate::composite::{CompositeStrategy, CompositeMember, CombineMode};
+use crate::orderbook::{OrderBookImbalanceStrategy, BookImbalanceConfig};
+use crate::mean_reversion::{MeanReversionStrategy, MeanReversionConfig};
+use crate::grid::{GridStrategy, GridConfig};
+use crate::market_maker::{MarketMakerStrategy, MarketMakerConfig};
+use crate::dca::{DcaStrategy, DcaConfig};
+use crate::paper::{PaperExchange, split_symbol};
+use std::collections::HashMap;
+use crate::flow::{TradeFlowAggregator, FlowWindow, WhaleDetector, WhaleConfig, WhaleAlert};
+use std::collections::VecDeque;
 use std::sync::Arc;
 use std::fmt::Display;
+use std::fs::OpenOptions;
+use std::io::Write;
+use crate::json::JsonValue;
 
 // --- Bot State Machine ---
 
@@ -42,12 +64,93 @@ impl Display for BotState {
 
 // --- Configuration ---
 
+/// Strategies selectable from configuration.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StrategyKind {
+    Breakout, // VolumeBreakoutStrategy on 1m bars
+    Book,     // OrderBookImbalanceStrategy
+    MeanReversion, // MeanReversionStrategy on 5m bars
+    Grid,     // GridStrategy, configured by `BotConfig::grid`
+    MarketMaker, // MarketMakerStrategy, tuned by `BotConfig::market_maker`
+    Dca,      // DcaStrategy, configured by `BotConfig::dca`
+}
+
+impl StrategyKind {
+    pub fn parse(value: &str) -> MResult<StrategyKind> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "breakout" => unit(StrategyKind::Breakout),
+            "book" => unit(StrategyKind::Book),
+            "mean_reversion" => unit(StrategyKind::MeanReversion),
+            "grid" => unit(StrategyKind::Grid),
+            "market_maker" => unit(StrategyKind::MarketMaker),
+            "dca" => unit(StrategyKind::Dca),
+            other => fail(BotError::ConfigurationError(format!("Unknown strategy '{}' (use breakout, book, mean_reversion, grid, market_maker or dca)", other))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            StrategyKind::Breakout => "breakout",
+            StrategyKind::Book => "book",
+            StrategyKind::MeanReversion => "mean_reversion",
+            StrategyKind::Grid => "grid",
+            StrategyKind::MarketMaker => "market_maker",
+            StrategyKind::Dca => "dca",
+        }
+    }
+
+    fn build(&self, config: &BotConfig) -> MResult<Box<dyn Strategy>> {
+        match self {
+            StrategyKind::Breakout => unit(Box::new(VolumeBreakoutStrategy::new(SpikeSeverity::Elevated, Some(Timeframe::M1))
+                .with_rsi_smoothing(config.rsi_smoothing))),
+            StrategyKind::Book => unit(Box::new(OrderBookImbalanceStrategy::new(BookImbalanceConfig::standard()))),
+            StrategyKind::MeanReversion => MeanReversionStrategy::new(MeanReversionConfig { rsi_smoothing: config.rsi_smoothing, ..MeanReversionConfig::standard() })
+                .map_data(|s| Box::new(s) as Box<dyn Strategy>),
+            StrategyKind::Grid => match &config.grid {
+                Some(grid) => GridStrategy::new(grid.clone()).map_data(|s| Box::new(s) as Box<dyn Strategy>),
+                None => fail(BotError::ConfigurationError("Grid strategy needs BOT_GRID_LOWER, BOT_GRID_UPPER, BOT_GRID_LEVELS and BOT_GRID_QTY".to_string())),
+            },
+            StrategyKind::MarketMaker => MarketMakerStrategy::new(config.market_maker.clone().unwrap_or_else(MarketMakerConfig::standard))
+                .map_data(|s| Box::new(s) as Box<dyn Strategy>),
+            StrategyKind::Dca => match &config.dca {
+                Some(dca) => DcaStrategy::new(dca.clone()).map_data(|s| Box::new(s) as Box<dyn Strategy>),
+                None => fail(BotError::ConfigurationError("DCA strategy needs BOT_DCA_AMOUNT and BOT_DCA_BUDGET".to_string())),
+            },
+        }
+    }
+}
+
+/// Starting funds and fees for the paper exchange.
+#[derive(Debug, Clone)]
+pub struct PaperConfig {
+    pub quote_balance: f64, // In USDT
+    pub base_balance: f64,  // In the traded symbol's base asset, e.g. for market making
+    pub fee_rate: f64,
+}
+
 #[derive(Clone)]
 pub struct BotConfig {
     pub symbol: String,
     pub api_key: String,
     pub secret_key: String,
     pub strategy_risk_factor: f64,
+    pub regime_risk: Vec<(MarketRegime, RegimeAdjustment)>, // Overrides of `RegimeAdjustment::standard`
+    pub strategies: Vec<StrategyKind>, // More than one runs them as a composite
+    pub strategy_combine: CombineMode,  // How a composite merges its members' signals
+    pub rsi_smoothing: RsiSmoothing,    // RSI averaging for the breakout and mean-reversion strategies
+    pub grid: Option<GridConfig>,
+    pub market_maker: Option<MarketMakerConfig>, // Defaults to `MarketMakerConfig::standard()`
+    pub dca: Option<DcaConfig>,
+    pub paper: Option<PaperConfig>,     // Trade against the in-memory paper exchange
+    pub baseline_path: String, // Seasonal volume profile, persisted across restarts
+    pub snapshot_path: String, // Indicator / strategy state, persisted across restarts
+    pub snapshot_max_age_secs: u64, // Older snapshots are discarded in favour of a full warm-up
+    pub profile_bin: BinSize, // Price bin width of the session volume profile
+    pub decision_log_path: Option<String>, // JSON-lines record of every order and the signal behind it
+    pub watchlist: Vec<String>, // Extra symbols ranked by the scanner each cycle
+    pub scan_only: bool,        // Run the scanner without trading `symbol`
+    pub alerts: AlertConfig,
+    pub alert_expressions: Vec<AlertExpression>, // Parsed user-defined alert conditions
 }
 
 // --- Performance Metrics ---
@@ -95,6 +198,94 @@ impl PerformanceTracker {
     }
 }
 
+// --- Positions ---
+
+/// Net position in one symbol with average-cost accounting.
+/// Positive quantity is long, negative is short.
+#[derive(Debug, Clone)]
+pub struct Position {
+    pub symbol: String,
+    pub quantity: f64,
+    pub avg_price: f64,
+    pub realized_pnl: f64,
+    pub mark_price: f64,
+}
+
+impl Position {
+    pub fn unrealized_pnl(&self) -> f64 {
+        self.quantity * (self.mark_price - self.avg_price)
+    }
+}
+
+pub struct PositionBook {
+    positions: HashMap<String, Position>,
+}
+
+impl PositionBook {
+    pub fn new() -> Self {
+        PositionBook { positions: HashMap::new() }
+    }
+
+    /// Applies a fill: same-direction fills average in, opposite fills realize
+    /// PnL against the average price and may flip the position.
+    pub fn apply_fill(&mut self, symbol: &str, side: &OrderSide, quantity: f64, price: f64) {
+        let pos = self.positions.entry(symbol.to_string()).or_insert(Position {
+            symbol: symbol.to_string(),
+            quantity: 0.0,
+            avg_price: 0.0,
+            realized_pnl: 0.0,
+            mark_price: price,
+        });
+        let signed = if *side == OrderSide::Buy { quantity } else { -quantity };
+
+        if pos.quantity == 0.0 || pos.quantity.signum() == signed.signum() {
+            let total = pos.quantity + signed;
+            pos.avg_price = (pos.quantity.abs() * pos.avg_price + quantity * price) / total.abs();
+            pos.quantity = total;
+        } else {
+            let closing = quantity.min(pos.quantity.abs());
+            pos.realized_pnl += closing * (price - pos.avg_price) * pos.quantity.signum();
+            pos.quantity += signed;
+            if pos.quantity.abs() < 1e-12 {
+                pos.quantity = 0.0;
+                pos.avg_price = 0.0;
+            } else if pos.quantity.signum() == signed.signum() {
+                pos.avg_price = price; // Flipped through flat
+            }
+        }
+        pos.mark_price = price;
+    }
+
+    pub fn mark(&mut self, symbol: &str, price: f64) {
+        if let Some(pos) = self.positions.get_mut(symbol) {
+            pos.mark_price = price;
+        }
+    }
+
+    pub fn open_positions(&self) -> impl Iterator<Item = &Position> {
+        self.positions.values().filter(|p| p.quantity != 0.0)
+    }
+
+    pub fn get(&self, symbol: &str) -> Option<&Position> {
+        self.positions.get(symbol)
+    }
+
+    pub fn realized_pnl(&self) -> f64 {
+        self.positions.values().map(|p| p.realized_pnl).sum()
+    }
+
+    pub fn unrealized_pnl(&self) -> f64 {
+        self.positions.values().map(|p| p.unrealized_pnl()).sum()
+    }
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
 // --- The Engine ---
 
 pub struct TradingEngine {
@@ -103,28 +294,127 @@ pub struct TradingEngine {
     client: Arc<dyn ExchangeClient>,
     strategy: Box<dyn Strategy>,
     risk_manager: RiskManager,
+    volume_detector: VolumeSpikeDetector,
+    trade_stream: TradeStream,
+    flow: TradeFlowAggregator,
+    whale_detector: WhaleDetector,
+    recent_whales: VecDeque<WhaleAlert>,
+    scanner: Option<MarketScanner>,
+    alerts: AlertRouter,
+    expressions: ExpressionMonitor,
+    positions: PositionBook,
+    last_fill_id: Option<u64>, // Newest execution already booked
+    profile: VolumeProfileTracker,
+    candles: CandleAggregator,
+    regime: RegimeClassifier,
+    indicators: IndicatorPanel, // Indicator readings for the status report
+    manual_pause: bool, // Set by an operator; blocks automatic recovery
     metrics: PerformanceTracker,
 }
 
 impl TradingEngine {
-    pub fn new(config: BotConfig) -> Self {
+    /// Fails with a `ConfigurationError` when the configured strategy cannot be built.
+    pub fn new(config: BotConfig) -> MResult<Self> {
         // Factory pattern for initialization
-        let client = Arc::new(BinanceClient::new(&config.api_key, &config.secret_key));
+        let market: Arc<dyn ExchangeClient> = Arc::new(BinanceClient::new(&config.api_key, &config.secret_key));
+        Self::with_client(config, market)
+    }
+
+    /// Builds the engine on top of `market`; paper mode wraps it.
+    pub fn with_client(config: BotConfig, market: Arc<dyn ExchangeClient>) -> MResult<Self> {
+        let client: Arc<dyn ExchangeClient> = match &config.paper {
+            Some(paper) => {
+                let base = split_symbol(&config.symbol).map(|(base, _)| base).unwrap_or_default();
+                log_info(&format!("Paper trading with {:.2} USDT and {} {} (fee {:.2}%)",
+                    paper.quote_balance, paper.base_balance, base, paper.fee_rate * 100.0));
+                Arc::new(PaperExchange::new(market, paper.fee_rate)
+                    .with_balance("USDT", paper.quote_balance)
+                    .with_balance(&base, paper.base_balance))
+            }
+            None => market,
+        };
         
-        // Initializing the specific strategy implementation
-        let strategy = Box::new(VolumeBreakoutStrategy::new(2500.0)); // Min 2500 volume
+        // Initializing the configured strategy; several are combined with equal weight
+        let built: MResult<Box<dyn Strategy>> = match config.strategies.as_slice() {
+            [kind] => kind.build(&config),
+            kinds => kinds.iter()
+                .map(|k| k.build(&config).map_data(|strategy| CompositeMember::new(k.name(), 1.0, strategy)))
+                .collect::<MResult<Vec<_>>>()
+                .bind(|members| CompositeStrategy::new(config.strategy_combine, members))
+                .map_data(|composite| Box::new(composite) as Box<dyn Strategy>),
+        };
+        let strategy = built.catch(|e| fail(BotError::ConfigurationError(format!("Invalid strategy setup: {:?}", e))))?;
+        log_info(&format!("Strategy: {}", config.strategies.iter().map(|k| k.name()).collect::<Vec<_>>().join(" + ")));
+
+        // Volume spikes are scored against the last 60 observations
+        let mut volume_detector = VolumeSpikeDetector::new(60, SpikeThresholds::standard());
+
+        // Hour-of-day / weekday baselines, each completed hour weighted at 10%
+        let baseline = SeasonalBaseline::load(&config.baseline_path, 0.1)
+            .inspect_err(|e| log_info(&format!("Seasonal baseline unavailable, starting fresh: {:?}", e)))
+            .unwrap_or_else(|_| SeasonalBaseline::new(0.1));
+        volume_detector.set_seasonal_baseline(baseline);
+
+        // Aggressor-side flow over a rolling 60s window
+        let trade_stream = TradeStream::new(&config.symbol, 500);
+        let flow = TradeFlowAggregator::new(&config.symbol, 60_000);
+
+        // Whales: 50k notional outright, or the top 0.1% of the last 2000 prints.
+        // Same-side prints less than 250ms apart are merged into one sweep.
+        let whale_detector = WhaleDetector::new(&config.symbol, WhaleConfig {
+            min_notional: 50_000.0,
+            percentile: 0.999,
+            sample_size: 2000,
+            cluster_gap_ms: 250,
+        });
         
         // Initializing risk management with 2% risk per trade and 1.5% stop loss
-        let risk_manager = RiskManager::new(0.02, 0.015);
+        let mut risk_manager = RiskManager::new(0.02, 0.015);
+        for (regime, adjustment) in &config.regime_risk {
+            risk_manager.set_regime_adjustment(*regime, *adjustment)?;
+        }
+
+        // Watchlist scanner, only when there is something to scan
+        let scanner = if config.watchlist.is_empty() {
+            None
+        } else {
+            Some(MarketScanner::new(config.watchlist.clone(), 60))
+        };
 
-        TradingEngine {
+        let alerts = AlertRouter::from_config(&config.alerts);
+        log_info(&format!("Alert sinks: {}", alerts.sink_names().join(", ")));
+        let expressions = ExpressionMonitor::new(config.alert_expressions.clone());
+
+        // OHLCV bars on every supported timeframe; consumers pick the one they need
+        let candles = CandleAggregator::new(&config.symbol, &Timeframe::ALL);
+        let profile = VolumeProfileTracker::new(86_400, config.profile_bin);
+
+        let mut engine = TradingEngine {
             state: BotState::Initializing,
             config,
             client,
             strategy,
             risk_manager,
+            volume_detector,
+            trade_stream,
+            flow,
+            whale_detector,
+            recent_whales: VecDeque::new(),
+            scanner,
+            alerts,
+            expressions,
+            positions: PositionBook::new(),
+            last_fill_id: None,
+            // UTC daily sessions, bins 0.05% of the session's opening price wide
+            profile,
+            candles,
+            regime: RegimeClassifier::new(RegimeConfig::standard()),
+            indicators: IndicatorPanel::new(Timeframe::M5),
+            manual_pause: false,
             metrics: PerformanceTracker::new(),
-        }
+        };
+        engine.restore_snapshot();
+        unit(engine)
     }
 
     /// The primary execution cycle.
@@ -141,45 +431,118 @@ impl TradingEngine {
 
     // --- State Handlers ---
 
+    /// Moves the state machine and announces the change.
+    fn transition(&mut self, next: BotState) {
+        if next == self.state {
+            return;
+        }
+        let severity = match next {
+            BotState::Paused(_) | BotState::Terminating => AlertSeverity::Warning,
+            _ => AlertSeverity::Info,
+        };
+        self.alerts.route(&Alert::state_change(&self.state.to_string(), &next.to_string(), severity));
+        self.state = next;
+    }
+
     fn handle_init(&mut self) -> MResult<()> {
         log_info("Engine initializing... Verifying exchange connectivity.");
         
         self.client.check_connectivity()
             .bind(|latency| {
                 log_info(&format!("Connection OK. Latency: {}ms", latency));
-                self.state = BotState::Syncing;
+                self.transition(BotState::Syncing);
                 unit(())
             })
             .catch(|e| {
                 log_info(&format!("Init Failed: {:?}", e));
                 // In production we might retry or panic. Here we pause.
-                self.state = BotState::Paused("Connection Failure".to_string());
+                self.transition(BotState::Paused("Connection Failure".to_string()));
                 unit(())
             })
     }
 
     fn handle_sync(&mut self) -> MResult<()> {
-        // In a real bot, we would load historical candles here to warm up indicators.
-        // For this version, we'll simulate a warm-up by fetching a ticker.
         log_info("Syncing market data and warming up indicators...");
-        
-        self.client.fetch_ticker(&self.config.symbol)
+
+        self.warm_up()
+            .bind(|_| self.client.fetch_ticker(&self.config.symbol))
             .bind(|ticker| {
+                self.analyze_volume(&ticker)?;
                 self.strategy.process_tick(&ticker)?; // Feed initial data
                 log_info("Indicators warmed up.");
-                self.state = BotState::Trading;
+                self.transition(BotState::Trading);
                 unit(())
             })
     }
 
+    /// Replays exchange history so nothing starts trading cold:
+    /// - the regime classifier is rebuilt from its own timeframe's bars
+    /// - the strategy gets `warmup_bars` closed bars per subscribed timeframe
+    /// - the status report's indicator panel is rebuilt from 5m bars
+    /// - the volume window is primed with rolling 1h volumes built from 1m bars
+    /// - a fresh seasonal baseline is trained from a week of hourly bars
+    ///
+    /// User alert expressions are out of scope: their indicators count engine
+    /// polls, not bars, so replaying klines would mix units. They warm up live.
+    fn warm_up(&mut self) -> MResult<()> {
+        let symbol = self.config.symbol.clone();
+
+        let regime_bars = self.regime.warmup_bars();
+        let history = self.client.fetch_klines(&symbol, self.regime.timeframe(), regime_bars as u32)?;
+        self.regime.reset();
+        history.iter().for_each(|c| { self.regime.update(c); });
+        if let Some(reading) = self.regime.latest() {
+            log_info(&format!("Market regime after warm-up: {:?} (ADX {:.1})", reading.regime, reading.adx));
+            self.strategy.observe_regime(reading);
+        }
+
+        let panel_bars = self.client.fetch_klines(&symbol, self.indicators.timeframe(), self.indicators.warmup_bars() as u32)?;
+        self.indicators.warm_up(&panel_bars);
+
+        let bars = self.strategy.warmup_bars();
+        if bars > 0 {
+            for timeframe in self.strategy.timeframes() {
+                let history = self.client.fetch_klines(&symbol, timeframe, bars as u32)?;
+                log_info(&format!("Replaying {} {} bars into the strategy", history.len(), timeframe));
+                self.strategy.warm_up(&history);
+            }
+        }
+
+        let lookback = self.volume_detector.lookback();
+        let minutes = self.client.fetch_klines(&symbol, Timeframe::M1, (lookback + 59) as u32)?;
+        let rolling: Vec<f64> = minutes.windows(60)
+            .map(|hour| hour.iter().map(|c| c.volume).sum())
+            .collect();
+        self.volume_detector.prime(&rolling);
+
+        if self.volume_detector.seasonal_baseline().is_some_and(|b| b.is_empty()) {
+            let hours = self.client.fetch_klines(&symbol, Timeframe::H1, 168)?;
+            let samples: Vec<(u64, f64)> = hours.iter().map(|c| (c.open_time, c.volume)).collect();
+            if let Some(baseline) = self.volume_detector.seasonal_baseline_mut() {
+                baseline.train(&samples);
+                log_info(&format!("Seasonal baseline trained from {} hourly bars", samples.len()));
+            }
+        }
+        unit(())
+    }
+
     fn handle_paused(&mut self) -> MResult<()> {
+        if self.manual_pause {
+            return unit(()); // Waits for an explicit resume
+        }
         // Simple logic to attempt recovery every tick
         log_info("Bot is PAUSED. Attempting recovery...");
-        self.state = BotState::Initializing;
+        self.transition(BotState::Initializing);
         unit(())
     }
 
     fn handle_trading(&mut self) -> MResult<()> {
+        self.alerts.tick();
+        self.run_scanner();
+        if self.config.scan_only {
+            return unit(());
+        }
+
         // MONADIC TRADING PIPELINE
         // The core logic flow:
         // 1. Fetch Market Data -> 2. Strategy Analysis -> 3. Risk Calculation -> 4. Execution
@@ -189,12 +552,39 @@ impl TradingEngine {
         let pipeline = self.client.fetch_ticker(&symbol)
             // Step 1: Log price
             .inspect(|ticker| {
+                self.positions.mark(&ticker.symbol, ticker.price);
                 if ticker.timestamp % 10 == 0 { // Reduce log noise
                     println!(">>> [MARKET] {} | Price: {:.2} | Vol: {:.0}", ticker.symbol, ticker.price, ticker.volume_1h);
                 }
             })
             
-            // Step 2: Strategy Analysis
+            // Step 2: Volume Analysis & User Alerts
+            .bind(|ticker| {
+                self.analyze_volume(&ticker).map_data(|_| ticker)
+            })
+            .inspect(|ticker| {
+                if !self.config.watchlist.contains(&ticker.symbol) {
+                    self.evaluate_expressions(ticker);
+                }
+            })
+
+            // Step 3: Order Flow
+            .bind(|ticker| {
+                self.analyze_flow().map_data(|_| ticker)
+            })
+
+            // Step 3b: Order Book, only for strategies that read it
+            .bind(|ticker| {
+                self.analyze_book().map_data(|_| ticker)
+            })
+
+            // Step 3c: Own Orders (book new fills, then strategy-managed orders)
+            .inspect(|ticker| {
+                self.sync_fills();
+                self.manage_orders(ticker);
+            })
+
+            // Step 4: Strategy Analysis
             .bind(|ticker| {
                 // We map the strategy result. If None (No Signal), we stop the chain early via specific error or handle logic
                 // Here we return a tuple to keep ticker data for the next step
@@ -205,12 +595,16 @@ impl TradingEngine {
                 }
             })
 
-            // Step 3: Log Signal
+            // Step 5: Log Signal
             .inspect(|(_, signal)| {
                 log_info(&format!("SIGNAL DETECTED: {:?} [{}] Strength: {:.2}", signal.side, signal.reason, signal.strength));
+                if !signal.components.is_empty() {
+                    log_info(&format!("Strength breakdown: {}", format_components(&signal.components)));
+                }
+                self.alerts.route(&Alert::from_signal(signal));
             })
 
-            // Step 4: Risk Management & Balance Check
+            // Step 6: Risk Management & Balance Check
             .bind(|(ticker, signal)| {
                 // We need the balance to calculate position size
                 self.client.fetch_balance("USDT")
@@ -219,11 +613,14 @@ impl TradingEngine {
                     })
             })
 
-            // Step 5: Execution
+            // Step 7: Execution
             .bind(|instruction| {
                 self.execute_instruction(instruction)
             });
 
+        // Book anything this cycle's orders filled
+        self.sync_fills();
+
         // Pipeline Result Handling
         match pipeline {
             Ok(order_id) => {
@@ -237,37 +634,586 @@ impl TradingEngine {
             Err(e) => {
                 // Real error handling
                 log_info(&format!("Cycle Error: {:?}", e));
+                self.alerts.route(&Alert::from_error(&e, Some(&symbol)));
                 self.metrics.record_error();
                 Ok(()) // We return Ok to keep the loop running, unless critical
             }
         }
     }
 
-    fn execute_instruction(&self, instr: TradeInstruction) -> MResult<String> {
+    /// Scores the ticker's volume, shares the result with the strategy and
+    /// raises an alert for anything above normal.
+    fn analyze_volume(&mut self, ticker: &crate::exchange::Ticker) -> MResult<Option<VolumeSpike>> {
+        self.volume_detector.update(ticker)
+            .inspect(|spike| {
+                if let Some(spike) = spike {
+                    self.strategy.observe_volume(spike);
+                    if spike.is_spike() {
+                        self.alerts.route(&Alert::from_spike(spike));
+                    }
+                }
+            })
+    }
+
+    /// Refreshes the watchlist leaderboard. Scanner failures never block trading.
+    fn run_scanner(&mut self) {
+        if let Some(scanner) = self.scanner.as_mut() {
+            match scanner.scan(self.client.as_ref()) {
+                Ok(board) => {
+                    if let Some(top) = board.entries.first().filter(|e| e.severity > SpikeSeverity::Normal) {
+                        log_info(&format!("[SCANNER] Top mover {} | {} score {:.2} | {:+.2}%", top.symbol, top.severity, top.volume_score, top.price_change_pct));
+                    }
+                }
+                Err(e) => log_info(&format!("Scanner Error: {:?}", e)),
+            }
+        }
+
+        let tickers = self.scanner.as_ref().map(|s| s.latest_tickers().to_vec()).unwrap_or_default();
+        for ticker in &tickers {
+            self.evaluate_expressions(ticker);
+        }
+    }
+
+    /// Runs user-defined alert expressions for one symbol's tick.
+    fn evaluate_expressions(&mut self, ticker: &crate::exchange::Ticker) {
+        if self.expressions.is_empty() {
+            return;
+        }
+        for hit in self.expressions.evaluate(ticker) {
+            self.alerts.route(&Alert::from_expression(&hit));
+        }
+    }
+
+    /// Latest watchlist ranking, if the scanner is enabled.
+    pub fn leaderboard(&self) -> Option<&Leaderboard> {
+        self.scanner.as_ref().and_then(|s| s.leaderboard())
+    }
+
+    /// Pulls new trades into the flow aggregator and shares the window with the strategy.
+    fn analyze_flow(&mut self) -> MResult<FlowWindow> {
+        self.trade_stream.poll(self.client.as_ref())
+            .inspect(|trades| self.detect_whales(trades))
+            .inspect(|trades| self.update_profile(trades))
+            .inspect(|trades| self.update_candles(trades))
+            .bind(|trades| self.flow.ingest(&trades, now_secs() * 1000))
+            .inspect(|window| self.strategy.observe_flow(window))
+    }
+
+    /// Books executions since the last sync into positions and metrics and
+    /// hands them to the strategy. Failures are retried next tick.
+    fn sync_fills(&mut self) {
+        let fills = match self.client.fetch_fills(&self.config.symbol, self.last_fill_id) {
+            Ok(fills) => fills,
+            Err(e) => return log_info(&format!("Fill sync failed: {:?}", e)),
+        };
+        for fill in &fills {
+            log_info(&format!("[FILL] {:?} {:.6} {} @ {:.2} | fee {:.4} | {}s ago",
+                fill.side, fill.quantity, fill.symbol, fill.price, fill.fee, (now_secs() * 1000).saturating_sub(fill.timestamp_ms) / 1000));
+            self.positions.apply_fill(&fill.symbol, &fill.side, fill.quantity, fill.price);
+            self.metrics.record_trade(fill.notional());
+            self.strategy.observe_fill(fill);
+            self.last_fill_id = Some(fill.id);
+        }
+    }
+
+    /// Sends the orders the strategy manages itself and reports each outcome
+    /// back, with refusals as `Rejected` orders.
+    fn manage_orders(&mut self, ticker: &Ticker) {
+        for action in self.strategy.order_actions(ticker) {
+            match action {
+                OrderAction::Place(request) => {
+                    let order = self.client.place_order(&request).unwrap_or_else(|e| {
+                        log_info(&format!("Order {} refused: {:?}", request.client_id, e));
+                        Order::rejected(&request, &e, now_secs() * 1000)
+                    });
+                    self.strategy.observe_order(&order);
+                }
+                OrderAction::Cancel { order_id } => match self.client.cancel_order(&self.config.symbol, &order_id) {
+                    Ok(order) => self.strategy.observe_order(&order),
+                    // Usually filled in the meantime; the fill sync will report it
+                    Err(e) => log_info(&format!("Cancel {} failed: {:?}", order_id, e)),
+                },
+            }
+        }
+    }
+
+    /// Fetches the order book at the depth the strategy asked for, if any.
+    fn analyze_book(&mut self) -> MResult<()> {
+        let depth = self.strategy.book_depth();
+        if depth == 0 {
+            return unit(());
+        }
+        self.client.fetch_order_book(&self.config.symbol, depth)
+            .map_data(|book| self.strategy.observe_book(&book))
+    }
+
+    fn update_profile(&mut self, trades: &[crate::exchange::Trade]) {
+        self.profile.ingest(trades);
+        if let Some(summary) = self.profile.summary() {
+            self.strategy.observe_profile(&summary);
+        }
+    }
+
+    /// Builds bars from the new trades, closes any that ended during a quiet
+    /// spell, and hands the closed bars to the strategy if it subscribed.
+    fn update_candles(&mut self, trades: &[crate::exchange::Trade]) {
+        let mut closed = self.candles.ingest(trades);
+        closed.extend(self.candles.flush(now_secs()));
+
+        let subscribed = self.strategy.timeframes();
+        for candle in &closed {
+            // Label the bar first so the strategy sees the regime it closed in
+            if candle.timeframe == self.regime.timeframe() {
+                self.classify_regime(candle);
+            }
+            self.indicators.on_candle(candle);
+            if subscribed.contains(&candle.timeframe) {
+                self.strategy.observe_candle(candle);
+            }
+        }
+    }
+
+    fn classify_regime(&mut self, candle: &Candle) {
+        let previous = self.regime.latest().map(|r| r.regime);
+        if let Some(reading) = self.regime.update(candle).cloned() {
+            if let Some(previous) = previous.filter(|p| *p != reading.regime) {
+                log_info(&format!("[REGIME] {:?} -> {:?} (ADX {:.1}, slope {:+.3}%/bar, vol x{:.2})", previous, reading.regime, reading.adx, reading.slope_pct, reading.volatility_ratio));
+            }
+            self.strategy.observe_regime(&reading);
+        }
+    }
+
+    /// Regime label of the last classified bar.
+    pub fn regime(&self) -> Option<&RegimeReading> {
+        self.regime.latest()
+    }
+
+    /// The bar currently being built for `timeframe`.
+    pub fn current_candle(&self, timeframe: Timeframe) -> Option<&Candle> {
+        self.candles.builder(timeframe).and_then(|b| b.current())
+    }
+
+    /// Volume profile levels for the current session.
+    pub fn profile_summary(&self) -> Option<ProfileSummary> {
+        self.profile.summary()
+    }
+
+    fn detect_whales(&mut self, trades: &[crate::exchange::Trade]) {
+        let mut alerts = self.whale_detector.process(trades);
+        let now_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+        alerts.extend(self.whale_detector.flush(now_ms));
+
+        for alert in alerts {
+            self.alerts.route(&Alert::from_whale(&alert));
+            if self.recent_whales.len() >= 10 {
+                self.recent_whales.pop_front();
+            }
+            self.recent_whales.push_back(alert);
+        }
+    }
+
+    /// Most recent whale events, oldest first.
+    pub fn recent_whales(&self) -> impl Iterator<Item = &WhaleAlert> {
+        self.recent_whales.iter()
+    }
+
+    /// Current aggressor-side flow window.
+    pub fn flow_window(&self) -> FlowWindow {
+        self.flow.snapshot()
+    }
+
+    /// Latest volume analysis, for components that poll instead of subscribe.
+    pub fn last_volume_spike(&self) -> Option<&VolumeSpike> {
+        self.volume_detector.last()
+    }
+
+    fn execute_instruction(&mut self, instr: TradeInstruction) -> MResult<String> {
         println!("\n| $$$EXECUTING TRADE$$$");
         println!("| Symbol: {}", instr.symbol);
         println!("| Side:   {:?}", instr.side);
         println!("| Size:   {}", instr.amount);
         println!("| Price:  {:?}", instr.limit_price);
+        println!("| Why:    {}", instr.explanation.get("reason").and_then(|r| r.as_str()).unwrap_or("-"));
         println!("| -----------------------");
 
-        let order_type = if instr.limit_price.is_some() { OrderType::Limit } else { OrderType::Market };
-        
-        // Execute the trade via exchange client
-        self.client.execute_order(
-            &instr.symbol,
-            instr.side,
-            order_type,
-            instr.amount,
-            instr.limit_price
-        )
+        let request = OrderRequest {
+            client_id: format!("sig-{}", now_secs()),
+            symbol: instr.symbol.clone(),
+            side: instr.side.clone(),
+            order_type: if instr.limit_price.is_some() { OrderType::Limit } else { OrderType::Market },
+            quantity: instr.amount,
+            price: instr.limit_price,
+        };
+
+        // Execute the trade via exchange client; fills are booked by the fill sync
+        let result = self.client.place_order(&request)
+            .inspect(|order| self.strategy.observe_order(order))
+            .map_data(|order| order.id);
+
+        self.record_decision(&instr, &result);
+        result
+    }
+
+    /// Logs the order outcome with the full signal explanation, and appends
+    /// both to the decision log so a trade can be reconstructed after the fact.
+    fn record_decision(&self, instr: &TradeInstruction, result: &MResult<String>) {
+        let (status, detail) = match result {
+            Ok(order_id) => ("submitted", order_id.clone()),
+            Err(e) => ("rejected", format!("{:?}", e)),
+        };
+        log_info(&format!("ORDER {} {} | signal {}", status, detail, instr.explanation));
+
+        let path = match &self.config.decision_log_path {
+            Some(path) => path,
+            None => return,
+        };
+        let entry = JsonValue::object()
+            .with("timestamp", now_secs())
+            .with("status", status)
+            .with("detail", detail)
+            .with("symbol", instr.symbol.as_str())
+            .with("side", format!("{:?}", instr.side))
+            .with("amount", instr.amount)
+            .with("limit_price", instr.limit_price)
+            .with("stop_loss", instr.stop_loss)
+            .with("take_profit", instr.take_profit)
+            .with("signal", instr.explanation.clone());
+
+        let written = OpenOptions::new().create(true).append(true).open(path)
+            .and_then(|mut file| writeln!(file, "{}", entry));
+        if let Err(e) = written {
+            log_info(&format!("Decision log {}: {}", path, e));
+        }
+    }
+
+    // --- Operator Commands ---
+
+    /// Executes a chat command and returns the reply text.
+    pub fn execute_command(&mut self, command: &Command) -> String {
+        match command {
+            Command::Status => self.status_summary(),
+            Command::Help => Command::help_text().to_string(),
+            Command::Pause => {
+                if self.manual_pause {
+                    return "Already paused.".to_string();
+                }
+                self.manual_pause = true;
+                self.transition(BotState::Paused("Operator request".to_string()));
+                "Trading paused. Use /resume to continue.".to_string()
+            }
+            Command::Resume => {
+                if !self.manual_pause {
+                    return format!("Not paused (state: {}).", self.state);
+                }
+                self.manual_pause = false;
+                self.transition(BotState::Initializing);
+                "Resuming: re-initializing connectivity and indicators.".to_string()
+            }
+            Command::Positions => {
+                let lines: Vec<String> = self.positions.open_positions()
+                    .map(|p| format!("{} {:+.4} @ {:.2} (mark {:.2}, uPnL {:+.2})", p.symbol, p.quantity, p.avg_price, p.mark_price, p.unrealized_pnl()))
+                    .collect();
+                if lines.is_empty() { "No open positions.".to_string() } else { lines.join("\n") }
+            }
+            Command::Pnl => format!(
+                "Realized: {:+.2}\nUnrealized: {:+.2}\nTotal: {:+.2}",
+                self.positions.realized_pnl(),
+                self.positions.unrealized_pnl(),
+                self.positions.realized_pnl() + self.positions.unrealized_pnl()
+            ),
+            Command::Set { key, value } => {
+                let result = match key.as_str() {
+                    "risk" => self.risk_manager.set_risk_per_trade(*value),
+                    "stop" => self.risk_manager.set_stop_loss_pct(*value),
+                    other => fail(BotError::ConfigurationError(format!("Unknown setting '{}'. Use risk or stop.", other))),
+                };
+                match result {
+                    Ok(()) => {
+                        log_info(&format!("[COMMANDS] {} set to {}", key, value));
+                        format!("{} set to {}", key, value)
+                    }
+                    Err(e) => format!("Rejected: {}", e),
+                }
+            }
+        }
+    }
+
+    /// Compact, chat-friendly version of `report_status`.
+    pub fn status_summary(&self) -> String {
+        let mut lines = vec![
+            format!("State: {}", self.state),
+            format!("Symbol: {}", self.config.symbol),
+            format!("Risk/trade: {:.3} | Stop: {:.3}", self.risk_manager.risk_per_trade(), self.risk_manager.stop_loss_pct()),
+        ];
+        if let Some(spike) = self.volume_detector.last() {
+            lines.push(format!("Volume: {:.0} ({} x{:.2})", spike.volume, spike.severity, spike.relative_volume));
+        }
+        if let Some(p) = self.positions.get(&self.config.symbol).filter(|p| p.quantity != 0.0) {
+            lines.push(format!("Position: {:+.4} @ {:.2}", p.quantity, p.avg_price));
+        }
+        lines.push(format!(
+            "Uptime: {}s | Trades: {} | Failed: {}",
+            self.metrics.get_uptime_secs(), self.metrics.trades_executed, self.metrics.failed_trades
+        ));
+        lines.push(format!("PnL: {:+.2}", self.positions.realized_pnl() + self.positions.unrealized_pnl()));
+        lines.join("\n")
+    }
+
+    /// Persists learned state that should survive a restart.
+    pub fn save_state(&self) -> MResult<()> {
+        let baseline = match self.volume_detector.seasonal_baseline() {
+            Some(baseline) => baseline.save(&self.config.baseline_path)
+                .inspect(|_| log_info(&format!("Seasonal baseline saved to {}", self.config.baseline_path))),
+            None => unit(()),
+        };
+        baseline.bind(|_| self.save_snapshot())
+    }
+
+    fn save_snapshot(&self) -> MResult<()> {
+        match self.strategy.snapshot() {
+            Some(state) => StateSnapshot::new(&self.config.symbol, state, now_secs())
+                .save(&self.config.snapshot_path),
+            None => unit(()),
+        }
+    }
+
+    /// Picks up indicator state from the last run if it is fresh enough; the
+    /// sync warm-up then only replays the bars missed while the bot was down.
+    fn restore_snapshot(&mut self) {
+        let loaded = StateSnapshot::load(&self.config.snapshot_path, &self.config.symbol, self.config.snapshot_max_age_secs, now_secs());
+        let restored = match loaded {
+            Ok(Some(snapshot)) => self.strategy.restore(&snapshot.strategy)
+                .map_data(|_| now_secs().saturating_sub(snapshot.saved_at)),
+            Ok(None) => return,
+            Err(e) => Err(e),
+        };
+        match restored {
+            Ok(age) => log_info(&format!("Restored strategy state from {} ({}s old)", self.config.snapshot_path, age)),
+            Err(e) => log_info(&format!("Snapshot not restored, warming up from history: {:?}", e)),
+        }
     }
 
     /// Manually trigger a status report
     pub fn report_status(&self) {
         println!("\n=== ENGINE STATUS REPORT ===");
         println!("State: {}", self.state);
+        if let Some(spike) = self.last_volume_spike() {
+            println!("Volume: {:.0} ({} | x{:.2} avg | z {:.2})", spike.volume, spike.severity, spike.relative_volume, spike.z_score);
+            println!("Window: mean {:.0} (sd {:.0}) | median {:.0} | MAD {:.0} | robust z {:.2}", spike.mean, spike.std_dev, spike.median, spike.mad, spike.robust_score);
+            if let Some(seasonal) = &spike.seasonal {
+                println!("Seasonal: expected {:.0} for {} {:02}:00 | x{:.2} | z {:.2}", seasonal.expected, weekday_name(seasonal.weekday), seasonal.hour, seasonal.relative_volume, seasonal.z_score);
+            }
+        }
+        let flow = self.flow_window();
+        println!("Flow ({}s, {} trades): Buy {:.3} | Sell {:.3} | Delta {:+.3} | CVD {:+.3}", flow.window_ms / 1000, flow.trade_count, flow.buy_volume, flow.sell_volume, flow.delta, flow.cumulative_volume_delta);
+        let dominant = flow.dominant_side(0.6).map_or("balanced".to_string(), |side| format!("{:?}-led", side));
+        println!("Flow Notional: Buy {:.2} | Sell {:.2} | {}", flow.buy_notional, flow.sell_notional, dominant);
+        if let Some(whale) = self.recent_whales().last() {
+            println!("Last Whale: {}", whale);
+        }
+        if let Some(p) = self.profile_summary() {
+            let fmt_levels = |levels: &[f64]| levels.iter().map(|l| format!("{:.2}", l)).collect::<Vec<_>>().join(", ");
+            println!("Profile: POC {:.2} | VA {:.2} - {:.2} | Vol {:.3}", p.point_of_control, p.value_area_low, p.value_area_high, p.total_volume);
+            println!("Nodes: HVN [{}] | LVN [{}]", fmt_levels(&p.high_volume_nodes), fmt_levels(&p.low_volume_nodes));
+            if let Some(bar) = self.current_candle(Timeframe::M1) {
+                println!("Price {:.2}: {:?} the value area", bar.close, p.locate(bar.close));
+            }
+        }
+        if let Some(session) = self.profile.current() {
+            println!("Profile Bins: {} of {:.4} | Vol {:.3}", session.levels().len(), session.bin_size(), session.total_volume());
+        }
+        if let Some(p) = self.profile.previous().and_then(|session| session.summary()) {
+            println!("Prior Session: POC {:.2} | VA {:.2} - {:.2}", p.point_of_control, p.value_area_low, p.value_area_high);
+        }
+        if let Some(r) = self.regime() {
+            println!("Regime: {:?} | ADX {:.1} (+DI {:.1} / -DI {:.1}) | slope {:+.3}%/bar | vol {:.5} (x{:.2}) | bar {}s old", r.regime, r.adx, r.plus_di, r.minus_di, r.slope_pct, r.volatility, r.volatility_ratio, now_secs().saturating_sub(r.timestamp));
+        }
+        if let Some(c) = self.current_candle(Timeframe::M5) {
+            println!("Candle (5m): O {:.2} H {:.2} L {:.2} C {:.2} | Vol {:.3} | {} trades", c.open, c.high, c.low, c.close, c.volume, c.trade_count);
+        }
+        if let Some(builder) = self.candles.builder(Timeframe::M1) {
+            println!("Bars (1m): {} closed | {} late trades dropped", builder.history().count(), builder.late_trades());
+        }
+        if let Some(line) = self.indicators.summary() {
+            println!("Indicators ({}): {}", self.indicators.timeframe(), line);
+        }
+        if let Some(line) = self.strategy.status_line() {
+            println!("Strategy: {}", line);
+        }
+        if let Ok(orders) = self.client.fetch_open_orders(&self.config.symbol) {
+            let working: Vec<String> = orders.iter()
+                .filter(|o| o.status.is_open())
+                .map(|o| format!("{:?} {:.6} @ {:.2}", o.side, o.remaining(), o.price.unwrap_or(0.0)))
+                .collect();
+            if !working.is_empty() {
+                println!("Open Orders: {}", working.join(", "));
+            }
+        }
+        if let (Some(scanner), Some(board)) = (self.scanner.as_ref(), self.leaderboard()) {
+            println!("Scanner: {} symbols | last scan {}s ago", scanner.watchlist().len(), now_secs().saturating_sub(board.timestamp));
+            board.print(5);
+        }
+        if let Some(rules) = self.alerts.rules() {
+            let delivered = rules.audit_trail().filter(|r| r.decision.is_delivered()).count();
+            let held = rules.audit_trail().count() - delivered;
+            println!("Alerts: {} delivered | {} held back by the rules", delivered, held);
+        }
         self.metrics.print_summary();
         println!("============================");
     }
 }
+
+// =================================================================================
+// UNIT TESTS
+// =================================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::Trade;
+    use crate::exchange::stub::StubExchange;
+    use crate::alerts::AlertConfig;
+
+    /// +0.30 / -0.20 per bar: rising, with an RSI near 60.
+    fn price(index: u64) -> f64 {
+        100.0 + 0.05 * index as f64 + (index % 2) as f64 * 0.25
+    }
+
+    /// Exchange stand-in with a steady, choppy uptrend on every timeframe.
+    /// The first ticker is quiet; later ones carry a volume spike.
+    fn market(history: bool) -> StubExchange {
+        let now = now_secs();
+        // Open time of the 1m bar the live trades fall into (closed by `now`)
+        let live_bar = Timeframe::M1.bucket(now) - 60;
+        let last = price(live_bar / 60);
+        StubExchange::new()
+            .with_balance(100_000.0)
+            .with_ticker_feed(move |symbol, served| Some(Ticker {
+                symbol: symbol.to_string(),
+                price: last,
+                volume_24h: 16_000.0,
+                volume_1h: if served == 1 { 660.0 } else { 5_000.0 },
+                open: 100.0,
+                high: last,
+                low: 100.0,
+                bid: last - 0.01,
+                ask: last + 0.01,
+                timestamp: now + served,
+            }))
+            .with_trades((1..=3).map(|id| Trade {
+                symbol: "MONUSDT".to_string(),
+                id,
+                price: last,
+                quantity: 1.0,
+                aggressor: OrderSide::Buy,
+                timestamp_ms: (live_bar + id * 10) * 1000,
+            }).collect())
+            .with_klines(move |symbol, interval, limit| {
+                if !history {
+                    return Vec::new();
+                }
+                let step = interval.secs();
+                let last = interval.bucket(live_bar) / step - 1;
+                (last + 1 - limit as u64..=last).map(|k| Candle {
+                    symbol: symbol.to_string(),
+                    timeframe: interval,
+                    open_time: k * step,
+                    open: price(k - 1),
+                    high: price(k) + 0.1,
+                    low: price(k) - 0.3,
+                    close: price(k),
+                    volume: (10 + k % 3) as f64 * (step / 60) as f64,
+                    trade_count: 10,
+                }).collect()
+            })
+    }
+
+    fn config(tag: &str) -> BotConfig {
+        let scratch = |name: &str| std::env::temp_dir()
+            .join(format!("bot-test-{}-{}-{}", std::process::id(), tag, name))
+            .to_string_lossy()
+            .into_owned();
+        BotConfig {
+            symbol: "MONUSDT".to_string(),
+            api_key: String::new(),
+            secret_key: String::new(),
+            strategy_risk_factor: 1.0,
+            regime_risk: Vec::new(),
+            strategies: vec![StrategyKind::Breakout],
+            strategy_combine: CombineMode::Any,
+            rsi_smoothing: RsiSmoothing::Wilder,
+            grid: None,
+            market_maker: None,
+            dca: None,
+            paper: None,
+            baseline_path: scratch("baseline.txt"),
+            snapshot_path: scratch("snapshot.json"),
+            snapshot_max_age_secs: 900,
+            profile_bin: BinSize::Relative(0.0005),
+            decision_log_path: None,
+            watchlist: Vec::new(),
+            scan_only: false,
+            alerts: AlertConfig {
+                stdout_min: AlertSeverity::Critical,
+                jsonl_path: None,
+                jsonl_min: AlertSeverity::Critical,
+                webhook_url: None,
+                webhook_min: AlertSeverity::Critical,
+                telegram_api_base: String::new(),
+                telegram_token: None,
+                telegram_chat_id: None,
+                telegram_min: AlertSeverity::Critical,
+                rules: None,
+            },
+            alert_expressions: Vec::new(),
+        }
+    }
+
+    /// Runs init, sync and the first trading tick; returns the orders placed.
+    fn first_live_tick(tag: &str, market: StubExchange) -> Vec<OrderRequest> {
+        let market = Arc::new(market);
+        let mut engine = TradingEngine::with_client(config(tag), market.clone()).unwrap();
+        engine.tick().unwrap();
+        engine.tick().unwrap();
+        assert_eq!(engine.state, BotState::Trading);
+        engine.tick().unwrap();
+
+        market.placed()
+    }
+
+    #[test]
+    fn test_warm_up_lets_strategy_signal_on_first_live_tick() {
+        let orders = first_live_tick("warm", market(true));
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].side, OrderSide::Buy);
+
+        // Without history the 20-bar average is still cold on the same tick
+        assert!(first_live_tick("cold", market(false)).is_empty());
+    }
+
+    #[test]
+    fn test_entry_goes_through_when_price_moves_before_the_order() {
+        // The price ticks up 5 between the ticker read and the order arriving,
+        // which a limit at the ticker price would not survive
+        let orders = first_live_tick("drift", market(true).with_drift(5.0));
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].side, OrderSide::Buy);
+        assert!(matches!(orders[0].order_type, OrderType::Market));
+        assert_eq!(orders[0].price, None);
+    }
+
+    #[test]
+    fn test_unbuildable_strategy_refuses_to_start() {
+        let market = Arc::new(market(false));
+        // A grid without its levels must not quietly trade as something else
+        let mut grid = config("nogrid");
+        grid.strategies = vec![StrategyKind::Grid];
+        match TradingEngine::with_client(grid, market) {
+            Err(BotError::ConfigurationError(msg)) => assert!(msg.contains("BOT_GRID_LOWER")),
+            Err(e) => panic!("expected a configuration error, got {:?}", e),
+            Ok(_) => panic!("engine started without a grid"),
+        }
+    }
+}
@@ -0,0 +1,347 @@
+// =================================================================================
+// MODULE: Candle Aggregation
+// DESCRIPTION:
+// Builds OHLCV bars from the trade feed so indicator periods mean "N minutes"
+// instead of "N polls". Several timeframes are maintained side by side and
+// consumers subscribe to the one they need.
+//
+// Key components:
+// - Timeframes: 1m / 5m / 15m / 1h
+// - Gap filling: periods without trades become flat, zero-volume bars
+// - Late trades: folded into the already closed bar they belong to
+// - Per-indicator timeframe subscriptions (`TimeframeIndicator`)
+// =================================================================================
+
+use crate::monad::MResult;
+use crate::exchange::Trade;
+use crate::trader::Indicator;
+use crate::json::JsonValue;
+use std::collections::VecDeque;
+use std::fmt::Display;
+
+/// Closed bars retained per timeframe.
+const HISTORY_CAPACITY: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Timeframe {
+    M1,
+    M5,
+    M15,
+    H1,
+}
+
+impl Timeframe {
+    pub const ALL: [Timeframe; 4] = [Timeframe::M1, Timeframe::M5, Timeframe::M15, Timeframe::H1];
+
+    pub fn secs(&self) -> u64 {
+        match self {
+            Timeframe::M1 => 60,
+            Timeframe::M5 => 300,
+            Timeframe::M15 => 900,
+            Timeframe::H1 => 3_600,
+        }
+    }
+
+    /// Start of the bar containing `timestamp` (unix seconds).
+    pub fn bucket(&self, timestamp: u64) -> u64 {
+        timestamp - timestamp % self.secs()
+    }
+}
+
+impl Display for Timeframe {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Timeframe::M1 => write!(f, "1m"),
+            Timeframe::M5 => write!(f, "5m"),
+            Timeframe::M15 => write!(f, "15m"),
+            Timeframe::H1 => write!(f, "1h"),
+        }
+    }
+}
+
+/// One OHLCV bar.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candle {
+    pub symbol: String,
+    pub timeframe: Timeframe,
+    pub open_time: u64, // Unix seconds, inclusive
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+    pub trade_count: u32,
+}
+
+impl Candle {
+    fn start(symbol: &str, timeframe: Timeframe, open_time: u64, price: f64, volume: f64) -> Self {
+        Candle {
+            symbol: symbol.to_string(),
+            timeframe,
+            open_time,
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume,
+            trade_count: if volume > 0.0 { 1 } else { 0 },
+        }
+    }
+
+    /// A zero-volume bar carrying the previous close across a quiet period.
+    fn flat(symbol: &str, timeframe: Timeframe, open_time: u64, price: f64) -> Self {
+        Candle::start(symbol, timeframe, open_time, price, 0.0)
+    }
+
+    pub fn close_time(&self) -> u64 {
+        self.open_time + self.timeframe.secs()
+    }
+
+    /// Typical price (H+L+C)/3, used by volume-weighted indicators.
+    pub fn typical_price(&self) -> f64 {
+        (self.high + self.low + self.close) / 3.0
+    }
+
+    fn apply(&mut self, price: f64, volume: f64) {
+        self.high = self.high.max(price);
+        self.low = self.low.min(price);
+        self.close = price;
+        self.volume += volume;
+        self.trade_count += 1;
+    }
+
+    /// Folds a late trade in without moving the close, which was already final.
+    fn apply_late(&mut self, price: f64, volume: f64) {
+        self.high = self.high.max(price);
+        self.low = self.low.min(price);
+        self.volume += volume;
+        self.trade_count += 1;
+    }
+}
+
+// --- Builder ---
+
+/// Aggregates observations into bars of a single timeframe.
+pub struct CandleBuilder {
+    symbol: String,
+    timeframe: Timeframe,
+    current: Option<Candle>,
+    history: VecDeque<Candle>,
+    late_trades: u64,
+}
+
+impl CandleBuilder {
+    pub fn new(symbol: &str, timeframe: Timeframe) -> Self {
+        CandleBuilder {
+            symbol: symbol.to_string(),
+            timeframe,
+            current: None,
+            history: VecDeque::with_capacity(HISTORY_CAPACITY),
+            late_trades: 0,
+        }
+    }
+
+    /// Adds one trade at `timestamp` (unix seconds). Returns bars closed by it,
+    /// including flat bars for any periods skipped since the last trade.
+    pub fn add(&mut self, price: f64, volume: f64, timestamp: u64) -> Vec<Candle> {
+        let bucket = self.timeframe.bucket(timestamp);
+
+        let current_open = match &self.current {
+            None => {
+                self.current = Some(Candle::start(&self.symbol, self.timeframe, bucket, price, volume));
+                return Vec::new();
+            }
+            Some(c) => c.open_time,
+        };
+
+        if bucket == current_open {
+            self.current.as_mut().unwrap().apply(price, volume);
+            return Vec::new();
+        }
+
+        if bucket < current_open {
+            // Late trade: amend the closed bar it belongs to, if we still have it
+            self.late_trades += 1;
+            if let Some(bar) = self.history.iter_mut().rev().find(|c| c.open_time == bucket) {
+                bar.apply_late(price, volume);
+            }
+            return Vec::new();
+        }
+
+        let closed = self.roll_to(bucket);
+        self.current = Some(Candle::start(&self.symbol, self.timeframe, bucket, price, volume));
+        closed
+    }
+
+    /// Closes bars whose period ended before `now` even if no trade arrived,
+    /// so quiet markets still produce (flat) bars on time.
+    pub fn flush(&mut self, now: u64) -> Vec<Candle> {
+        let bucket = self.timeframe.bucket(now);
+        match &self.current {
+            Some(c) if c.open_time < bucket => {
+                let close = c.close;
+                let closed = self.roll_to(bucket);
+                // Opens flat at the last close; the next trade extends it
+                self.current = Some(Candle::flat(&self.symbol, self.timeframe, bucket, close));
+                closed
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Closes the current bar and emits flat bars up to (excluding) `bucket`.
+    fn roll_to(&mut self, bucket: u64) -> Vec<Candle> {
+        let mut closed = Vec::new();
+        if let Some(bar) = self.current.take() {
+            let mut next_open = bar.close_time();
+            let last_close = bar.close;
+            closed.push(bar);
+            while next_open < bucket {
+                closed.push(Candle::flat(&self.symbol, self.timeframe, next_open, last_close));
+                next_open += self.timeframe.secs();
+            }
+        }
+        for bar in &closed {
+            if self.history.len() >= HISTORY_CAPACITY {
+                self.history.pop_front();
+            }
+            self.history.push_back(bar.clone());
+        }
+        closed
+    }
+
+    /// Closed bars, oldest first.
+    pub fn history(&self) -> impl Iterator<Item = &Candle> {
+        self.history.iter()
+    }
+
+    /// The bar still being built.
+    pub fn current(&self) -> Option<&Candle> {
+        self.current.as_ref()
+    }
+
+    pub fn late_trades(&self) -> u64 {
+        self.late_trades
+    }
+}
+
+// --- Multi-timeframe Aggregator ---
+
+/// Runs one `CandleBuilder` per timeframe for a symbol.
+pub struct CandleAggregator {
+    builders: Vec<CandleBuilder>,
+}
+
+impl CandleAggregator {
+    pub fn new(symbol: &str, timeframes: &[Timeframe]) -> Self {
+        CandleAggregator {
+            builders: timeframes.iter().map(|tf| CandleBuilder::new(symbol, *tf)).collect(),
+        }
+    }
+
+    /// Feeds trades (oldest first) and returns every bar they closed,
+    /// shortest timeframe first within each trade.
+    pub fn ingest(&mut self, trades: &[Trade]) -> Vec<Candle> {
+        let mut closed = Vec::new();
+        for trade in trades {
+            for builder in self.builders.iter_mut() {
+                closed.extend(builder.add(trade.price, trade.quantity, trade.timestamp_ms / 1000));
+            }
+        }
+        closed
+    }
+
+    pub fn flush(&mut self, now: u64) -> Vec<Candle> {
+        self.builders.iter_mut().flat_map(|b| b.flush(now)).collect()
+    }
+
+    pub fn builder(&self, timeframe: Timeframe) -> Option<&CandleBuilder> {
+        self.builders.iter().find(|b| b.timeframe == timeframe)
+    }
+}
+
+// --- Subscriptions ---
+
+/// Binds an indicator to a timeframe.
+/// With `None` the indicator keeps its legacy per-tick behaviour; with a
+/// timeframe it only advances on closed bars of that timeframe.
+pub struct TimeframeIndicator<I: Indicator> {
+    timeframe: Option<Timeframe>,
+    inner: I,
+}
+
+impl<I: Indicator> TimeframeIndicator<I> {
+    pub fn new(inner: I, timeframe: Option<Timeframe>) -> Self {
+        TimeframeIndicator { timeframe, inner }
+    }
+
+    /// Returns true if the indicator consumed the tick.
+    pub fn on_tick(&mut self, price: f64) -> bool {
+        if self.timeframe.is_some() {
+            return false;
+        }
+        self.inner.update(price);
+        true
+    }
+
+    /// Returns true if the indicator consumed the bar.
+    pub fn on_candle(&mut self, candle: &Candle) -> bool {
+        if self.timeframe != Some(candle.timeframe) {
+            return false;
+        }
+        self.inner.update_candle(candle);
+        true
+    }
+
+    pub fn value(&self) -> Option<f64> {
+        self.inner.value()
+    }
+
+    pub fn inner(&self) -> &I {
+        &self.inner
+    }
+
+    pub fn reset(&mut self) {
+        self.inner.reset();
+    }
+
+    pub fn snapshot(&self) -> Option<JsonValue> {
+        self.inner.snapshot()
+    }
+
+    pub fn restore(&mut self, state: &JsonValue) -> MResult<()> {
+        self.inner.restore(state)
+    }
+}
+
+// =================================================================================
+// UNIT TESTS
+// =================================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gap_filling_and_late_trades() {
+        let mut builder = CandleBuilder::new("BTCUSDT", Timeframe::M1);
+        assert!(builder.add(100.0, 1.0, 60).is_empty());
+        assert!(builder.add(102.0, 2.0, 90).is_empty());
+
+        // Jump two minutes ahead: the 60s bar closes and the 120s bar is flat
+        let closed = builder.add(101.0, 1.0, 190);
+        assert_eq!(closed.len(), 2);
+        assert_eq!((closed[0].open, closed[0].high, closed[0].close, closed[0].volume), (100.0, 102.0, 102.0, 3.0));
+        assert_eq!((closed[1].open_time, closed[1].close, closed[1].volume), (120, 102.0, 0.0));
+
+        // A late print for the first bar widens it without changing its close
+        assert!(builder.add(99.0, 0.5, 75).is_empty());
+        let first = builder.history().next().unwrap();
+        assert_eq!((first.low, first.close, first.volume), (99.0, 102.0, 3.5));
+        assert_eq!(builder.late_trades(), 1);
+
+        // Quiet market: flushing closes the open bar on time
+        let flushed = builder.flush(250);
+        assert_eq!(flushed.len(), 1);
+        assert_eq!(flushed[0].open_time, 180);
+    }
+}
@@ -0,0 +1,263 @@
+// =================================================================================
+// MODULE: Chat Command Interface
+// DESCRIPTION:
+// Lets operators control the engine from a Telegram chat. A background thread
+// long-polls a Telegram-Bot-API-compatible `getUpdates` endpoint, drops
+// messages from chats that are not explicitly allowed, parses commands and
+// hands them to the main loop over a channel. The main loop executes them
+// against the `TradingEngine` and replies through `sendMessage`.
+//
+// Supported commands:
+//   /status  /pause  /resume  /positions  /pnl  /set <risk|stop> <value>  /help
+// =================================================================================
+
+use crate::monad::{MResult, unit, fail, BotError, Bind, log_info};
+use crate::json::JsonValue;
+use crate::http;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+
+/// Extra time on top of the long-poll window before the HTTP read gives up.
+const POLL_GRACE: Duration = Duration::from_secs(5);
+
+/// Back-off after a failed poll so a dead endpoint does not spin the thread.
+const ERROR_BACKOFF: Duration = Duration::from_secs(5);
+
+// --- Commands ---
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Status,
+    Pause,
+    Resume,
+    Positions,
+    Pnl,
+    Set { key: String, value: f64 },
+    Help,
+}
+
+impl Command {
+    /// Parses chat text such as `/set risk 0.01` or `/status@MyBot`. A typo is
+    /// not an engine error: the `Err` is the reply for the chat, usage included.
+    pub fn parse(text: &str) -> Result<Command, String> {
+        let usage = |problem: String| Err(format!("{}\n\n{}", problem, Command::help_text()));
+        let mut parts = text.split_whitespace();
+        let head = parts.next().unwrap_or("");
+        let name = match head.strip_prefix('/') {
+            Some(n) => n.split('@').next().unwrap_or("").to_ascii_lowercase(),
+            None => return usage("Commands start with '/'.".to_string()),
+        };
+
+        let command = match name.as_str() {
+            "status" => Command::Status,
+            "pause" => Command::Pause,
+            "resume" => Command::Resume,
+            "positions" => Command::Positions,
+            "pnl" => Command::Pnl,
+            "help" | "start" => Command::Help,
+            "set" => {
+                let key = parts.next().map(|k| k.to_ascii_lowercase());
+                let raw = parts.next();
+                match (key, raw) {
+                    (Some(key), Some(raw)) => match raw.parse::<f64>() {
+                        Ok(value) if value.is_finite() => Command::Set { key, value },
+                        _ => return usage(format!("'{}' is not a number.", raw)),
+                    },
+                    _ => return usage("/set needs a setting and a value.".to_string()),
+                }
+            }
+            other => return usage(format!("Unknown command /{}.", other)),
+        };
+        Ok(command)
+    }
+
+    pub fn help_text() -> &'static str {
+        "/status - engine state and metrics\n\
+         /pause - stop trading until /resume\n\
+         /resume - re-initialize and resume trading\n\
+         /positions - open positions\n\
+         /pnl - realized and unrealized PnL\n\
+         /set risk <fraction> - risk per trade, e.g. 0.01\n\
+         /set stop <fraction> - stop-loss distance, e.g. 0.015"
+    }
+}
+
+/// A command from an authorized chat, ready for the engine, or the reply
+/// explaining why it could not be parsed.
+#[derive(Debug, Clone)]
+pub struct IncomingCommand {
+    pub chat_id: i64,
+    pub command: Result<Command, String>,
+}
+
+// --- Telegram Transport ---
+
+#[derive(Debug, Clone)]
+pub struct CommandConfig {
+    pub api_base: String,
+    pub token: String,
+    pub allowed_chat_ids: Vec<i64>,
+    pub poll_timeout_secs: u64,
+}
+
+/// Telegram Bot API client for `getUpdates` / `sendMessage`.
+pub struct TelegramCommandClient {
+    config: CommandConfig,
+    offset: i64,
+}
+
+impl TelegramCommandClient {
+    pub fn new(mut config: CommandConfig) -> Self {
+        config.api_base = config.api_base.trim_end_matches('/').to_string();
+        TelegramCommandClient { config, offset: 0 }
+    }
+
+    fn method_url(&self, method: &str) -> String {
+        format!("{}/bot{}/{}", self.config.api_base, self.config.token, method)
+    }
+
+    /// One long-poll round. Acknowledges every update (so it is not redelivered)
+    /// but only returns commands from allowed chats.
+    pub fn poll(&mut self) -> MResult<Vec<IncomingCommand>> {
+        let url = format!("{}?offset={}&timeout={}", self.method_url("getUpdates"), self.offset, self.config.poll_timeout_secs);
+        let timeout = Duration::from_secs(self.config.poll_timeout_secs) + POLL_GRACE;
+
+        http::get(&url, timeout)
+            .bind(|resp| {
+                if resp.is_success() {
+                    JsonValue::parse(&resp.body)
+                } else {
+                    fail(BotError::NetworkFailure(format!("getUpdates responded {}", resp.status)))
+                }
+            })
+            .bind(|body| {
+                if body.get("ok").and_then(|v| v.as_bool()) != Some(true) {
+                    return fail(BotError::ExchangeError(format!("getUpdates failed: {}", body)));
+                }
+                let updates = body.get("result").and_then(|r| r.as_array()).unwrap_or(&[]);
+                unit(self.collect_commands(updates))
+            })
+    }
+
+    fn collect_commands(&mut self, updates: &[JsonValue]) -> Vec<IncomingCommand> {
+        let mut commands = Vec::new();
+        for update in updates {
+            if let Some(id) = update.get("update_id").and_then(|v| v.as_i64()) {
+                self.offset = self.offset.max(id + 1);
+            }
+            let chat_id = update.path(&["message", "chat", "id"]).and_then(|v| v.as_i64());
+            let text = update.path(&["message", "text"]).and_then(|v| v.as_str());
+
+            match (chat_id, text) {
+                (Some(chat_id), Some(text)) if self.is_allowed(chat_id) => {
+                    commands.push(IncomingCommand { chat_id, command: Command::parse(text) });
+                }
+                (Some(chat_id), Some(_)) => {
+                    log_info(&format!("[COMMANDS] Ignoring message from unauthorized chat {}", chat_id));
+                }
+                _ => {} // Edits, joins, stickers...
+            }
+        }
+        commands
+    }
+
+    pub fn is_allowed(&self, chat_id: i64) -> bool {
+        self.config.allowed_chat_ids.contains(&chat_id)
+    }
+
+    pub fn reply(&self, chat_id: i64, text: &str) -> MResult<()> {
+        let payload = JsonValue::object()
+            .with("chat_id", JsonValue::Number(chat_id as f64))
+            .with("text", text);
+        http::post_json(&self.method_url("sendMessage"), &payload.to_string(), POLL_GRACE)
+            .bind(|resp| {
+                if resp.is_success() {
+                    unit(())
+                } else {
+                    fail(BotError::NetworkFailure(format!("sendMessage responded {}", resp.status)))
+                }
+            })
+    }
+}
+
+/// Starts the long-poll thread. Commands arrive on the returned receiver;
+/// replies go out through the returned client (which never polls).
+pub fn spawn_listener(config: CommandConfig) -> (Receiver<IncomingCommand>, TelegramCommandClient) {
+    let (tx, rx): (Sender<IncomingCommand>, Receiver<IncomingCommand>) = mpsc::channel();
+    let replier = TelegramCommandClient::new(config.clone());
+    let mut poller = TelegramCommandClient::new(config);
+
+    thread::spawn(move || loop {
+        match poller.poll() {
+            Ok(commands) => {
+                for command in commands {
+                    if tx.send(command).is_err() {
+                        return; // Main loop is gone
+                    }
+                }
+            }
+            Err(e) => {
+                log_info(&format!("[COMMANDS] Poll failed: {:?}", e));
+                thread::sleep(ERROR_BACKOFF);
+            }
+        }
+    });
+
+    (rx, replier)
+}
+
+// =================================================================================
+// UNIT TESTS
+// =================================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    #[test]
+    fn test_command_parsing() {
+        assert_eq!(Command::parse("/status").unwrap(), Command::Status);
+        assert_eq!(Command::parse("/PAUSE@VolumeBot").unwrap(), Command::Pause);
+        assert_eq!(Command::parse("/set risk 0.01").unwrap(), Command::Set { key: "risk".to_string(), value: 0.01 });
+        // Mistakes are answered with what went wrong and the usage
+        let reply = Command::parse("/set risk lots").unwrap_err();
+        assert!(reply.starts_with("'lots' is not a number.") && reply.ends_with(Command::help_text()), "{}", reply);
+        assert!(Command::parse("/launch").unwrap_err().starts_with("Unknown command /launch."));
+        assert!(Command::parse("status").unwrap_err().contains("/set risk <fraction>"));
+    }
+
+    #[test]
+    fn test_poll_filters_unauthorized_chats() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 2048];
+            let n = stream.read(&mut buf).unwrap();
+            let body = r#"{"ok":true,"result":[
+                {"update_id":41,"message":{"chat":{"id":-1001},"text":"/pause"}},
+                {"update_id":42,"message":{"chat":{"id":777},"text":"/resume"}}
+            ]}"#;
+            let reply = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}", body.len(), body);
+            stream.write_all(reply.as_bytes()).unwrap();
+            String::from_utf8_lossy(&buf[..n]).to_string()
+        });
+
+        let mut client = TelegramCommandClient::new(CommandConfig {
+            api_base: base,
+            token: "123:ABC".to_string(),
+            allowed_chat_ids: vec![-1001],
+            poll_timeout_secs: 1,
+        });
+        let commands = client.poll().unwrap();
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("GET /bot123:ABC/getUpdates?offset=0&timeout=1 HTTP/1.1"));
+        assert_eq!(commands.len(), 1);
+        assert_eq!(commands[0].chat_id, -1001);
+        assert_eq!(commands[0].command, Ok(Command::Pause));
+        assert_eq!(client.offset, 43);
+    }
+}
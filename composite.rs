@@ -0,0 +1,445 @@
+// =================================================================================
+// MODULE: Composite Strategy
+// DESCRIPTION:
+// Runs several strategies on the same tick and combines their signals into one,
+// so setups can be layered (e.g. a volume breakout confirmed by a trend filter)
+// without writing a new strategy for every combination.
+//
+// Every member sees every engine hook and is evaluated on every tick, even when
+// the outcome is already decided, so no member's indicators fall behind.
+//
+// Combination modes (w = member weight, s = member strength, W = total weight):
+//
+//   mode           fires when                                strength
+//   WeightedVote   (w_side - w_opposite) / W >= threshold     (Σ w·s side - Σ w·s opposite) / W
+//   Unanimous      every member signals the same side         Σ w·s / W
+//   Any            at least one member signals                Σ w·s / Σ w over the winning side
+//
+// Conflicting sides are resolved by weight: the side with more weight behind it
+// wins and the other side's weight counts against it; an exact tie is no signal.
+// =================================================================================
+
+use crate::monad::{MResult, unit, fail, BotError};
+use crate::exchange::{Ticker, OrderSide, OrderBook, Order, Fill};
+use crate::volume::VolumeSpike;
+use crate::flow::FlowWindow;
+use crate::profile::ProfileSummary;
+use crate::candles::{Candle, Timeframe};
+use crate::regime::RegimeReading;
+use crate::json::JsonValue;
+use crate::snapshot;
+use crate::trader::{Strategy, Signal, SignalContext, StrengthComponent, OrderAction};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CombineMode {
+    /// Net weight share of the winning side must reach `threshold` (0, 1].
+    WeightedVote { threshold: f64 },
+    Unanimous,
+    Any,
+}
+
+impl CombineMode {
+    pub fn parse(value: &str) -> MResult<CombineMode> {
+        let value = value.trim().to_ascii_lowercase();
+        match value.as_str() {
+            "unanimous" => unit(CombineMode::Unanimous),
+            "any" => unit(CombineMode::Any),
+            "vote" => unit(CombineMode::WeightedVote { threshold: 0.5 }),
+            _ => match value.strip_prefix("vote:").map(|t| t.parse::<f64>()) {
+                Some(Ok(threshold)) if threshold > 0.0 && threshold <= 1.0 => unit(CombineMode::WeightedVote { threshold }),
+                _ => fail(BotError::ConfigurationError(format!(
+                    "Unknown combine mode '{}' (use vote, vote:<threshold>, unanimous or any)", value))),
+            },
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            CombineMode::WeightedVote { .. } => "vote",
+            CombineMode::Unanimous => "unanimous",
+            CombineMode::Any => "any",
+        }
+    }
+}
+
+/// A strategy and the weight of its vote.
+pub struct CompositeMember {
+    pub name: &'static str,
+    pub weight: f64,
+    pub strategy: Box<dyn Strategy>,
+}
+
+impl CompositeMember {
+    pub fn new(name: &'static str, weight: f64, strategy: Box<dyn Strategy>) -> Self {
+        CompositeMember { name, weight, strategy }
+    }
+}
+
+pub struct CompositeStrategy {
+    mode: CombineMode,
+    members: Vec<CompositeMember>,
+}
+
+impl CompositeStrategy {
+    pub fn new(mode: CombineMode, members: Vec<CompositeMember>) -> MResult<Self> {
+        if members.is_empty() {
+            return fail(BotError::ConfigurationError("Composite strategy needs at least one member".to_string()));
+        }
+        if let Some(m) = members.iter().find(|m| !(m.weight > 0.0 && m.weight.is_finite())) {
+            return fail(BotError::ConfigurationError(format!("Member '{}' has invalid weight {}", m.name, m.weight)));
+        }
+        if let CombineMode::WeightedVote { threshold } = mode {
+            if !(threshold > 0.0 && threshold <= 1.0) {
+                return fail(BotError::ConfigurationError(format!("Vote threshold {} outside (0, 1]", threshold)));
+            }
+        }
+        unit(CompositeStrategy { mode, members })
+    }
+
+    /// Combines one tick's votes (`None` = abstained), indexed like `members`.
+    fn combine(&self, ticker: &Ticker, votes: Vec<Option<Signal>>) -> Option<Signal> {
+        let total: f64 = self.members.iter().map(|m| m.weight).sum();
+        let side_weight = |side: OrderSide| -> (f64, f64) {
+            self.members.iter().zip(&votes)
+                .filter_map(|(m, v)| v.as_ref().filter(|s| s.side == side).map(|s| (m.weight, m.weight * s.strength)))
+                .fold((0.0, 0.0), |(w, ws), (mw, mws)| (w + mw, ws + mws))
+        };
+        let (buy_w, buy_ws) = side_weight(OrderSide::Buy);
+        let (sell_w, sell_ws) = side_weight(OrderSide::Sell);
+
+        // Conflicts go to the side with more weight; a tie (including no votes) is no signal
+        let (side, (win_w, win_ws), (lose_w, lose_ws)) = if buy_w > sell_w {
+            (OrderSide::Buy, (buy_w, buy_ws), (sell_w, sell_ws))
+        } else if sell_w > buy_w {
+            (OrderSide::Sell, (sell_w, sell_ws), (buy_w, buy_ws))
+        } else {
+            return None;
+        };
+
+        let strength = match self.mode {
+            CombineMode::WeightedVote { threshold } => {
+                if (win_w - lose_w) / total < threshold {
+                    return None;
+                }
+                (win_ws - lose_ws) / total
+            }
+            CombineMode::Unanimous => {
+                if lose_w > 0.0 || win_w < total {
+                    return None;
+                }
+                win_ws / total
+            }
+            CombineMode::Any => win_ws / win_w,
+        };
+
+        let side_name = if side == OrderSide::Buy { "buy" } else { "sell" };
+        let mut reasons = Vec::new();
+        let mut components = Vec::new();
+        let mut context = SignalContext::new();
+        let mut lead: Option<(f64, &Signal)> = None; // Heaviest member on the winning side
+        for (member, vote) in self.members.iter().zip(&votes) {
+            let agrees = vote.as_ref().is_some_and(|s| s.side == side);
+            // Signed strength: positive agrees with the outcome, negative opposes, 0 abstains
+            let value = match vote {
+                Some(s) if s.side == side => s.strength,
+                Some(s) => -s.strength,
+                None => 0.0,
+            };
+            components.push(StrengthComponent { name: member.name, value, score: value.max(0.0), weight: member.weight });
+            context = context.check(member.name, format!("votes {}", side_name), value, agrees);
+
+            if let Some(signal) = vote {
+                reasons.push(format!("{}: {}", member.name, signal.reason));
+                context.indicators.extend(signal.context.indicators.iter().copied());
+                context.conditions.extend(signal.context.conditions.iter().cloned());
+                if agrees && lead.is_none_or(|(w, _)| member.weight > w) {
+                    lead = Some((member.weight, signal));
+                }
+            }
+        }
+        let (regime, quantity) = lead.map(|(_, s)| (s.regime, s.quantity))?;
+
+        Some(Signal {
+            symbol: ticker.symbol.clone(),
+            side,
+            strength: strength.clamp(0.0, 1.0),
+            quantity,
+            regime,
+            timestamp: ticker.timestamp,
+            reason: format!("Composite {} {:.0}% {}: {}", self.mode.name(), win_w / total * 100.0, side_name, reasons.join("; ")),
+            components,
+            context,
+        })
+    }
+}
+
+impl Strategy for CompositeStrategy {
+    /// Every member is evaluated, even once the outcome is known.
+    /// The first member error is returned after all members have run.
+    fn process_tick(&mut self, ticker: &Ticker) -> MResult<Option<Signal>> {
+        let mut votes = Vec::with_capacity(self.members.len());
+        let mut first_error = None;
+        for member in self.members.iter_mut() {
+            match member.strategy.process_tick(ticker) {
+                Ok(vote) => votes.push(vote),
+                Err(e) => {
+                    votes.push(None);
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        match first_error {
+            Some(e) => fail(e),
+            None => unit(self.combine(ticker, votes)),
+        }
+    }
+
+    fn order_actions(&mut self, ticker: &Ticker) -> Vec<OrderAction> {
+        self.members.iter_mut().flat_map(|m| m.strategy.order_actions(ticker)).collect()
+    }
+
+    /// Members match orders and fills by their own client ids and ignore the rest.
+    fn observe_order(&mut self, order: &Order) {
+        self.members.iter_mut().for_each(|m| m.strategy.observe_order(order));
+    }
+
+    fn observe_fill(&mut self, fill: &Fill) {
+        self.members.iter_mut().for_each(|m| m.strategy.observe_fill(fill));
+    }
+
+    fn status_line(&self) -> Option<String> {
+        let lines: Vec<String> = self.members.iter()
+            .filter_map(|m| m.strategy.status_line().map(|line| format!("[{}] {}", m.name, line)))
+            .collect();
+        if lines.is_empty() { None } else { Some(lines.join(" | ")) }
+    }
+
+    fn observe_volume(&mut self, spike: &VolumeSpike) {
+        self.members.iter_mut().for_each(|m| m.strategy.observe_volume(spike));
+    }
+
+    fn observe_flow(&mut self, flow: &FlowWindow) {
+        self.members.iter_mut().for_each(|m| m.strategy.observe_flow(flow));
+    }
+
+    fn observe_profile(&mut self, profile: &ProfileSummary) {
+        self.members.iter_mut().for_each(|m| m.strategy.observe_profile(profile));
+    }
+
+    fn observe_regime(&mut self, reading: &RegimeReading) {
+        self.members.iter_mut().for_each(|m| m.strategy.observe_regime(reading));
+    }
+
+    /// Deepest book any member needs.
+    fn book_depth(&self) -> u32 {
+        self.members.iter().map(|m| m.strategy.book_depth()).max().unwrap_or(0)
+    }
+
+    fn observe_book(&mut self, book: &OrderBook) {
+        for member in self.members.iter_mut().filter(|m| m.strategy.book_depth() > 0) {
+            member.strategy.observe_book(book);
+        }
+    }
+
+    /// Union of the members' subscriptions.
+    fn timeframes(&self) -> Vec<Timeframe> {
+        let mut timeframes: Vec<Timeframe> = self.members.iter().flat_map(|m| m.strategy.timeframes()).collect();
+        timeframes.sort();
+        timeframes.dedup();
+        timeframes
+    }
+
+    /// Only members subscribed to the bar's timeframe receive it.
+    fn observe_candle(&mut self, candle: &Candle) {
+        for member in self.members.iter_mut() {
+            if member.strategy.timeframes().contains(&candle.timeframe) {
+                member.strategy.observe_candle(candle);
+            }
+        }
+    }
+
+    fn warmup_bars(&self) -> usize {
+        self.members.iter().map(|m| m.strategy.warmup_bars()).max().unwrap_or(0)
+    }
+
+    /// The engine replays one timeframe at a time; each batch goes to the
+    /// members subscribed to it.
+    fn warm_up(&mut self, candles: &[Candle]) {
+        let timeframe = match candles.first() {
+            Some(c) => c.timeframe,
+            None => return,
+        };
+        for member in self.members.iter_mut() {
+            if member.strategy.timeframes().contains(&timeframe) {
+                member.strategy.warm_up(candles);
+            }
+        }
+    }
+
+    /// Members without snapshot support are stored as `null` and warm up
+    /// from history on restart. `None` if no member supports snapshots.
+    fn snapshot(&self) -> Option<JsonValue> {
+        let states: Vec<Option<JsonValue>> = self.members.iter().map(|m| m.strategy.snapshot()).collect();
+        if states.iter().all(|s| s.is_none()) {
+            return None;
+        }
+        let members = self.members.iter().zip(states)
+            .map(|(m, state)| JsonValue::object().with("name", m.name).with("state", state))
+            .collect();
+        Some(JsonValue::object()
+            .with("kind", "composite")
+            .with("members", JsonValue::Array(members)))
+    }
+
+    /// Members restore independently (each all-or-nothing); a member whose
+    /// state is rejected stays cold and is rebuilt by the warm-up. Returns the
+    /// first rejection.
+    fn restore(&mut self, state: &JsonValue) -> MResult<()> {
+        if state.get("kind").and_then(|v| v.as_str()) != Some("composite") {
+            return fail(snapshot::invalid("not a composite snapshot"));
+        }
+        let saved = state.get("members").and_then(|v| v.as_array())
+            .ok_or_else(|| snapshot::invalid("'members' is not an array"))?;
+        let names: Vec<Option<&str>> = saved.iter().map(|m| m.get("name").and_then(|n| n.as_str())).collect();
+        if names != self.members.iter().map(|m| Some(m.name)).collect::<Vec<_>>() {
+            return fail(snapshot::invalid("composite members do not match"));
+        }
+
+        let mut first_error = None;
+        for (member, saved) in self.members.iter_mut().zip(saved) {
+            let restored = match saved.get("state") {
+                Some(JsonValue::Null) => continue,
+                Some(s) => member.strategy.restore(s),
+                None => fail(snapshot::invalid(&format!("missing state for '{}'", member.name))),
+            };
+            if let Err(e) = restored {
+                first_error.get_or_insert(e);
+            }
+        }
+        match first_error {
+            Some(e) => fail(e),
+            None => unit(()),
+        }
+    }
+}
+
+// =================================================================================
+// UNIT TESTS
+// =================================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trader::MarketRegime;
+
+    /// Emits a preset vote and counts the bars it was given.
+    struct Scripted {
+        vote: Option<(OrderSide, f64)>,
+        timeframe: Timeframe,
+        bars: usize,
+    }
+
+    impl Strategy for Scripted {
+        fn process_tick(&mut self, ticker: &Ticker) -> MResult<Option<Signal>> {
+            unit(self.vote.clone().map(|(side, strength)| Signal {
+                symbol: ticker.symbol.clone(),
+                side,
+                strength,
+                quantity: None,
+                regime: MarketRegime::Sideways,
+                timestamp: ticker.timestamp,
+                reason: "scripted".to_string(),
+                components: Vec::new(),
+                context: SignalContext::new().indicator("bars", self.bars as f64),
+            }))
+        }
+
+        fn timeframes(&self) -> Vec<Timeframe> {
+            vec![self.timeframe]
+        }
+
+        fn observe_candle(&mut self, _candle: &Candle) {
+            self.bars += 1;
+        }
+    }
+
+    fn member(name: &'static str, weight: f64, vote: Option<(OrderSide, f64)>) -> CompositeMember {
+        CompositeMember::new(name, weight, Box::new(Scripted { vote, timeframe: Timeframe::M1, bars: 0 }))
+    }
+
+    fn ticker() -> Ticker {
+        Ticker {
+            symbol: "BTCUSDT".to_string(),
+            price: 100.0,
+            volume_24h: 0.0,
+            volume_1h: 0.0,
+            open: 100.0,
+            high: 100.0,
+            low: 100.0,
+            bid: 99.9,
+            ask: 100.1,
+            timestamp: 7,
+        }
+    }
+
+    fn run(mode: CombineMode, votes: &[(f64, Option<(OrderSide, f64)>)]) -> Option<Signal> {
+        let names = ["a", "b", "c"];
+        let members = votes.iter().enumerate().map(|(i, (w, v))| member(names[i], *w, v.clone())).collect();
+        CompositeStrategy::new(mode, members).unwrap().process_tick(&ticker()).unwrap()
+    }
+
+    #[test]
+    fn test_combine_modes_and_conflicts() {
+        let buy = |s| Some((OrderSide::Buy, s));
+        let sell = |s| Some((OrderSide::Sell, s));
+        let vote = CombineMode::WeightedVote { threshold: 0.5 };
+
+        // 3 buy vs 1 sell out of 4: net 0.5 passes, strength nets the opposition
+        let signal = run(vote, &[(2.0, buy(0.8)), (1.0, buy(0.6)), (1.0, sell(1.0))]).unwrap();
+        assert_eq!(signal.side, OrderSide::Buy);
+        assert!((signal.strength - (1.6 + 0.6 - 1.0) / 4.0).abs() < 1e-9);
+        assert_eq!(signal.components.len(), 3);
+        assert_eq!(signal.components[2].value, -1.0);
+        assert!(run(vote, &[(2.0, buy(0.8)), (1.0, None), (1.0, sell(1.0))]).is_none()); // Net 0.25
+        assert!(run(CombineMode::Any, &[(1.0, buy(0.8)), (1.0, sell(0.8))]).is_none()); // Tie
+
+        // Any: the heavier side wins and strength averages over that side only
+        let signal = run(CombineMode::Any, &[(1.0, sell(0.4)), (3.0, buy(0.2)), (1.0, None)]).unwrap();
+        assert_eq!(signal.side, OrderSide::Buy);
+        assert!((signal.strength - 0.2).abs() < 1e-9);
+
+        // Unanimous: an abstention or a dissent blocks the signal
+        assert!(run(CombineMode::Unanimous, &[(1.0, buy(0.5)), (1.0, None)]).is_none());
+        assert!(run(CombineMode::Unanimous, &[(1.0, sell(0.5)), (1.0, buy(0.5))]).is_none());
+        let signal = run(CombineMode::Unanimous, &[(1.0, sell(0.5)), (3.0, sell(0.9))]).unwrap();
+        assert!((signal.strength - (0.5 + 2.7) / 4.0).abs() < 1e-9);
+
+        assert!(CompositeStrategy::new(vote, vec![member("a", 0.0, None)]).is_err());
+        assert_eq!(CombineMode::parse("vote:0.75").unwrap(), CombineMode::WeightedVote { threshold: 0.75 });
+        assert!(CombineMode::parse("vote:1.5").is_err());
+    }
+
+    #[test]
+    fn test_hooks_reach_subscribed_members() {
+        let slow = CompositeMember::new("slow", 1.0, Box::new(Scripted { vote: None, timeframe: Timeframe::M5, bars: 0 }));
+        let mut composite = CompositeStrategy::new(CombineMode::Any, vec![member("fast", 1.0, Some((OrderSide::Buy, 1.0))), slow]).unwrap();
+        assert_eq!(composite.timeframes(), vec![Timeframe::M1, Timeframe::M5]);
+
+        let bar = |timeframe| Candle {
+            symbol: "BTCUSDT".to_string(),
+            timeframe,
+            open_time: 0,
+            open: 100.0,
+            high: 100.0,
+            low: 100.0,
+            close: 100.0,
+            volume: 1.0,
+            trade_count: 1,
+        };
+        composite.warm_up(&[bar(Timeframe::M1), bar(Timeframe::M1)]);
+        composite.observe_candle(&bar(Timeframe::M5));
+
+        // The fast member saw only its two 1m bars
+        let signal = composite.process_tick(&ticker()).unwrap().unwrap();
+        assert_eq!(signal.context.indicators, vec![("bars", 2.0)]);
+    }
+}
@@ -0,0 +1,490 @@
+// =================================================================================
+// MODULE: Dollar-Cost-Averaging Strategy
+// DESCRIPTION:
+// Accumulates the base asset by buying a fixed quote amount every schedule
+// interval with market orders, until a total quote budget is spent. The final
+// buy is trimmed so that it and its expected fee fit what is left of the budget.
+//
+// With a dip boost configured, a scheduled buy is scaled up when price sits a
+// given percentage below its moving average on the boost timeframe AND the
+// latest volume reading is at least the configured severity (capitulation
+// rather than a quiet drift lower).
+//
+// Spending is booked from fills (notional plus fees); orders in flight count
+// against the budget at their estimated cost until they fill.
+// =================================================================================
+
+use crate::monad::{MResult, Bind, unit, fail, BotError, log_info};
+use crate::exchange::{Ticker, OrderSide, OrderType, OrderStatus, OrderRequest, Order, Fill};
+use crate::volume::{VolumeSpike, SpikeSeverity};
+use crate::candles::{Candle, Timeframe, TimeframeIndicator};
+use crate::json::JsonValue;
+use crate::snapshot::{self, read_optional_f64};
+use crate::trader::{Strategy, Signal, OrderAction, SMA};
+
+/// Larger buys on volume-confirmed dips.
+#[derive(Debug, Clone)]
+pub struct DipBoost {
+    pub timeframe: Timeframe,
+    pub ma_period: usize,            // Bars in the reference moving average
+    pub drop_pct: f64,               // Price at least this far below the average counts as a dip
+    pub multiplier: f64,             // Scheduled amount is multiplied by this on a dip
+    pub min_severity: SpikeSeverity, // Volume needed to confirm the dip
+}
+
+#[derive(Debug, Clone)]
+pub struct DcaConfig {
+    pub quote_amount: f64, // Quote spent per scheduled buy
+    pub interval_secs: u64,
+    pub budget: f64,       // Total quote to spend, fees included
+    pub fee_rate: f64,     // Expected fee per fill, reserved against the budget
+    pub dip: Option<DipBoost>,
+}
+
+impl DcaConfig {
+    pub fn validate(&self) -> MResult<()> {
+        if !(self.quote_amount > 0.0 && self.budget >= self.quote_amount) {
+            return fail(BotError::ConfigurationError(format!(
+                "DCA amount {} must be positive and within the budget {}", self.quote_amount, self.budget)));
+        }
+        if !(0.0..1.0).contains(&self.fee_rate) {
+            return fail(BotError::ConfigurationError(format!("DCA fee rate {} outside [0, 1)", self.fee_rate)));
+        }
+        if self.interval_secs == 0 {
+            return fail(BotError::ConfigurationError("DCA interval must be at least one second".to_string()));
+        }
+        if let Some(dip) = &self.dip {
+            let drop_ok = dip.drop_pct.is_finite() && dip.drop_pct > 0.0;
+            let multiplier_ok = dip.multiplier.is_finite() && dip.multiplier >= 1.0;
+            if dip.ma_period < 1 || !drop_ok || !multiplier_ok {
+                return fail(BotError::ConfigurationError(format!(
+                    "Dip boost needs a period, a positive drop and a multiplier of at least 1 (got {}, {}%, x{})",
+                    dip.ma_period, dip.drop_pct, dip.multiplier)));
+            }
+        }
+        unit(())
+    }
+}
+
+/// A buy sent but not yet completely filled.
+#[derive(Debug, Clone)]
+struct PendingBuy {
+    client_id: String,
+    estimate: f64, // Quote the order was sized for, expected fee included
+    quantity: f64,
+    filled: f64,
+}
+
+pub struct DcaStrategy {
+    config: DcaConfig,
+    average: Option<TimeframeIndicator<SMA>>, // Dip reference, with a boost configured
+    latest_spike: Option<VolumeSpike>,
+    pending: Vec<PendingBuy>,
+    last_buy: Option<u64>, // Time of the last scheduled buy
+    next_seq: u64,
+    spent: f64,    // Quote paid, fees included
+    bought: f64,   // Base received
+    fees: f64,
+    buys: u32,
+    boosted: u32,  // Buys scaled up by the dip boost
+    last_price: f64,
+}
+
+impl DcaStrategy {
+    pub fn new(config: DcaConfig) -> MResult<Self> {
+        config.validate()?;
+        unit(DcaStrategy {
+            average: config.dip.as_ref().map(|d| TimeframeIndicator::new(SMA::new(d.ma_period), Some(d.timeframe))),
+            config,
+            latest_spike: None,
+            pending: Vec::new(),
+            last_buy: None,
+            next_seq: 1,
+            spent: 0.0,
+            bought: 0.0,
+            fees: 0.0,
+            buys: 0,
+            boosted: 0,
+            last_price: 0.0,
+        })
+    }
+
+    /// Quote paid per unit of base, fees included.
+    pub fn average_cost(&self) -> Option<f64> {
+        if self.bought > 0.0 { Some(self.spent / self.bought) } else { None }
+    }
+
+    /// Budget not yet spent or committed to orders in flight.
+    pub fn remaining_budget(&self) -> f64 {
+        let in_flight: f64 = self.pending.iter().map(|p| p.estimate * (1.0 - p.filled / p.quantity)).sum();
+        (self.config.budget - self.spent - in_flight).max(0.0)
+    }
+
+    /// Dip size in percent below the average, when volume confirms it.
+    fn confirmed_dip(&self, price: f64) -> Option<f64> {
+        let dip = self.config.dip.as_ref()?;
+        let average = self.average.as_ref()?.value()?;
+        let drop_pct = (average - price) / average * 100.0;
+        let confirmed = self.latest_spike.as_ref().is_some_and(|s| s.severity >= dip.min_severity);
+        if drop_pct >= dip.drop_pct && confirmed { Some(drop_pct) } else { None }
+    }
+
+    fn pending_of(&self, client_id: &str) -> Option<usize> {
+        self.pending.iter().position(|p| p.client_id == client_id)
+    }
+}
+
+impl Strategy for DcaStrategy {
+    /// Buys are placed only through `order_actions`.
+    fn process_tick(&mut self, _ticker: &Ticker) -> MResult<Option<Signal>> {
+        unit(None)
+    }
+
+    fn observe_volume(&mut self, spike: &VolumeSpike) {
+        self.latest_spike = Some(spike.clone());
+    }
+
+    fn timeframes(&self) -> Vec<Timeframe> {
+        self.config.dip.iter().map(|d| d.timeframe).collect()
+    }
+
+    fn observe_candle(&mut self, candle: &Candle) {
+        if let Some(average) = self.average.as_mut() {
+            average.on_candle(candle);
+        }
+    }
+
+    fn warmup_bars(&self) -> usize {
+        self.config.dip.as_ref().map_or(0, |d| d.ma_period)
+    }
+
+    fn warm_up(&mut self, candles: &[Candle]) {
+        if let Some(average) = self.average.as_mut() {
+            average.reset();
+        }
+        candles.iter().for_each(|c| self.observe_candle(c));
+    }
+
+    fn order_actions(&mut self, ticker: &Ticker) -> Vec<OrderAction> {
+        let (price, now) = (ticker.price, ticker.timestamp);
+        if !price.is_finite() || price <= 0.0 {
+            return Vec::new();
+        }
+        self.last_price = price;
+
+        let due = self.last_buy.is_none_or(|t| now >= t + self.config.interval_secs);
+        let remaining = self.remaining_budget();
+        if !due || remaining < 1e-9 {
+            return Vec::new();
+        }
+
+        let dip = self.confirmed_dip(price);
+        let multiplier = match (dip, &self.config.dip) {
+            (Some(_), Some(boost)) => boost.multiplier,
+            _ => 1.0,
+        };
+        let fee_factor = 1.0 + self.config.fee_rate;
+        let amount = (self.config.quote_amount * multiplier).min(remaining / fee_factor);
+        self.last_buy = Some(now);
+        self.buys += 1;
+        match dip {
+            Some(drop) => {
+                self.boosted += 1;
+                log_info(&format!("[DCA] Dip {:.1}% below average with volume: buying {:.2} (x{})", drop, amount, multiplier));
+            }
+            None => log_info(&format!("[DCA] Scheduled buy of {:.2} at {:.2}", amount, price)),
+        }
+
+        let client_id = format!("dca-{}", self.next_seq);
+        self.next_seq += 1;
+        let quantity = amount / price;
+        self.pending.push(PendingBuy { client_id: client_id.clone(), estimate: amount * fee_factor, quantity, filled: 0.0 });
+        vec![OrderAction::Place(OrderRequest {
+            client_id,
+            symbol: ticker.symbol.clone(),
+            side: OrderSide::Buy,
+            order_type: OrderType::Market,
+            quantity,
+            price: None,
+        })]
+    }
+
+    fn observe_order(&mut self, order: &Order) {
+        let index = match self.pending_of(&order.client_id) {
+            Some(index) => index,
+            None => return,
+        };
+        // Anything unfilled on a dead order goes back to the budget
+        if let OrderStatus::Rejected | OrderStatus::Canceled = order.status {
+            log_info(&format!("[DCA] Buy {} {:?}: {}", order.client_id, order.status, order.reason.as_deref().unwrap_or("-")));
+            self.pending.remove(index);
+        }
+    }
+
+    fn observe_fill(&mut self, fill: &Fill) {
+        let index = match self.pending_of(&fill.client_id) {
+            Some(index) => index,
+            None => return,
+        };
+        self.spent += fill.notional() + fill.fee;
+        self.bought += fill.quantity;
+        self.fees += fill.fee;
+        let pending = &mut self.pending[index];
+        pending.filled += fill.quantity;
+        if pending.filled + 1e-12 >= pending.quantity {
+            self.pending.remove(index);
+        }
+    }
+
+    fn status_line(&self) -> Option<String> {
+        let basis = match self.average_cost() {
+            Some(cost) if self.last_price > 0.0 => format!("avg cost {:.2} vs price {:.2} ({:+.2}%, value {:.2})",
+                cost, self.last_price, (self.last_price / cost - 1.0) * 100.0, self.bought * self.last_price),
+            Some(cost) => format!("avg cost {:.2}", cost),
+            None => "no fills yet".to_string(),
+        };
+        let next = match self.last_buy {
+            _ if self.remaining_budget() < 1e-9 => "budget spent".to_string(),
+            Some(t) => format!("next buy at {}", t + self.config.interval_secs),
+            None => "first buy on next tick".to_string(),
+        };
+        Some(format!("DCA | {} buys ({} boosted) | spent {:.2} / {:.2} | holding {:.6} | {} | {}",
+            self.buys, self.boosted, self.spent, self.config.budget, self.bought, basis, next))
+    }
+
+    /// Accounting, schedule and buys in flight survive restarts so the budget
+    /// is never re-spent.
+    fn snapshot(&self) -> Option<JsonValue> {
+        let pending = self.pending.iter()
+            .map(|p| JsonValue::object()
+                .with("client_id", p.client_id.as_str())
+                .with("estimate", p.estimate)
+                .with("quantity", p.quantity)
+                .with("filled", p.filled))
+            .collect();
+        Some(JsonValue::object()
+            .with("kind", "dca")
+            .with("last_buy", self.last_buy)
+            .with("next_seq", self.next_seq)
+            .with("pending", JsonValue::Array(pending))
+            .with("spent", self.spent)
+            .with("bought", self.bought)
+            .with("fees", self.fees)
+            .with("buys", self.buys as f64)
+            .with("boosted", self.boosted as f64)
+            .with("average", match &self.average {
+                Some(average) => average.snapshot()?,
+                None => JsonValue::Null,
+            }))
+    }
+
+    fn restore(&mut self, state: &JsonValue) -> MResult<()> {
+        if state.get("kind").and_then(|v| v.as_str()) != Some("dca") {
+            return fail(snapshot::invalid("not a dca snapshot"));
+        }
+        let number = |key: &str| read_optional_f64(state, key)
+            .bind(|v| v.ok_or_else(|| snapshot::invalid(&format!("'{}' is null", key))));
+        let (spent, bought, fees) = (number("spent")?, number("bought")?, number("fees")?);
+        let (buys, boosted) = (number("buys")?, number("boosted")?);
+        let last_buy = read_optional_f64(state, "last_buy")?;
+        let next_seq = number("next_seq")?;
+        let pending = state.get("pending").and_then(|v| v.as_array())
+            .ok_or_else(|| snapshot::invalid("'pending' is not an array"))?
+            .iter()
+            .map(|p| {
+                let field = |key: &str| read_optional_f64(p, key)
+                    .bind(|v| v.ok_or_else(|| snapshot::invalid(&format!("pending '{}' is null", key))));
+                let client_id = p.get("client_id").and_then(|v| v.as_str())
+                    .ok_or_else(|| snapshot::invalid("pending 'client_id' is not a string"))?;
+                unit(PendingBuy { client_id: client_id.to_string(), estimate: field("estimate")?, quantity: field("quantity")?, filled: field("filled")? })
+            })
+            .collect::<MResult<Vec<_>>>()?;
+
+        // The average is rebuilt by warm-up if its state does not fit
+        if let (Some(average), Some(saved)) = (self.average.as_mut(), state.get("average").filter(|v| **v != JsonValue::Null)) {
+            if let Err(e) = average.restore(saved) {
+                log_info(&format!("[DCA] Dip average not restored: {:?}", e));
+                average.reset();
+            }
+        }
+        self.spent = spent;
+        self.bought = bought;
+        self.fees = fees;
+        self.buys = buys as u32;
+        self.boosted = boosted as u32;
+        self.last_buy = last_buy.map(|t| t as u64);
+        self.next_seq = next_seq as u64;
+        self.pending = pending;
+        unit(())
+    }
+}
+
+// =================================================================================
+// UNIT TESTS
+// =================================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::{ExchangeClient, BinanceClient};
+    use crate::paper::PaperExchange;
+    use std::sync::Arc;
+
+    fn ticker(price: f64, timestamp: u64) -> Ticker {
+        Ticker {
+            symbol: "BTC/USDT".to_string(),
+            price,
+            volume_24h: 0.0,
+            volume_1h: 0.0,
+            open: price,
+            high: price,
+            low: price,
+            bid: price,
+            ask: price,
+            timestamp,
+        }
+    }
+
+    fn bar(i: u64, close: f64) -> Candle {
+        Candle {
+            symbol: "BTC/USDT".to_string(),
+            timeframe: Timeframe::H1,
+            open_time: i * 3600,
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: 100.0,
+            trade_count: 10,
+        }
+    }
+
+    fn spike(severity: SpikeSeverity) -> VolumeSpike {
+        VolumeSpike {
+            symbol: "BTC/USDT".to_string(),
+            volume: 0.0,
+            mean: 0.0,
+            std_dev: 0.0,
+            z_score: 0.0,
+            median: 0.0,
+            mad: 0.0,
+            robust_score: 0.0,
+            relative_volume: 0.0,
+            seasonal: None,
+            severity,
+            timestamp: 0,
+        }
+    }
+
+    /// One engine tick: send the strategy's orders, then book new fills from the journal.
+    fn tick(paper: &PaperExchange, dca: &mut DcaStrategy, seen: &mut Option<u64>, price: f64, timestamp: u64) {
+        paper.on_price("BTC/USDT", price);
+        for action in dca.order_actions(&ticker(price, timestamp)) {
+            if let OrderAction::Place(request) = action {
+                let order = paper.place_order(&request).unwrap();
+                dca.observe_order(&order);
+            }
+        }
+        for fill in paper.fetch_fills("BTC/USDT", *seen).unwrap() {
+            dca.observe_fill(&fill);
+            *seen = Some(fill.id);
+        }
+    }
+
+    #[test]
+    fn test_schedule_dip_boost_and_budget() {
+        let paper = PaperExchange::new(Arc::new(BinanceClient::new("k", "s")), 0.0)
+            .with_balance("USDT", 10_000.0);
+        let config = DcaConfig {
+            quote_amount: 100.0,
+            interval_secs: 3600,
+            budget: 450.0,
+            fee_rate: 0.0,
+            dip: Some(DipBoost { timeframe: Timeframe::H1, ma_period: 3, drop_pct: 5.0, multiplier: 2.0, min_severity: SpikeSeverity::High }),
+        };
+        let mut dca = DcaStrategy::new(config).unwrap();
+        dca.warm_up(&[bar(0, 100.0), bar(1, 100.0), bar(2, 100.0)]);
+        let mut seen = None;
+
+        // First buy at once, nothing more until the interval has passed
+        tick(&paper, &mut dca, &mut seen, 100.0, 10_000);
+        tick(&paper, &mut dca, &mut seen, 100.0, 11_000);
+        assert_eq!(dca.spent, 100.0);
+        assert_eq!(dca.bought, 1.0);
+
+        // A 10% dip without volume is an ordinary buy
+        dca.observe_volume(&spike(SpikeSeverity::Normal));
+        tick(&paper, &mut dca, &mut seen, 90.0, 13_600);
+        assert!((dca.spent - 200.0).abs() < 1e-9);
+
+        // With volume it buys double, and cost basis falls below the first price
+        dca.observe_volume(&spike(SpikeSeverity::Extreme));
+        tick(&paper, &mut dca, &mut seen, 80.0, 17_200);
+        assert!((dca.spent - 400.0).abs() < 1e-9);
+        let expected = 400.0 / (1.0 + 100.0 / 90.0 + 2.5);
+        assert!((dca.average_cost().unwrap() - expected).abs() < 1e-9);
+
+        // The last buy is trimmed to the budget, then buying stops
+        tick(&paper, &mut dca, &mut seen, 100.0, 20_800);
+        tick(&paper, &mut dca, &mut seen, 100.0, 24_400);
+        assert!((dca.spent - 450.0).abs() < 1e-9);
+        let status = dca.status_line().unwrap();
+        assert!(status.contains("budget spent") && status.contains("vs price 100.00"), "{}", status);
+
+        // Accounting survives a restart
+        let mut restored = DcaStrategy::new(dca.config.clone()).unwrap();
+        restored.restore(&dca.snapshot().unwrap()).unwrap();
+        assert!((restored.remaining_budget()).abs() < 1e-9);
+        assert_eq!(restored.average_cost(), dca.average_cost());
+    }
+
+    #[test]
+    fn test_final_buy_leaves_room_for_its_fee() {
+        let paper = PaperExchange::new(Arc::new(BinanceClient::new("k", "s")), 0.001)
+            .with_balance("USDT", 10_000.0);
+        let config = DcaConfig { quote_amount: 100.0, interval_secs: 60, budget: 150.0, fee_rate: 0.001, dip: None };
+        let mut dca = DcaStrategy::new(config).unwrap();
+        let mut seen = None;
+
+        tick(&paper, &mut dca, &mut seen, 100.0, 0);
+        tick(&paper, &mut dca, &mut seen, 100.0, 60);
+        // The fee is booked with the notional, and the total stays within budget
+        assert!(dca.spent <= 150.0 + 1e-9, "{}", dca.spent);
+        assert!((dca.spent - 150.0).abs() < 1e-6, "{}", dca.spent);
+        assert!(dca.remaining_budget() < 1e-6);
+    }
+
+    #[test]
+    fn test_buys_in_flight_survive_snapshot() {
+        let config = DcaConfig { quote_amount: 100.0, interval_secs: 60, budget: 300.0, fee_rate: 0.001, dip: None };
+        let mut dca = DcaStrategy::new(config.clone()).unwrap();
+        // Placed but not yet filled: the estimate, fee included, is committed
+        let placed = dca.order_actions(&ticker(100.0, 0));
+        assert_eq!(placed.len(), 1);
+        assert!((dca.remaining_budget() - 199.9).abs() < 1e-9);
+
+        let saved = JsonValue::parse(&dca.snapshot().unwrap().to_string()).unwrap();
+        let mut restarted = DcaStrategy::new(config).unwrap();
+        restarted.restore(&saved).unwrap();
+        assert!((restarted.remaining_budget() - 199.9).abs() < 1e-9);
+
+        // The fill that lands after the restart is still booked against that order
+        restarted.observe_fill(&Fill {
+            id: 1,
+            order_id: "o1".to_string(),
+            client_id: "dca-1".to_string(),
+            symbol: "BTC/USDT".to_string(),
+            side: OrderSide::Buy,
+            price: 100.0,
+            quantity: 1.0,
+            fee: 0.1,
+            timestamp_ms: 0,
+        });
+        assert!((restarted.spent - 100.1).abs() < 1e-9);
+        assert!((restarted.remaining_budget() - 199.9).abs() < 1e-9);
+        // New buys do not reuse the id of one sent before the restart
+        match restarted.order_actions(&ticker(100.0, 60)).as_slice() {
+            [OrderAction::Place(request)] => assert_eq!(request.client_id, "dca-2"),
+            other => panic!("expected one buy, got {}", other.len()),
+        }
+    }
+}
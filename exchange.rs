@@ -7,7 +7,9 @@
 // =================================================================================
 
 use crate::monad::{MResult, unit, fail, BotError, Bind, log_info};
-use std::collections::HashMap;
+use crate::candles::{Candle, Timeframe};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 // --- Data Models ---
@@ -28,6 +30,97 @@ pub enum OrderType {
     TakeProfit,
 }
 
+/// Lifecycle of an order on the exchange.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OrderStatus {
+    New,             // Accepted and resting
+    #[allow(dead_code)] // Reported by real venues; the simulated ones fill in full
+    PartiallyFilled,
+    Filled,
+    Canceled,
+    Rejected,
+}
+
+impl OrderStatus {
+    /// True while the order can still fill.
+    pub fn is_open(&self) -> bool {
+        matches!(self, OrderStatus::New | OrderStatus::PartiallyFilled)
+    }
+}
+
+/// What the caller wants placed. `client_id` is echoed back on the order and
+/// its fills so the caller can match them to its own bookkeeping.
+#[derive(Debug, Clone)]
+pub struct OrderRequest {
+    pub client_id: String,
+    pub symbol: String,
+    pub side: OrderSide,
+    pub order_type: OrderType,
+    pub quantity: f64,
+    pub price: Option<f64>, // Required for limit orders
+}
+
+/// The exchange's view of an order.
+#[derive(Debug, Clone)]
+pub struct Order {
+    pub id: String,
+    pub client_id: String,
+    pub symbol: String,
+    pub side: OrderSide,
+    pub order_type: OrderType,
+    pub price: Option<f64>,
+    pub quantity: f64,
+    pub filled_quantity: f64,
+    pub avg_fill_price: f64, // 0 until something fills
+    pub status: OrderStatus,
+    pub reason: Option<String>, // Why it was rejected
+    pub updated_ms: u64,
+}
+
+impl Order {
+    /// A locally generated rejection, for requests the exchange refused.
+    pub fn rejected(request: &OrderRequest, error: &BotError, now_ms: u64) -> Self {
+        Order {
+            id: String::new(),
+            client_id: request.client_id.clone(),
+            symbol: request.symbol.clone(),
+            side: request.side.clone(),
+            order_type: request.order_type.clone(),
+            price: request.price,
+            quantity: request.quantity,
+            filled_quantity: 0.0,
+            avg_fill_price: 0.0,
+            status: OrderStatus::Rejected,
+            reason: Some(format!("{:?}", error)),
+            updated_ms: now_ms,
+        }
+    }
+
+    pub fn remaining(&self) -> f64 {
+        (self.quantity - self.filled_quantity).max(0.0)
+    }
+}
+
+/// One execution against one of our orders.
+#[derive(Debug, Clone)]
+pub struct Fill {
+    pub id: u64, // Increasing per account, for incremental polling
+    pub order_id: String,
+    pub client_id: String,
+    pub symbol: String,
+    pub side: OrderSide,
+    pub price: f64,
+    pub quantity: f64,
+    pub fee: f64, // In the quote asset
+    pub timestamp_ms: u64,
+}
+
+impl Fill {
+    pub fn notional(&self) -> f64 {
+        self.price * self.quantity
+    }
+}
+
 /// Comprehensive Ticker information.
 #[derive(Debug, Clone)]
 pub struct Ticker {
@@ -58,6 +151,24 @@ pub struct PriceLevel {
     pub quantity: f64,
 }
 
+/// A single executed trade from the public trade feed.
+/// `aggressor` is the side that crossed the spread (the taker).
+#[derive(Debug, Clone)]
+pub struct Trade {
+    pub symbol: String,
+    pub id: u64,
+    pub price: f64,
+    pub quantity: f64,
+    pub aggressor: OrderSide,
+    pub timestamp_ms: u64,
+}
+
+impl Trade {
+    pub fn notional(&self) -> f64 {
+        self.price * self.quantity
+    }
+}
+
 /// Structure for tracking account balances.
 #[derive(Debug, Clone)]
 pub struct Balance {
@@ -104,30 +215,79 @@ impl RequestSigner {
     }
 }
 
+// --- Trade Stream ---
+
+/// Incremental trade feed built on `fetch_trades`.
+/// Remembers the last seen trade id so each `poll` only yields new prints.
+pub struct TradeStream {
+    symbol: String,
+    last_id: Option<u64>,
+    batch_limit: u32,
+}
+
+impl TradeStream {
+    pub fn new(symbol: &str, batch_limit: u32) -> Self {
+        TradeStream {
+            symbol: symbol.to_string(),
+            last_id: None,
+            batch_limit,
+        }
+    }
+
+    pub fn poll(&mut self, client: &dyn ExchangeClient) -> MResult<Vec<Trade>> {
+        client.fetch_trades(&self.symbol, self.last_id, self.batch_limit)
+            .inspect(|trades| {
+                if let Some(last) = trades.last() {
+                    self.last_id = Some(last.id);
+                }
+            })
+    }
+}
+
 // --- Exchange Client Implementation ---
 
 /// Trait defining the standard interface for any exchange adapter.
-pub trait ExchangeClient {
+/// Clients are shared behind `Arc`, e.g. by the paper exchange wrapping one.
+pub trait ExchangeClient: Send + Sync {
     fn fetch_ticker(&self, symbol: &str) -> MResult<Ticker>;
     fn fetch_order_book(&self, symbol: &str, depth: u32) -> MResult<OrderBook>;
+    /// Recent public trades, oldest first. With `since_id`, only trades newer than that id.
+    fn fetch_trades(&self, symbol: &str, since_id: Option<u64>, limit: u32) -> MResult<Vec<Trade>>;
+    /// The last `limit` closed bars for `interval`, oldest first.
+    fn fetch_klines(&self, symbol: &str, interval: Timeframe, limit: u32) -> MResult<Vec<Candle>>;
     fn fetch_balance(&self, asset: &str) -> MResult<Balance>;
-    fn execute_order(&self, symbol: &str, side: OrderSide, order_type: OrderType, qty: f64, price: Option<f64>) -> MResult<String>;
+    /// Submits an order and returns its state right after acceptance
+    /// (which may already be filled). Refusals are errors.
+    fn place_order(&self, request: &OrderRequest) -> MResult<Order>;
+    /// Cancels an open order and returns its final state.
+    fn cancel_order(&self, symbol: &str, order_id: &str) -> MResult<Order>;
+    fn fetch_open_orders(&self, symbol: &str) -> MResult<Vec<Order>>;
+    /// Our executions, oldest first. With `since_id`, only fills newer than that id.
+    fn fetch_fills(&self, symbol: &str, since_id: Option<u64>) -> MResult<Vec<Fill>>;
     fn check_connectivity(&self) -> MResult<u64>;
 }
 
 /// Concrete implementation for Binance (or similar centralized exchanges).
+/// The simulation fills market orders and marketable limits in full on
+/// placement and refuses limits that would rest; resting orders need the
+/// paper exchange (`paper.rs`).
 pub struct BinanceClient {
     signer: RequestSigner,
     base_url: String,
     rate_limit_tokens: u32,
+    fills: Mutex<VecDeque<Fill>>, // Simulated executions, newest last
 }
 
+/// Simulated executions kept for `fetch_fills`.
+const FILL_JOURNAL_CAPACITY: usize = 1000;
+
 impl BinanceClient {
     pub fn new(api_key: &str, secret_key: &str) -> Self {
         BinanceClient {
             signer: RequestSigner::new(api_key, secret_key),
             base_url: "https://api.binance.com".to_string(),
             rate_limit_tokens: 1200, // Standard weight per minute
+            fills: Mutex::new(VecDeque::new()),
         }
     }
 
@@ -144,7 +304,7 @@ impl BinanceClient {
             .subsec_nanos();
         
         // 1% chance of network failure
-        if now % 100 == 0 {
+        if now.is_multiple_of(100) {
             log_info(&format!("Network timeout connecting to {}", endpoint));
             return fail(BotError::NetworkFailure("Connection timed out".to_string()));
         }
@@ -155,6 +315,11 @@ impl BinanceClient {
     /// Generates deterministic but varying market data based on time.
     fn generate_market_data(&self, symbol: &str) -> (f64, f64) {
         let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        self.market_data_at(symbol, now)
+    }
+
+    /// Simulated `(price, 1h volume)` at unix second `now`.
+    fn market_data_at(&self, _symbol: &str, now: u64) -> (f64, f64) {
         let cycle = (now % 3600) as f64; // 1 hour cycle
         
         // Math sine wave for price movement
@@ -163,10 +328,58 @@ impl BinanceClient {
         let price = base_price + (cycle * 0.1).sin() * amplitude;
         
         // Volume spike generation
-        let volume = if now % 20 == 0 { 5000.0 } else { 150.0 + (now % 500) as f64 };
+        let volume = if now.is_multiple_of(20) { 5000.0 } else { 150.0 + (now % 500) as f64 };
 
         (price, volume)
     }
+
+    /// Simulated bar built from 60 evenly spaced samples of the market data.
+    /// Bar volume is the sampled hourly rate scaled to the bar's length.
+    fn generate_kline(&self, symbol: &str, interval: Timeframe, open_time: u64) -> Candle {
+        let step = (interval.secs() / 60).max(1);
+        let samples: Vec<(f64, f64)> = (0..interval.secs() / step)
+            .map(|i| self.market_data_at(symbol, open_time + i * step))
+            .collect();
+        let mean_rate = samples.iter().map(|(_, v)| v).sum::<f64>() / samples.len() as f64;
+
+        Candle {
+            symbol: symbol.to_string(),
+            timeframe: interval,
+            open_time,
+            open: samples[0].0,
+            high: samples.iter().map(|(p, _)| *p).fold(f64::MIN, f64::max),
+            low: samples.iter().map(|(p, _)| *p).fold(f64::MAX, f64::min),
+            close: samples[samples.len() - 1].0,
+            volume: mean_rate * interval.secs() as f64 / 3600.0,
+            trade_count: samples.len() as u32,
+        }
+    }
+
+    /// Deterministic pseudo-random trade for a 100ms slot.
+    /// Aggression leans with the direction of the simulated price wave.
+    fn generate_trade(&self, symbol: &str, slot: u64) -> Trade {
+        let secs = slot / 10;
+        let cycle = (secs % 3600) as f64;
+        let (price, _) = self.market_data_at(symbol, secs);
+        let hash = slot.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 32;
+
+        let trend = (cycle * 0.1).cos(); // Derivative of the price wave
+        let buy_bias = 0.5 + trend * 0.2;
+        let aggressor = if (hash % 1000) as f64 / 1000.0 < buy_bias { OrderSide::Buy } else { OrderSide::Sell };
+
+        // Mostly retail-sized prints with the occasional large order
+        let quantity = if hash.is_multiple_of(97) { 5.0 + (hash % 20) as f64 } else { 0.01 + (hash % 500) as f64 / 1000.0 };
+        let jitter = ((hash % 21) as f64 - 10.0) * 0.01;
+
+        Trade {
+            symbol: symbol.to_string(),
+            id: slot,
+            price: price + jitter,
+            quantity,
+            aggressor,
+            timestamp_ms: slot * 100,
+        }
+    }
 }
 
 impl ExchangeClient for BinanceClient {
@@ -201,26 +414,64 @@ impl ExchangeClient for BinanceClient {
     fn fetch_order_book(&self, symbol: &str, depth: u32) -> MResult<OrderBook> {
         self.simulate_network_call("/api/v3/depth", 5).bind(|_| {
             let (price, _) = self.generate_market_data(symbol);
-            
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+
+            // Resting size leans with the direction of the simulated price wave
+            let trend = (((now % 3600) as f64) * 0.1).cos();
+
             let mut bids = Vec::new();
             let mut asks = Vec::new();
 
-            // Generate depth
+            // Generate depth, touch levels matching the ticker's bid/ask
             for i in 0..depth {
-                let spread = (i as f64) * 0.5;
-                bids.push(PriceLevel { price: price - spread, quantity: 1.0 + (i as f64) });
-                asks.push(PriceLevel { price: price + spread, quantity: 1.0 + (i as f64) });
+                let spread = 0.2 + (i as f64) * 0.5;
+                let size = 1.0 + (i as f64);
+                bids.push(PriceLevel { price: price - spread, quantity: size * (1.0 + trend * 0.5) });
+                asks.push(PriceLevel { price: price + spread, quantity: size * (1.0 - trend * 0.5) });
             }
 
             unit(OrderBook {
                 symbol: symbol.to_string(),
                 bids,
                 asks,
-                last_update_id: 1000234,
+                last_update_id: now,
             })
         })
     }
 
+    fn fetch_trades(&self, symbol: &str, since_id: Option<u64>, limit: u32) -> MResult<Vec<Trade>> {
+        self.simulate_network_call("/api/v3/trades", 5).bind(|_| {
+            // One simulated print per 100ms slot
+            let current_slot = (SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() / 100) as u64;
+            let oldest = current_slot.saturating_sub(limit.max(1) as u64 - 1);
+            let first = match since_id {
+                Some(id) => (id + 1).max(oldest),
+                None => oldest,
+            };
+
+            let trades = (first..=current_slot)
+                .map(|slot| self.generate_trade(symbol, slot))
+                .collect();
+            unit(trades)
+        })
+    }
+
+    fn fetch_klines(&self, symbol: &str, interval: Timeframe, limit: u32) -> MResult<Vec<Candle>> {
+        self.simulate_network_call("/api/v3/klines", 2).bind(|_| {
+            if limit == 0 || limit > 1000 {
+                return fail(BotError::ConfigurationError(format!("Kline limit must be in 1..=1000, got {}", limit)));
+            }
+            // Only closed bars: the newest ends where the current one starts
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+            let current = interval.bucket(now);
+            let klines = (1..=limit as u64).rev()
+                .filter_map(|back| current.checked_sub(back * interval.secs()))
+                .map(|open_time| self.generate_kline(symbol, interval, open_time))
+                .collect();
+            unit(klines)
+        })
+    }
+
     fn fetch_balance(&self, asset: &str) -> MResult<Balance> {
         // Requires authentication
         let _headers = self.signer.get_headers();
@@ -236,30 +487,288 @@ impl ExchangeClient for BinanceClient {
         })
     }
 
-    fn execute_order(&self, symbol: &str, side: OrderSide, order_type: OrderType, qty: f64, price: Option<f64>) -> MResult<String> {
+    fn place_order(&self, request: &OrderRequest) -> MResult<Order> {
         // High weight operation
         self.simulate_network_call("/api/v3/order", 15).bind(|_| {
             // Validation
-            if qty <= 0.0 {
+            if request.quantity <= 0.0 {
                 return fail(BotError::StrategyError("Order quantity must be positive".to_string()));
             }
 
             // Price validation for LIMIT orders
-            if let OrderType::Limit = order_type {
-                if price.is_none() || price.unwrap() <= 0.0 {
+            if let OrderType::Limit = request.order_type {
+                if request.price.is_none_or(|p| p <= 0.0) {
                     return fail(BotError::StrategyError("Limit order requires valid price".to_string()));
                 }
             }
 
             // Generate Payload signature
-            let query = format!("symbol={}&side={:?}&quantity={}", symbol, side, qty);
+            let query = format!("symbol={}&side={:?}&quantity={}&newClientOrderId={}", request.symbol, request.side, request.quantity, request.client_id);
             let signature = self.signer.sign(&query);
 
             log_info(&format!("Signed Order Request: {} [Sig: {}...]", query, &signature[0..8]));
 
             // Return a generated Order ID
-            let order_id = format!("ORD-{}-{}-{:x}", symbol, qty as u64, SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_micros());
-            unit(order_id)
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+            let now_ms = now.as_millis() as u64;
+            let order_id = format!("ORD-{}-{}-{:x}", request.symbol, request.quantity as u64, now.as_micros());
+            let mut order = Order {
+                id: order_id,
+                client_id: request.client_id.clone(),
+                symbol: request.symbol.clone(),
+                side: request.side.clone(),
+                order_type: request.order_type.clone(),
+                price: request.price,
+                quantity: request.quantity,
+                filled_quantity: 0.0,
+                avg_fill_price: 0.0,
+                status: OrderStatus::New,
+                reason: None,
+                updated_ms: now_ms,
+            };
+
+            // Market orders and marketable limits fill at the simulated price; stop / take-profit
+            // triggers are only acknowledged. Nothing rests, so a limit that would is refused.
+            let fill_price = match order.order_type {
+                OrderType::Limit => {
+                    let market = self.generate_market_data(&request.symbol).0;
+                    let limit = request.price.unwrap_or(0.0);
+                    let marketable = match request.side {
+                        OrderSide::Buy => limit >= market,
+                        OrderSide::Sell => limit <= market,
+                    };
+                    if !marketable {
+                        return fail(BotError::ExchangeError(format!(
+                            "Limit {:?} at {} is not marketable against {:.2}; resting orders need BOT_PAPER",
+                            request.side, limit, market
+                        )));
+                    }
+                    Some(market)
+                }
+                OrderType::Market => Some(self.generate_market_data(&request.symbol).0),
+                OrderType::StopLoss | OrderType::TakeProfit => None,
+            };
+            if let Some(price) = fill_price {
+                order.filled_quantity = order.quantity;
+                order.avg_fill_price = price;
+                order.status = OrderStatus::Filled;
+
+                let mut journal = self.fills.lock().unwrap();
+                let id = journal.back().map_or(1, |f| f.id + 1);
+                if journal.len() >= FILL_JOURNAL_CAPACITY {
+                    journal.pop_front();
+                }
+                journal.push_back(Fill {
+                    id,
+                    order_id: order.id.clone(),
+                    client_id: order.client_id.clone(),
+                    symbol: order.symbol.clone(),
+                    side: order.side.clone(),
+                    price,
+                    quantity: order.quantity,
+                    fee: 0.0,
+                    timestamp_ms: now_ms,
+                });
+            }
+            unit(order)
         })
     }
+
+    fn cancel_order(&self, _symbol: &str, order_id: &str) -> MResult<Order> {
+        self.simulate_network_call("/api/v3/order", 1).bind(|_| {
+            // Nothing rests in the simulation, so there is never anything to cancel
+            fail(BotError::ExchangeError(format!("Unknown order {}", order_id)))
+        })
+    }
+
+    fn fetch_open_orders(&self, _symbol: &str) -> MResult<Vec<Order>> {
+        self.simulate_network_call("/api/v3/openOrders", 3).bind(|_| unit(Vec::new()))
+    }
+
+    fn fetch_fills(&self, symbol: &str, since_id: Option<u64>) -> MResult<Vec<Fill>> {
+        self.simulate_network_call("/api/v3/myTrades", 10).bind(|_| {
+            let journal = self.fills.lock().unwrap();
+            unit(journal.iter()
+                .filter(|f| f.symbol == symbol && since_id.is_none_or(|id| f.id > id))
+                .cloned()
+                .collect())
+        })
+    }
+}
+
+// =================================================================================
+// TEST SUPPORT
+// =================================================================================
+#[cfg(test)]
+pub mod stub {
+    use super::*;
+
+    type TickerFeed = Box<dyn Fn(&str, u64) -> Option<Ticker> + Send + Sync>;
+    type KlineFeed = Box<dyn Fn(&str, Timeframe, u32) -> Vec<Candle> + Send + Sync>;
+
+    /// In-memory `ExchangeClient` for tests. Market data is whatever it is
+    /// given; endpoints without data fail like an unreachable exchange.
+    ///
+    /// Placed orders are recorded and acknowledged as `New`. Like the simulated
+    /// client, it refuses a limit that is not marketable, here against the last
+    /// ticker price moved by `drift` (the move between reading a price and the
+    /// order arriving).
+    pub struct StubExchange {
+        tickers: Option<TickerFeed>, // (symbol, n-th ticker request)
+        klines: Option<KlineFeed>,   // (symbol, interval, limit)
+        trades: Vec<Trade>,          // Served to the first poll only
+        balance: Option<f64>,        // Free quote, for any asset
+        drift: f64,
+        tickers_served: Mutex<u64>,
+        last_price: Mutex<Option<f64>>,
+        orders: Mutex<Vec<OrderRequest>>,
+    }
+
+    impl StubExchange {
+        pub fn new() -> Self {
+            StubExchange {
+                tickers: None,
+                klines: None,
+                trades: Vec::new(),
+                balance: None,
+                drift: 0.0,
+                tickers_served: Mutex::new(0),
+                last_price: Mutex::new(None),
+                orders: Mutex::new(Vec::new()),
+            }
+        }
+
+        /// Serves these tickers, one per symbol, on every request.
+        pub fn with_tickers(self, tickers: Vec<Ticker>) -> Self {
+            let by_symbol: HashMap<String, Ticker> = tickers.into_iter().map(|t| (t.symbol.clone(), t)).collect();
+            self.with_ticker_feed(move |symbol, _| by_symbol.get(symbol).cloned())
+        }
+
+        /// Tickers computed from the symbol and the 1-based request count.
+        pub fn with_ticker_feed<F>(mut self, feed: F) -> Self
+        where
+            F: Fn(&str, u64) -> Option<Ticker> + Send + Sync + 'static,
+        {
+            self.tickers = Some(Box::new(feed));
+            self
+        }
+
+        pub fn with_klines<F>(mut self, feed: F) -> Self
+        where
+            F: Fn(&str, Timeframe, u32) -> Vec<Candle> + Send + Sync + 'static,
+        {
+            self.klines = Some(Box::new(feed));
+            self
+        }
+
+        pub fn with_trades(mut self, trades: Vec<Trade>) -> Self {
+            self.trades = trades;
+            self
+        }
+
+        pub fn with_balance(mut self, free: f64) -> Self {
+            self.balance = Some(free);
+            self
+        }
+
+        pub fn with_drift(mut self, drift: f64) -> Self {
+            self.drift = drift;
+            self
+        }
+
+        /// Every order request accepted so far, oldest first.
+        pub fn placed(&self) -> Vec<OrderRequest> {
+            self.orders.lock().unwrap().clone()
+        }
+    }
+
+    fn unreachable<T>() -> MResult<T> {
+        fail(BotError::NetworkFailure("stub".to_string()))
+    }
+
+    impl ExchangeClient for StubExchange {
+        fn fetch_ticker(&self, symbol: &str) -> MResult<Ticker> {
+            let mut served = self.tickers_served.lock().unwrap();
+            *served += 1;
+            match self.tickers.as_ref().and_then(|feed| feed(symbol, *served)) {
+                Some(ticker) => {
+                    *self.last_price.lock().unwrap() = Some(ticker.price);
+                    unit(ticker)
+                }
+                None => fail(BotError::NetworkFailure(format!("No ticker for {}", symbol))),
+            }
+        }
+        fn fetch_order_book(&self, _: &str, _: u32) -> MResult<OrderBook> {
+            unreachable()
+        }
+        fn fetch_trades(&self, _: &str, since_id: Option<u64>, _: u32) -> MResult<Vec<Trade>> {
+            match since_id {
+                None => unit(self.trades.clone()),
+                Some(_) => unit(Vec::new()),
+            }
+        }
+        fn fetch_klines(&self, symbol: &str, interval: Timeframe, limit: u32) -> MResult<Vec<Candle>> {
+            match &self.klines {
+                Some(feed) => unit(feed(symbol, interval, limit)),
+                None => unreachable(),
+            }
+        }
+        fn fetch_balance(&self, asset: &str) -> MResult<Balance> {
+            match self.balance {
+                Some(free) => unit(Balance { asset: asset.to_string(), free, locked: 0.0 }),
+                None => unreachable(),
+            }
+        }
+        fn place_order(&self, request: &OrderRequest) -> MResult<Order> {
+            if let (OrderType::Limit, Some(limit), Some(price)) = (&request.order_type, request.price, *self.last_price.lock().unwrap()) {
+                let market = price + self.drift;
+                let marketable = match request.side {
+                    OrderSide::Buy => limit >= market,
+                    OrderSide::Sell => limit <= market,
+                };
+                if !marketable {
+                    return fail(BotError::ExchangeError(format!("Limit {} is not marketable against {}", limit, market)));
+                }
+            }
+            self.orders.lock().unwrap().push(request.clone());
+            let mut order = Order::rejected(request, &BotError::ExchangeError("stub".to_string()), 0);
+            order.id = format!("stub-{}", request.client_id);
+            order.status = OrderStatus::New;
+            order.reason = None;
+            unit(order)
+        }
+        fn cancel_order(&self, _: &str, _: &str) -> MResult<Order> {
+            unreachable()
+        }
+        fn fetch_open_orders(&self, _: &str) -> MResult<Vec<Order>> {
+            unit(Vec::new())
+        }
+        fn fetch_fills(&self, _: &str, _: Option<u64>) -> MResult<Vec<Fill>> {
+            unit(Vec::new())
+        }
+        fn check_connectivity(&self) -> MResult<u64> {
+            unit(1)
+        }
+    }
+}
+
+// =================================================================================
+// UNIT TESTS
+// =================================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_historical_trades_follow_the_price_wave() {
+        let client = BinanceClient::new("k", "s");
+        // Each print sits within its jitter of the price at its own time
+        let prints: Vec<Trade> = [0u64, 5, 10, 15].iter().map(|secs| client.generate_trade("X", secs * 10)).collect();
+        for trade in &prints {
+            let (price, _) = client.market_data_at("X", trade.timestamp_ms / 1000);
+            assert!((trade.price - price).abs() <= 0.1 + 1e-9, "{} vs {}", trade.price, price);
+        }
+        // 15s of the wave moves the price by tens, not a flat line
+        assert!((prints[3].price - prints[0].price).abs() > 10.0);
+    }
 }
@@ -0,0 +1,681 @@
+// =================================================================================
+// MODULE: Alert Expressions
+// DESCRIPTION:
+// A small expression language for user-defined alerts, evaluated every tick
+// per symbol without recompiling the bot, e.g.
+//
+//     volume_1h > 3 * sma(volume_1h, 24) and rsi(14) < 40
+//
+// Grammar (lowest to highest precedence):
+//     or_expr   := and_expr ("or" and_expr)*
+//     and_expr  := not_expr ("and" not_expr)*
+//     not_expr  := "not" not_expr | compare
+//     compare   := sum (("<" | "<=" | ">" | ">=" | "==" | "!=") sum)?
+//     sum       := product (("+" | "-") product)*
+//     product   := unary (("*" | "/") unary)*
+//     unary     := "-" unary | primary
+//     primary   := number | field | call | "(" or_expr ")"
+//     call      := ("sma" | "rsi") "(" [field ","] integer ")"
+//
+// Fields are the `Ticker` columns; indicator calls default to `price` when no
+// source field is given. Expressions are type-checked at parse time, so
+// `rsi(14) and price` is rejected before the bot starts.
+// =================================================================================
+
+use crate::monad::{MResult, unit, fail, BotError};
+use crate::exchange::Ticker;
+use crate::trader::{Indicator, SMA, RSI};
+use std::collections::HashMap;
+
+// --- Syntax Tree ---
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Field {
+    Price,
+    Volume24h,
+    Volume1h,
+    Open,
+    High,
+    Low,
+    Bid,
+    Ask,
+}
+
+impl Field {
+    fn parse(name: &str) -> Option<Field> {
+        match name {
+            "price" => Some(Field::Price),
+            "volume_24h" => Some(Field::Volume24h),
+            "volume_1h" => Some(Field::Volume1h),
+            "open" => Some(Field::Open),
+            "high" => Some(Field::High),
+            "low" => Some(Field::Low),
+            "bid" => Some(Field::Bid),
+            "ask" => Some(Field::Ask),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Field::Price => "price",
+            Field::Volume24h => "volume_24h",
+            Field::Volume1h => "volume_1h",
+            Field::Open => "open",
+            Field::High => "high",
+            Field::Low => "low",
+            Field::Bid => "bid",
+            Field::Ask => "ask",
+        }
+    }
+
+    fn read(&self, ticker: &Ticker) -> f64 {
+        match self {
+            Field::Price => ticker.price,
+            Field::Volume24h => ticker.volume_24h,
+            Field::Volume1h => ticker.volume_1h,
+            Field::Open => ticker.open,
+            Field::High => ticker.high,
+            Field::Low => ticker.low,
+            Field::Bid => ticker.bid,
+            Field::Ask => ticker.ask,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IndicatorKind {
+    Sma,
+    Rsi,
+}
+
+impl IndicatorKind {
+    fn parse(name: &str) -> Option<IndicatorKind> {
+        match name {
+            "sma" => Some(IndicatorKind::Sma),
+            "rsi" => Some(IndicatorKind::Rsi),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            IndicatorKind::Sma => "sma",
+            IndicatorKind::Rsi => "rsi",
+        }
+    }
+}
+
+/// One indicator call site; each gets its own state per symbol.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndicatorSpec {
+    pub kind: IndicatorKind,
+    pub source: Field,
+    pub period: usize,
+}
+
+impl IndicatorSpec {
+    fn build(&self) -> Box<dyn Indicator> {
+        match self.kind {
+            IndicatorKind::Sma => Box::new(SMA::new(self.period)),
+            IndicatorKind::Rsi => Box::new(RSI::new(self.period)),
+        }
+    }
+
+    fn label(&self) -> String {
+        format!("{}({}, {})", self.kind.name(), self.source.name(), self.period)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BinOp {
+    Add, Sub, Mul, Div,
+    Lt, Le, Gt, Ge, Eq, Ne,
+    And, Or,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Number(f64),
+    Field(Field),
+    Indicator(usize), // Index into `AlertExpression::indicators`
+    Neg(Box<Expr>),
+    Not(Box<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Type {
+    Number,
+    Bool,
+}
+
+// --- Lexer ---
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Op(&'static str),
+    LParen,
+    RParen,
+    Comma,
+    End,
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Number(n) => format!("number {}", n),
+        Token::Ident(s) => format!("'{}'", s),
+        Token::Op(op) => format!("'{}'", op),
+        Token::LParen => "'('".to_string(),
+        Token::RParen => "')'".to_string(),
+        Token::Comma => "','".to_string(),
+        Token::End => "end of expression".to_string(),
+    }
+}
+
+fn tokenize(source: &str) -> MResult<Vec<(Token, usize)>> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        if c.is_ascii_digit() || (c == '.' && chars.get(i + 1).is_some_and(|d| d.is_ascii_digit())) {
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let value = text.parse::<f64>().map_err(|_| syntax_error(source, start, &format!("invalid number '{}'", text)))?;
+            tokens.push((Token::Number(value), start));
+            continue;
+        }
+
+        if c.is_ascii_alphabetic() || c == '_' {
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push((Token::Ident(chars[start..i].iter().collect()), start));
+            continue;
+        }
+
+        let next = chars.get(i + 1).copied();
+        let (token, width) = match (c, next) {
+            ('<', Some('=')) => (Token::Op("<="), 2),
+            ('>', Some('=')) => (Token::Op(">="), 2),
+            ('=', Some('=')) => (Token::Op("=="), 2),
+            ('!', Some('=')) => (Token::Op("!="), 2),
+            ('&', Some('&')) => (Token::Ident("and".to_string()), 2),
+            ('|', Some('|')) => (Token::Ident("or".to_string()), 2),
+            ('<', _) => (Token::Op("<"), 1),
+            ('>', _) => (Token::Op(">"), 1),
+            ('+', _) => (Token::Op("+"), 1),
+            ('-', _) => (Token::Op("-"), 1),
+            ('*', _) => (Token::Op("*"), 1),
+            ('/', _) => (Token::Op("/"), 1),
+            ('(', _) => (Token::LParen, 1),
+            (')', _) => (Token::RParen, 1),
+            (',', _) => (Token::Comma, 1),
+            ('=', _) => return fail(syntax_error(source, start, "unexpected '=' (use '==' to compare)")),
+            _ => return fail(syntax_error(source, start, &format!("unexpected character '{}'", c))),
+        };
+        tokens.push((token, start));
+        i += width;
+    }
+
+    tokens.push((Token::End, chars.len()));
+    unit(tokens)
+}
+
+/// Formats a parse error with a caret under the offending column.
+fn syntax_error(source: &str, column: usize, message: &str) -> BotError {
+    BotError::ConfigurationError(format!(
+        "{} at column {}\n    {}\n    {}^",
+        message, column + 1, source, " ".repeat(column)
+    ))
+}
+
+// --- Parser ---
+
+struct Parser<'a> {
+    source: &'a str,
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    indicators: Vec<IndicatorSpec>,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos].0
+    }
+
+    fn column(&self) -> usize {
+        self.tokens[self.pos].1
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.tokens[self.pos].0.clone();
+        if token != Token::End {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn error<T>(&self, message: &str) -> MResult<T> {
+        fail(syntax_error(self.source, self.column(), message))
+    }
+
+    fn expect(&mut self, expected: Token) -> MResult<()> {
+        if *self.peek() == expected {
+            self.advance();
+            unit(())
+        } else {
+            self.error(&format!("expected {} but found {}", describe(&expected), describe(self.peek())))
+        }
+    }
+
+    fn is_keyword(&self, word: &str) -> bool {
+        matches!(self.peek(), Token::Ident(s) if s == word)
+    }
+
+    /// Checks an operand type, pointing at the operand's first column.
+    fn require(&self, found: Type, wanted: Type, column: usize, context: &str) -> MResult<()> {
+        if found == wanted {
+            return unit(());
+        }
+        let what = if wanted == Type::Bool { "a condition" } else { "a number" };
+        fail(syntax_error(self.source, column, &format!("{} expects {}", context, what)))
+    }
+
+    fn parse_or(&mut self) -> MResult<(Expr, Type)> {
+        let column = self.column();
+        let (mut lhs, mut ty) = self.parse_and()?;
+        while self.is_keyword("or") {
+            self.require(ty, Type::Bool, column, "'or'")?;
+            self.advance();
+            let rhs_column = self.column();
+            let (rhs, rhs_ty) = self.parse_and()?;
+            self.require(rhs_ty, Type::Bool, rhs_column, "'or'")?;
+            lhs = Expr::Binary(BinOp::Or, Box::new(lhs), Box::new(rhs));
+            ty = Type::Bool;
+        }
+        unit((lhs, ty))
+    }
+
+    fn parse_and(&mut self) -> MResult<(Expr, Type)> {
+        let column = self.column();
+        let (mut lhs, mut ty) = self.parse_not()?;
+        while self.is_keyword("and") {
+            self.require(ty, Type::Bool, column, "'and'")?;
+            self.advance();
+            let rhs_column = self.column();
+            let (rhs, rhs_ty) = self.parse_not()?;
+            self.require(rhs_ty, Type::Bool, rhs_column, "'and'")?;
+            lhs = Expr::Binary(BinOp::And, Box::new(lhs), Box::new(rhs));
+            ty = Type::Bool;
+        }
+        unit((lhs, ty))
+    }
+
+    fn parse_not(&mut self) -> MResult<(Expr, Type)> {
+        if self.is_keyword("not") {
+            self.advance();
+            let column = self.column();
+            let (inner, ty) = self.parse_not()?;
+            self.require(ty, Type::Bool, column, "'not'")?;
+            return unit((Expr::Not(Box::new(inner)), Type::Bool));
+        }
+        self.parse_compare()
+    }
+
+    fn parse_compare(&mut self) -> MResult<(Expr, Type)> {
+        let column = self.column();
+        let (lhs, ty) = self.parse_sum()?;
+        let op = match self.peek() {
+            Token::Op("<") => BinOp::Lt,
+            Token::Op("<=") => BinOp::Le,
+            Token::Op(">") => BinOp::Gt,
+            Token::Op(">=") => BinOp::Ge,
+            Token::Op("==") => BinOp::Eq,
+            Token::Op("!=") => BinOp::Ne,
+            _ => return unit((lhs, ty)),
+        };
+        self.require(ty, Type::Number, column, "comparison")?;
+        self.advance();
+        let rhs_column = self.column();
+        let (rhs, rhs_ty) = self.parse_sum()?;
+        self.require(rhs_ty, Type::Number, rhs_column, "comparison")?;
+        unit((Expr::Binary(op, Box::new(lhs), Box::new(rhs)), Type::Bool))
+    }
+
+    fn parse_sum(&mut self) -> MResult<(Expr, Type)> {
+        let column = self.column();
+        let (mut lhs, ty) = self.parse_product()?;
+        loop {
+            let op = match self.peek() {
+                Token::Op("+") => BinOp::Add,
+                Token::Op("-") => BinOp::Sub,
+                _ => return unit((lhs, ty)),
+            };
+            self.require(ty, Type::Number, column, "arithmetic")?;
+            self.advance();
+            let rhs_column = self.column();
+            let (rhs, rhs_ty) = self.parse_product()?;
+            self.require(rhs_ty, Type::Number, rhs_column, "arithmetic")?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+    }
+
+    fn parse_product(&mut self) -> MResult<(Expr, Type)> {
+        let column = self.column();
+        let (mut lhs, ty) = self.parse_unary()?;
+        loop {
+            let op = match self.peek() {
+                Token::Op("*") => BinOp::Mul,
+                Token::Op("/") => BinOp::Div,
+                _ => return unit((lhs, ty)),
+            };
+            self.require(ty, Type::Number, column, "arithmetic")?;
+            self.advance();
+            let rhs_column = self.column();
+            let (rhs, rhs_ty) = self.parse_unary()?;
+            self.require(rhs_ty, Type::Number, rhs_column, "arithmetic")?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+    }
+
+    fn parse_unary(&mut self) -> MResult<(Expr, Type)> {
+        if *self.peek() == Token::Op("-") {
+            self.advance();
+            let column = self.column();
+            let (inner, ty) = self.parse_unary()?;
+            self.require(ty, Type::Number, column, "'-'")?;
+            return unit((Expr::Neg(Box::new(inner)), Type::Number));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> MResult<(Expr, Type)> {
+        let column = self.column();
+        match self.advance() {
+            Token::Number(n) => unit((Expr::Number(n), Type::Number)),
+            Token::LParen => {
+                let inner = self.parse_or()?;
+                self.expect(Token::RParen)?;
+                unit(inner)
+            }
+            Token::Ident(name) => {
+                if let Some(kind) = IndicatorKind::parse(&name) {
+                    return self.parse_call(kind);
+                }
+                if let Some(field) = Field::parse(&name) {
+                    return unit((Expr::Field(field), Type::Number));
+                }
+                fail(syntax_error(self.source, column, &format!("unknown field or function '{}'", name)))
+            }
+            other => fail(syntax_error(self.source, column, &format!("unexpected {}", describe(&other)))),
+        }
+    }
+
+    /// `name(period)` or `name(field, period)`.
+    fn parse_call(&mut self, kind: IndicatorKind) -> MResult<(Expr, Type)> {
+        self.expect(Token::LParen)?;
+
+        let mut source = Field::Price;
+        if let Token::Ident(name) = self.peek().clone() {
+            match Field::parse(&name) {
+                Some(field) => source = field,
+                None => return self.error(&format!("unknown field '{}' in {}()", name, kind.name())),
+            }
+            self.advance();
+            self.expect(Token::Comma)?;
+        }
+
+        let period = match self.peek() {
+            Token::Number(n) if n.fract() == 0.0 && *n >= 1.0 => *n as usize,
+            _ => return self.error(&format!("{}() period must be a positive integer", kind.name())),
+        };
+        self.advance();
+        self.expect(Token::RParen)?;
+
+        let spec = IndicatorSpec { kind, source, period };
+        let slot = match self.indicators.iter().position(|s| *s == spec) {
+            Some(existing) => existing, // Identical calls share state
+            None => {
+                self.indicators.push(spec);
+                self.indicators.len() - 1
+            }
+        };
+        unit((Expr::Indicator(slot), Type::Number))
+    }
+}
+
+// --- Compiled Expression ---
+
+/// A named, parsed and type-checked alert condition.
+#[derive(Debug, Clone)]
+pub struct AlertExpression {
+    pub name: String,
+    pub source: String,
+    root: Expr,
+    indicators: Vec<IndicatorSpec>,
+}
+
+impl AlertExpression {
+    /// Parses `source`; errors are `BotError::ConfigurationError` with a caret
+    /// pointing at the problem.
+    pub fn parse(name: &str, source: &str) -> MResult<Self> {
+        let tokens = tokenize(source)
+            .map_err(|e| with_name(name, e))?;
+        let mut parser = Parser { source, tokens, pos: 0, indicators: Vec::new() };
+
+        let (root, ty) = parser.parse_or().map_err(|e| with_name(name, e))?;
+        if *parser.peek() != Token::End {
+            let msg = format!("unexpected {} after complete expression", describe(parser.peek()));
+            return fail(with_name(name, syntax_error(source, parser.column(), &msg)));
+        }
+        if ty != Type::Bool {
+            return fail(with_name(name, syntax_error(source, 0, "expression must be a condition (use a comparison)")));
+        }
+
+        unit(AlertExpression {
+            name: name.to_string(),
+            source: source.to_string(),
+            root,
+            indicators: parser.indicators,
+        })
+    }
+
+    pub fn indicators(&self) -> &[IndicatorSpec] {
+        &self.indicators
+    }
+}
+
+fn with_name(name: &str, error: BotError) -> BotError {
+    match error {
+        BotError::ConfigurationError(msg) => BotError::ConfigurationError(format!("Alert expression '{}': {}", name, msg)),
+        other => other,
+    }
+}
+
+// --- Evaluation ---
+
+/// Indicator values are `None` until warmed up; any comparison touching a
+/// cold indicator makes the whole expression "not ready".
+struct EvalContext<'a> {
+    ticker: &'a Ticker,
+    values: &'a [Option<f64>],
+}
+
+impl<'a> EvalContext<'a> {
+    fn number(&self, expr: &Expr) -> Option<f64> {
+        match expr {
+            Expr::Number(n) => Some(*n),
+            Expr::Field(field) => Some(field.read(self.ticker)),
+            Expr::Indicator(slot) => self.values[*slot],
+            Expr::Neg(inner) => self.number(inner).map(|v| -v),
+            Expr::Binary(op, lhs, rhs) => {
+                let (a, b) = (self.number(lhs)?, self.number(rhs)?);
+                match op {
+                    BinOp::Add => Some(a + b),
+                    BinOp::Sub => Some(a - b),
+                    BinOp::Mul => Some(a * b),
+                    BinOp::Div => if b == 0.0 { None } else { Some(a / b) },
+                    _ => None,
+                }
+            }
+            Expr::Not(_) => None,
+        }
+    }
+
+    fn condition(&self, expr: &Expr) -> Option<bool> {
+        match expr {
+            Expr::Not(inner) => self.condition(inner).map(|v| !v),
+            Expr::Binary(BinOp::And, lhs, rhs) => Some(self.condition(lhs)? && self.condition(rhs)?),
+            Expr::Binary(BinOp::Or, lhs, rhs) => Some(self.condition(lhs)? || self.condition(rhs)?),
+            Expr::Binary(op, lhs, rhs) => {
+                let (a, b) = (self.number(lhs)?, self.number(rhs)?);
+                match op {
+                    BinOp::Lt => Some(a < b),
+                    BinOp::Le => Some(a <= b),
+                    BinOp::Gt => Some(a > b),
+                    BinOp::Ge => Some(a >= b),
+                    BinOp::Eq => Some((a - b).abs() < 1e-9),
+                    BinOp::Ne => Some((a - b).abs() >= 1e-9),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+/// An expression that evaluated to true on a tick.
+#[derive(Debug, Clone)]
+pub struct ExpressionHit {
+    pub name: String,
+    pub symbol: String,
+    pub source: String,
+    pub values: Vec<(String, f64)>, // Indicator readings at the time of the hit
+    pub timestamp: u64,
+}
+
+/// Evaluates every configured expression against each symbol's ticks,
+/// keeping separate indicator state per (symbol, expression).
+pub struct ExpressionMonitor {
+    expressions: Vec<AlertExpression>,
+    states: HashMap<String, Vec<Vec<Box<dyn Indicator>>>>,
+}
+
+impl ExpressionMonitor {
+    pub fn new(expressions: Vec<AlertExpression>) -> Self {
+        ExpressionMonitor { expressions, states: HashMap::new() }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.expressions.is_empty()
+    }
+
+    /// Feeds one tick and returns the expressions that are true for it.
+    pub fn evaluate(&mut self, ticker: &Ticker) -> Vec<ExpressionHit> {
+        let expressions = &self.expressions;
+        let state = self.states.entry(ticker.symbol.clone()).or_insert_with(|| {
+            expressions.iter()
+                .map(|e| e.indicators.iter().map(|spec| spec.build()).collect())
+                .collect()
+        });
+
+        let mut hits = Vec::new();
+        for (expr, indicators) in expressions.iter().zip(state.iter_mut()) {
+            let values: Vec<Option<f64>> = expr.indicators.iter().zip(indicators.iter_mut())
+                .map(|(spec, ind)| {
+                    ind.update(spec.source.read(ticker));
+                    ind.value()
+                })
+                .collect();
+
+            let ctx = EvalContext { ticker, values: &values };
+            if ctx.condition(&expr.root) == Some(true) {
+                hits.push(ExpressionHit {
+                    name: expr.name.clone(),
+                    symbol: ticker.symbol.clone(),
+                    source: expr.source.clone(),
+                    values: expr.indicators.iter().zip(values.iter())
+                        .filter_map(|(spec, v)| v.map(|v| (spec.label(), v)))
+                        .collect(),
+                    timestamp: ticker.timestamp,
+                });
+            }
+        }
+        hits
+    }
+}
+
+// =================================================================================
+// UNIT TESTS
+// =================================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ticker(price: f64, volume_1h: f64) -> Ticker {
+        Ticker {
+            symbol: "MON/USDT".to_string(),
+            price,
+            volume_24h: volume_1h * 24.0,
+            volume_1h,
+            open: price,
+            high: price,
+            low: price,
+            bid: price - 0.1,
+            ask: price + 0.1,
+            timestamp: 0,
+        }
+    }
+
+    fn config_error(result: MResult<AlertExpression>) -> String {
+        match result {
+            Err(BotError::ConfigurationError(msg)) => msg,
+            other => panic!("expected configuration error, got {:?}", other.map(|e| e.source)),
+        }
+    }
+
+    #[test]
+    fn test_parse_errors_point_at_column() {
+        let msg = config_error(AlertExpression::parse("spike", "volume_1h > 3 * sma(volume_1h, 24"));
+        assert!(msg.starts_with("Alert expression 'spike': expected ')' but found end of expression at column 34"));
+
+        let msg = config_error(AlertExpression::parse("typo", "volume > 10"));
+        assert!(msg.contains("unknown field or function 'volume' at column 1"));
+
+        let msg = config_error(AlertExpression::parse("types", "rsi(14) and price > 1"));
+        assert!(msg.contains("'and' expects a condition at column 1"));
+
+        let msg = config_error(AlertExpression::parse("bare", "price * 2"));
+        assert!(msg.contains("expression must be a condition"));
+    }
+
+    #[test]
+    fn test_evaluates_volume_and_rsi_condition() {
+        let expr = AlertExpression::parse("spike", "volume_1h > 2 * sma(volume_1h, 4) and rsi(2) < 40").unwrap();
+        assert_eq!(expr.indicators().len(), 2);
+        let mut monitor = ExpressionMonitor::new(vec![expr]);
+
+        // Falling prices keep RSI low; volume explodes on the last tick
+        assert!(monitor.evaluate(&ticker(10.0, 100.0)).is_empty());
+        assert!(monitor.evaluate(&ticker(9.0, 100.0)).is_empty());
+        assert!(monitor.evaluate(&ticker(8.0, 100.0)).is_empty());
+        let hits = monitor.evaluate(&ticker(7.0, 2000.0));
+
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].name, "spike");
+        assert_eq!(hits[0].values[0].0, "sma(volume_1h, 4)");
+    }
+}
@@ -0,0 +1,472 @@
+// =================================================================================
+// MODULE: Order Flow Analytics
+// DESCRIPTION:
+// Turns the raw public trade feed into aggressor-side volume statistics.
+// A volume spike alone does not say who is in control; splitting volume by the
+// side that crossed the spread tells buying pressure apart from selling pressure.
+//
+// Key components:
+// - Rolling time window of trades
+// - Buy / Sell (taker) volume and notional
+// - Net delta per window and Cumulative Volume Delta (CVD) since start
+// - Whale detection: oversized prints and same-side sweeps
+// =================================================================================
+
+use crate::monad::{MResult, unit, fail, BotError};
+use crate::exchange::{Trade, OrderSide};
+use std::collections::VecDeque;
+use std::fmt::Display;
+
+/// Snapshot of aggressor-side flow over the aggregator's window.
+#[derive(Debug, Clone)]
+pub struct FlowWindow {
+    pub window_ms: u64,
+    pub buy_volume: f64,
+    pub sell_volume: f64,
+    pub buy_notional: f64,
+    pub sell_notional: f64,
+    pub delta: f64,                   // buy_volume - sell_volume within the window
+    pub cumulative_volume_delta: f64, // Running delta since the aggregator started
+    pub trade_count: usize,
+}
+
+impl FlowWindow {
+    pub fn total_volume(&self) -> f64 {
+        self.buy_volume + self.sell_volume
+    }
+
+    /// Share of window volume initiated by buyers (0.5 when empty).
+    pub fn buy_ratio(&self) -> f64 {
+        let total = self.total_volume();
+        if total > 0.0 { self.buy_volume / total } else { 0.5 }
+    }
+
+    /// Which side dominates the window, if the imbalance exceeds `min_ratio`.
+    pub fn dominant_side(&self, min_ratio: f64) -> Option<OrderSide> {
+        let ratio = self.buy_ratio();
+        if ratio >= min_ratio {
+            Some(OrderSide::Buy)
+        } else if 1.0 - ratio >= min_ratio {
+            Some(OrderSide::Sell)
+        } else {
+            None
+        }
+    }
+}
+
+/// Rolling aggregator of trades over a fixed time window.
+/// Window sums are maintained incrementally as trades enter and expire.
+pub struct TradeFlowAggregator {
+    symbol: String,
+    window_ms: u64,
+    trades: VecDeque<Trade>,
+    buy_volume: f64,
+    sell_volume: f64,
+    buy_notional: f64,
+    sell_notional: f64,
+    cumulative_volume_delta: f64,
+    last_trade_id: Option<u64>,
+    last_timestamp_ms: u64,
+}
+
+impl TradeFlowAggregator {
+    pub fn new(symbol: &str, window_ms: u64) -> Self {
+        TradeFlowAggregator {
+            symbol: symbol.to_string(),
+            window_ms,
+            trades: VecDeque::new(),
+            buy_volume: 0.0,
+            sell_volume: 0.0,
+            buy_notional: 0.0,
+            sell_notional: 0.0,
+            cumulative_volume_delta: 0.0,
+            last_trade_id: None,
+            last_timestamp_ms: 0,
+        }
+    }
+
+    /// Adds a batch of trades (oldest first) and ages the window out against
+    /// the wall clock `now_ms`, so flow decays while the feed is quiet.
+    /// Duplicates from overlapping polls are skipped by id.
+    pub fn ingest(&mut self, trades: &[Trade], now_ms: u64) -> MResult<FlowWindow> {
+        for trade in trades {
+            if trade.symbol != self.symbol {
+                return fail(BotError::StrategyError(format!(
+                    "Trade for {} routed to {} flow aggregator", trade.symbol, self.symbol
+                )));
+            }
+            if let Some(last) = self.last_trade_id {
+                if trade.id <= last {
+                    continue;
+                }
+            }
+            self.push(trade.clone());
+        }
+        self.expire(now_ms.max(self.last_timestamp_ms));
+        unit(self.snapshot())
+    }
+
+    fn push(&mut self, trade: Trade) {
+        let notional = trade.notional();
+        match trade.aggressor {
+            OrderSide::Buy => {
+                self.buy_volume += trade.quantity;
+                self.buy_notional += notional;
+                self.cumulative_volume_delta += trade.quantity;
+            }
+            OrderSide::Sell => {
+                self.sell_volume += trade.quantity;
+                self.sell_notional += notional;
+                self.cumulative_volume_delta -= trade.quantity;
+            }
+        }
+        self.last_trade_id = Some(trade.id);
+        self.last_timestamp_ms = self.last_timestamp_ms.max(trade.timestamp_ms);
+        self.trades.push_back(trade);
+    }
+
+    /// Drops trades older than the window relative to `now_ms`.
+    pub fn expire(&mut self, now_ms: u64) {
+        let cutoff = now_ms.saturating_sub(self.window_ms);
+        while let Some(front) = self.trades.front() {
+            if front.timestamp_ms > cutoff {
+                break;
+            }
+            let trade = self.trades.pop_front().unwrap();
+            let notional = trade.notional();
+            match trade.aggressor {
+                OrderSide::Buy => {
+                    self.buy_volume -= trade.quantity;
+                    self.buy_notional -= notional;
+                }
+                OrderSide::Sell => {
+                    self.sell_volume -= trade.quantity;
+                    self.sell_notional -= notional;
+                }
+            }
+        }
+        // Re-anchor once the window empties so float drift cannot accumulate
+        if self.trades.is_empty() {
+            self.buy_volume = 0.0;
+            self.sell_volume = 0.0;
+            self.buy_notional = 0.0;
+            self.sell_notional = 0.0;
+        }
+    }
+
+    pub fn snapshot(&self) -> FlowWindow {
+        FlowWindow {
+            window_ms: self.window_ms,
+            buy_volume: self.buy_volume,
+            sell_volume: self.sell_volume,
+            buy_notional: self.buy_notional,
+            sell_notional: self.sell_notional,
+            delta: self.buy_volume - self.sell_volume,
+            cumulative_volume_delta: self.cumulative_volume_delta,
+            trade_count: self.trades.len(),
+        }
+    }
+}
+
+// --- Whale Detection ---
+
+/// Whether a whale event was one print or a burst of same-side prints.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WhaleKind {
+    Block, // A single oversized trade
+    Sweep, // Rapid same-side trades walking the book
+}
+
+/// Alert emitted when a large order hits the tape.
+#[derive(Debug, Clone)]
+pub struct WhaleAlert {
+    pub symbol: String,
+    pub kind: WhaleKind,
+    pub side: OrderSide,
+    pub quantity: f64,
+    pub notional: f64,
+    pub trade_count: usize,
+    pub first_price: f64,
+    pub last_price: f64,
+    pub price_impact_bps: f64, // Move in the aggressor's direction vs. the print before the event
+    pub timestamp_ms: u64,     // Time of the first trade in the event
+}
+
+impl Display for WhaleAlert {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:?} {:?} {} | {:.2} @ {:.2}->{:.2} | Notional {:.0} | {} trades | Impact {:+.1}bps",
+            self.kind, self.side, self.symbol, self.quantity, self.first_price, self.last_price,
+            self.notional, self.trade_count, self.price_impact_bps
+        )
+    }
+}
+
+/// Parameters for the whale detector.
+#[derive(Debug, Clone)]
+pub struct WhaleConfig {
+    pub min_notional: f64,     // Absolute size that always qualifies
+    pub percentile: f64,       // e.g. 0.99: also qualify above this share of recent trade sizes
+    pub sample_size: usize,    // Recent trades used for the percentile
+    pub cluster_gap_ms: u64,   // Max gap between same-side trades of one sweep
+}
+
+/// Running cluster of consecutive same-side trades.
+struct TradeCluster {
+    side: OrderSide,
+    quantity: f64,
+    notional: f64,
+    count: usize,
+    first_price: f64,
+    last_price: f64,
+    reference_price: f64,
+    first_ms: u64,
+    last_ms: u64,
+}
+
+/// Flags trades above an absolute notional or a percentile of recent sizes,
+/// merging rapid same-side prints into a single sweep event.
+pub struct WhaleDetector {
+    symbol: String,
+    config: WhaleConfig,
+    recent_notionals: VecDeque<f64>,
+    cluster: Option<TradeCluster>,
+    last_price: Option<f64>,
+}
+
+impl WhaleDetector {
+    pub fn new(symbol: &str, config: WhaleConfig) -> Self {
+        WhaleDetector {
+            symbol: symbol.to_string(),
+            recent_notionals: VecDeque::with_capacity(config.sample_size),
+            config,
+            cluster: None,
+            last_price: None,
+        }
+    }
+
+    /// Current qualifying notional: the lower of the absolute floor and the
+    /// percentile cut (once enough samples exist), since either one qualifies.
+    pub fn threshold(&self) -> f64 {
+        match self.percentile_notional() {
+            Some(p) => p.min(self.config.min_notional),
+            None => self.config.min_notional,
+        }
+    }
+
+    fn percentile_notional(&self) -> Option<f64> {
+        if self.recent_notionals.len() < self.config.sample_size.min(100) {
+            return None;
+        }
+        let mut sorted: Vec<f64> = self.recent_notionals.iter().copied().collect();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        let rank = (self.config.percentile.clamp(0.0, 1.0) * (sorted.len() - 1) as f64).round() as usize;
+        Some(sorted[rank])
+    }
+
+    /// Processes a batch of trades (oldest first) and returns completed events.
+    /// The threshold is evaluated against history *before* the batch.
+    pub fn process(&mut self, trades: &[Trade]) -> Vec<WhaleAlert> {
+        let threshold = self.threshold();
+        let mut alerts = Vec::new();
+
+        for trade in trades {
+            let extends = match &self.cluster {
+                Some(c) => c.side == trade.aggressor && trade.timestamp_ms.saturating_sub(c.last_ms) <= self.config.cluster_gap_ms,
+                None => false,
+            };
+
+            if extends {
+                let c = self.cluster.as_mut().unwrap();
+                c.quantity += trade.quantity;
+                c.notional += trade.notional();
+                c.count += 1;
+                c.last_price = trade.price;
+                c.last_ms = trade.timestamp_ms;
+            } else {
+                if let Some(alert) = self.close_cluster(threshold) {
+                    alerts.push(alert);
+                }
+                self.cluster = Some(TradeCluster {
+                    side: trade.aggressor.clone(),
+                    quantity: trade.quantity,
+                    notional: trade.notional(),
+                    count: 1,
+                    first_price: trade.price,
+                    last_price: trade.price,
+                    reference_price: self.last_price.unwrap_or(trade.price),
+                    first_ms: trade.timestamp_ms,
+                    last_ms: trade.timestamp_ms,
+                });
+            }
+
+            if self.recent_notionals.len() >= self.config.sample_size {
+                self.recent_notionals.pop_front();
+            }
+            self.recent_notionals.push_back(trade.notional());
+            self.last_price = Some(trade.price);
+        }
+
+        alerts
+    }
+
+    /// Emits the open cluster once no same-side trade can extend it anymore.
+    pub fn flush(&mut self, now_ms: u64) -> Option<WhaleAlert> {
+        let expired = match &self.cluster {
+            Some(c) => now_ms.saturating_sub(c.last_ms) > self.config.cluster_gap_ms,
+            None => false,
+        };
+        if expired { self.close_cluster(self.threshold()) } else { None }
+    }
+
+    fn close_cluster(&mut self, threshold: f64) -> Option<WhaleAlert> {
+        let c = self.cluster.take()?;
+        if c.notional < threshold {
+            return None;
+        }
+
+        let direction = if c.side == OrderSide::Buy { 1.0 } else { -1.0 };
+        let impact = if c.reference_price > 0.0 {
+            (c.last_price - c.reference_price) / c.reference_price * 10_000.0 * direction
+        } else {
+            0.0
+        };
+
+        Some(WhaleAlert {
+            symbol: self.symbol.clone(),
+            kind: if c.count == 1 { WhaleKind::Block } else { WhaleKind::Sweep },
+            side: c.side,
+            quantity: c.quantity,
+            notional: c.notional,
+            trade_count: c.count,
+            first_price: c.first_price,
+            last_price: c.last_price,
+            price_impact_bps: impact,
+            timestamp_ms: c.first_ms,
+        })
+    }
+}
+
+// =================================================================================
+// UNIT TESTS
+// =================================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(id: u64, price: f64, quantity: f64, aggressor: OrderSide, timestamp_ms: u64) -> Trade {
+        Trade { symbol: "MONUSDT".to_string(), id, price, quantity, aggressor, timestamp_ms }
+    }
+
+    #[test]
+    fn test_flow_split_delta_and_expiry() {
+        let mut flow = TradeFlowAggregator::new("MONUSDT", 10_000);
+        let batch = vec![
+            trade(1, 10.0, 3.0, OrderSide::Buy, 1_000),
+            trade(2, 10.0, 1.0, OrderSide::Sell, 2_000),
+            trade(3, 11.0, 2.0, OrderSide::Buy, 3_000),
+        ];
+        let window = flow.ingest(&batch, 3_000).unwrap();
+        assert_eq!((window.buy_volume, window.sell_volume), (5.0, 1.0));
+        assert_eq!(window.buy_notional, 52.0);
+        assert_eq!(window.delta, 4.0);
+        assert_eq!(window.cumulative_volume_delta, 4.0);
+
+        // Overlapping poll: already-seen ids are skipped
+        let window = flow.ingest(&batch[1..], 4_000).unwrap();
+        assert_eq!(window.trade_count, 3);
+
+        // The first two trades leave the window; CVD keeps the full history
+        let window = flow.ingest(&[trade(4, 11.0, 4.0, OrderSide::Sell, 12_500)], 12_500).unwrap();
+        assert_eq!((window.buy_volume, window.sell_volume), (2.0, 4.0));
+        assert_eq!(window.delta, -2.0);
+        assert_eq!(window.cumulative_volume_delta, 0.0);
+        assert_eq!(window.dominant_side(0.6), Some(OrderSide::Sell));
+    }
+
+    #[test]
+    fn test_quiet_feed_ages_out_on_wall_clock() {
+        let mut flow = TradeFlowAggregator::new("MONUSDT", 10_000);
+        flow.ingest(&[trade(1, 10.0, 5.0, OrderSide::Buy, 1_000)], 1_000).unwrap();
+
+        let window = flow.ingest(&[], 20_000).unwrap();
+        assert_eq!(window.trade_count, 0);
+        assert_eq!(window.delta, 0.0);
+        assert_eq!(window.cumulative_volume_delta, 5.0);
+    }
+
+    #[test]
+    fn test_foreign_symbol_is_rejected() {
+        let mut flow = TradeFlowAggregator::new("MONUSDT", 10_000);
+        let mut other = trade(1, 10.0, 1.0, OrderSide::Buy, 1_000);
+        other.symbol = "BTCUSDT".to_string();
+        assert!(flow.ingest(&[other], 1_000).is_err());
+    }
+
+    fn whale_config(min_notional: f64) -> WhaleConfig {
+        WhaleConfig { min_notional, percentile: 0.9, sample_size: 10, cluster_gap_ms: 100 }
+    }
+
+    #[test]
+    fn test_whale_absolute_threshold() {
+        let mut detector = WhaleDetector::new("MONUSDT", whale_config(500.0));
+        assert!(detector.process(&[trade(1, 1.0, 100.0, OrderSide::Buy, 0)]).is_empty());
+        assert!(detector.process(&[trade(2, 1.0, 600.0, OrderSide::Sell, 1_000)]).is_empty());
+
+        // The small print closes below the floor; the large one qualifies on flush
+        let alert = detector.flush(2_000).unwrap();
+        assert_eq!(alert.kind, WhaleKind::Block);
+        assert_eq!(alert.side, OrderSide::Sell);
+        assert_eq!(alert.notional, 600.0);
+    }
+
+    #[test]
+    fn test_whale_percentile_threshold() {
+        let mut detector = WhaleDetector::new("MONUSDT", whale_config(500.0));
+        // Ten spaced prints sized 10..1: the 90th percentile is 9
+        let history: Vec<Trade> = (0..10)
+            .map(|i| trade(i + 1, 1.0, (10 - i) as f64, OrderSide::Buy, i * 1_000))
+            .collect();
+        assert!(detector.process(&history).is_empty());
+        assert_eq!(detector.threshold(), 9.0);
+
+        // Far below the absolute floor, but above the recent percentile
+        assert!(detector.process(&[trade(11, 1.0, 9.5, OrderSide::Buy, 20_000)]).is_empty());
+        let alert = detector.flush(21_000).unwrap();
+        assert_eq!(alert.notional, 9.5);
+
+        detector.process(&[trade(12, 1.0, 5.0, OrderSide::Buy, 30_000)]);
+        assert!(detector.flush(31_000).is_none());
+    }
+
+    #[test]
+    fn test_whale_sweep_clustering_and_impact() {
+        let mut detector = WhaleDetector::new("MONUSDT", whale_config(100.0));
+        let trades = vec![
+            trade(1, 100.0, 0.1, OrderSide::Sell, 0), // Reference print
+            trade(2, 100.0, 0.4, OrderSide::Buy, 1_000),
+            trade(3, 100.5, 0.4, OrderSide::Buy, 1_050),
+            trade(4, 101.0, 0.4, OrderSide::Buy, 1_100),
+            trade(5, 101.0, 0.4, OrderSide::Buy, 1_300), // Past the gap: starts a new cluster
+        ];
+        let alerts = detector.process(&trades);
+        assert_eq!(alerts.len(), 1);
+        let sweep = &alerts[0];
+        assert_eq!(sweep.kind, WhaleKind::Sweep);
+        assert_eq!(sweep.trade_count, 3);
+        assert_eq!((sweep.first_price, sweep.last_price), (100.0, 101.0));
+        assert!((sweep.notional - 120.6).abs() < 1e-9);
+        assert!((sweep.price_impact_bps - 100.0).abs() < 1e-9);
+        assert_eq!(sweep.timestamp_ms, 1_000);
+
+        // The lone trailing buy is below the floor
+        assert!(detector.flush(2_000).is_none());
+
+        // A sell that pushes the price down counts as positive impact
+        detector.process(&[trade(6, 99.0, 2.0, OrderSide::Sell, 3_000)]);
+        let block = detector.flush(4_000).unwrap();
+        assert_eq!(block.kind, WhaleKind::Block);
+        assert!((block.price_impact_bps - 200.0 / 101.0 * 100.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,354 @@
+// =================================================================================
+// MODULE: Grid Strategy
+// DESCRIPTION:
+// Works a ladder of resting limit orders between a lower and upper price.
+// On the first tick, buys are placed at every level below the reference
+// (current) price. Each filled buy re-arms as a sell one level up, and each
+// filled sell re-arms as a buy one level down, so every completed pair earns
+// one grid step. Levels hold at most one order.
+//
+// The grid shuts down (cancelling everything it has open) as soon as price
+// leaves [lower, upper]; inventory is kept and reported, not liquidated.
+//
+// Orders go through `Strategy::order_actions`, so the grid needs an exchange
+// with real order lifecycles (the paper exchange, or a live venue).
+// =================================================================================
+
+use crate::monad::{MResult, unit, fail, BotError, log_info};
+use crate::exchange::{Ticker, OrderSide, OrderType, OrderStatus, OrderRequest, Order, Fill};
+use crate::trader::{Strategy, Signal, OrderAction};
+
+#[derive(Debug, Clone)]
+pub struct GridConfig {
+    pub lower: f64,
+    pub upper: f64,
+    pub levels: usize,  // Price levels from lower to upper, both included
+    pub quantity: f64,  // Base quantity per level
+}
+
+impl GridConfig {
+    pub fn validate(&self) -> MResult<()> {
+        if !self.lower.is_finite() || !self.upper.is_finite() || self.lower <= 0.0 || self.upper <= self.lower {
+            return fail(BotError::ConfigurationError(format!("Grid range {} - {} is invalid", self.lower, self.upper)));
+        }
+        if self.levels < 2 {
+            return fail(BotError::ConfigurationError("Grid needs at least 2 levels".to_string()));
+        }
+        if !self.quantity.is_finite() || self.quantity <= 0.0 {
+            return fail(BotError::ConfigurationError(format!("Grid quantity {} must be positive", self.quantity)));
+        }
+        unit(())
+    }
+
+    pub fn step(&self) -> f64 {
+        (self.upper - self.lower) / (self.levels - 1) as f64
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GridState {
+    Idle,                       // Waiting for the first tick
+    Running { reference: f64 }, // Price the ladder was built around
+    Stopped { reason: String },
+}
+
+/// The order working one level.
+#[derive(Debug, Clone)]
+struct LevelOrder {
+    client_id: String,
+    order_id: Option<String>, // Known once the exchange acknowledges
+    side: OrderSide,
+    quantity: f64,
+    filled: f64,
+    filled_notional: f64,
+    cost: f64, // Entry price of the buy a sell is paired with
+    cancel_requested: bool,
+}
+
+pub struct GridStrategy {
+    config: GridConfig,
+    prices: Vec<f64>,
+    slots: Vec<Option<LevelOrder>>,
+    state: GridState,
+    symbol: String,
+    queued: Vec<OrderAction>,
+    completed: Vec<(usize, OrderSide, f64)>, // Levels filled since the last tick, with average price
+    next_seq: u64,
+    inventory: f64,       // Base bought minus base sold by the grid
+    realized_profit: f64, // Sell proceeds minus paired buy cost, before fees
+    fees: f64,
+    round_trips: u32,
+}
+
+impl GridStrategy {
+    pub fn new(config: GridConfig) -> MResult<Self> {
+        config.validate()?;
+        let step = config.step();
+        let prices: Vec<f64> = (0..config.levels).map(|i| config.lower + step * i as f64).collect();
+        unit(GridStrategy {
+            slots: vec![None; prices.len()],
+            prices,
+            config,
+            state: GridState::Idle,
+            symbol: String::new(),
+            queued: Vec::new(),
+            completed: Vec::new(),
+            next_seq: 1,
+            inventory: 0.0,
+            realized_profit: 0.0,
+            fees: 0.0,
+            round_trips: 0,
+        })
+    }
+
+    /// Realized profit net of fees.
+    pub fn grid_profit(&self) -> f64 {
+        self.realized_profit - self.fees
+    }
+
+    pub fn open_orders(&self) -> usize {
+        self.slots.iter().filter(|s| s.is_some()).count()
+    }
+
+    /// Queues an order at `level` unless the level is already working one.
+    fn arm(&mut self, level: usize, side: OrderSide, cost: f64) {
+        if self.slots[level].is_some() {
+            log_info(&format!("[GRID] Level {} ({:.2}) busy, not re-arming {:?}", level, self.prices[level], side));
+            return;
+        }
+        let client_id = format!("grid-{}-{}", level, self.next_seq);
+        self.next_seq += 1;
+        self.queued.push(OrderAction::Place(OrderRequest {
+            client_id: client_id.clone(),
+            symbol: self.symbol.clone(),
+            side: side.clone(),
+            order_type: OrderType::Limit,
+            quantity: self.config.quantity,
+            price: Some(self.prices[level]),
+        }));
+        self.slots[level] = Some(LevelOrder {
+            client_id,
+            order_id: None,
+            side,
+            quantity: self.config.quantity,
+            filled: 0.0,
+            filled_notional: 0.0,
+            cost,
+            cancel_requested: false,
+        });
+    }
+
+    fn stop(&mut self, reason: String) {
+        log_info(&format!("[GRID] Shutting down: {}", reason));
+        // Orders not yet sent are simply dropped
+        for action in self.queued.drain(..) {
+            if let OrderAction::Place(request) = action {
+                for slot in self.slots.iter_mut() {
+                    if slot.as_ref().is_some_and(|o| o.client_id == request.client_id) {
+                        *slot = None;
+                    }
+                }
+            }
+        }
+        self.state = GridState::Stopped { reason };
+    }
+
+    fn level_of(&self, client_id: &str) -> Option<usize> {
+        self.slots.iter().position(|s| s.as_ref().is_some_and(|o| o.client_id == client_id))
+    }
+}
+
+impl Strategy for GridStrategy {
+    /// The grid trades only through `order_actions`.
+    fn process_tick(&mut self, _ticker: &Ticker) -> MResult<Option<Signal>> {
+        unit(None)
+    }
+
+    fn order_actions(&mut self, ticker: &Ticker) -> Vec<OrderAction> {
+        let price = ticker.price;
+        let in_range = price >= self.config.lower && price <= self.config.upper;
+
+        match self.state {
+            GridState::Idle if !in_range => {
+                self.stop(format!("start price {:.2} outside {:.2} - {:.2}", price, self.config.lower, self.config.upper));
+            }
+            GridState::Idle => {
+                self.symbol = ticker.symbol.clone();
+                self.state = GridState::Running { reference: price };
+                let below: Vec<usize> = (0..self.prices.len()).filter(|i| self.prices[*i] < price).collect();
+                for level in below {
+                    self.arm(level, OrderSide::Buy, 0.0);
+                }
+                log_info(&format!("[GRID] Started around {:.2}: {} buys, step {:.2}", price, self.queued.len(), self.config.step()));
+            }
+            GridState::Running { .. } if !in_range => {
+                let side = if price < self.config.lower { "below" } else { "above" };
+                self.stop(format!("price {:.2} {} range {:.2} - {:.2}", price, side, self.config.lower, self.config.upper));
+            }
+            _ => {}
+        }
+
+        // Re-arm once all of the tick's fills are in, so a gap through several
+        // levels frees each target level before it is reused
+        if matches!(self.state, GridState::Running { .. }) {
+            for (level, side, avg_price) in std::mem::take(&mut self.completed) {
+                match side {
+                    OrderSide::Buy if level + 1 < self.prices.len() => self.arm(level + 1, OrderSide::Sell, avg_price),
+                    OrderSide::Buy => {} // Top level: held as inventory
+                    OrderSide::Sell if level > 0 => self.arm(level - 1, OrderSide::Buy, 0.0),
+                    OrderSide::Sell => {}
+                }
+            }
+        } else {
+            self.completed.clear();
+        }
+
+        // A stopped grid cancels whatever the exchange has acknowledged
+        if let GridState::Stopped { .. } = self.state {
+            for order in self.slots.iter_mut().flatten() {
+                if let (Some(order_id), false) = (&order.order_id, order.cancel_requested) {
+                    order.cancel_requested = true;
+                    self.queued.push(OrderAction::Cancel { order_id: order_id.clone() });
+                }
+            }
+        }
+        std::mem::take(&mut self.queued)
+    }
+
+    fn observe_order(&mut self, order: &Order) {
+        let level = match self.level_of(&order.client_id) {
+            Some(level) => level,
+            None => return,
+        };
+        match order.status {
+            OrderStatus::Rejected => {
+                log_info(&format!("[GRID] Level {} ({:.2}) rejected: {}", level, self.prices[level], order.reason.as_deref().unwrap_or("-")));
+                self.slots[level] = None;
+            }
+            OrderStatus::Canceled => self.slots[level] = None,
+            _ => {
+                if let Some(slot) = self.slots[level].as_mut() {
+                    slot.order_id = Some(order.id.clone());
+                }
+            }
+        }
+    }
+
+    fn observe_fill(&mut self, fill: &Fill) {
+        let level = match self.level_of(&fill.client_id) {
+            Some(level) => level,
+            None => return,
+        };
+        let slot = match self.slots[level].as_mut() {
+            Some(slot) => slot,
+            None => return,
+        };
+        slot.filled += fill.quantity;
+        slot.filled_notional += fill.notional();
+        self.fees += fill.fee;
+        match fill.side {
+            OrderSide::Buy => self.inventory += fill.quantity,
+            OrderSide::Sell => {
+                self.inventory -= fill.quantity;
+                self.realized_profit += (fill.price - slot.cost) * fill.quantity;
+            }
+        }
+        if slot.filled + 1e-12 < slot.quantity {
+            return; // Partially filled; the level keeps working
+        }
+
+        if slot.side == OrderSide::Sell {
+            self.round_trips += 1;
+        }
+        self.completed.push((level, slot.side.clone(), slot.filled_notional / slot.filled));
+        self.slots[level] = None;
+    }
+
+    fn status_line(&self) -> Option<String> {
+        let state = match &self.state {
+            GridState::Idle => "waiting for first price".to_string(),
+            GridState::Running { reference } => format!("running around {:.2}", reference),
+            GridState::Stopped { reason } => format!("stopped ({})", reason),
+        };
+        Some(format!("Grid {} | {:.2} - {:.2} x{} | {} open | inventory {:.4} | profit {:.2} (fees {:.2}) | {} round trips",
+            state, self.config.lower, self.config.upper, self.config.levels, self.open_orders(),
+            self.inventory, self.grid_profit(), self.fees, self.round_trips))
+    }
+}
+
+// =================================================================================
+// UNIT TESTS
+// =================================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::{ExchangeClient, BinanceClient};
+    use crate::paper::PaperExchange;
+    use std::sync::Arc;
+
+    fn ticker(price: f64) -> Ticker {
+        Ticker {
+            symbol: "BTC/USDT".to_string(),
+            price,
+            volume_24h: 0.0,
+            volume_1h: 0.0,
+            open: price,
+            high: price,
+            low: price,
+            bid: price,
+            ask: price,
+            timestamp: 0,
+        }
+    }
+
+    /// One engine tick: match resting orders, report fills, run the grid's actions.
+    fn tick(paper: &PaperExchange, grid: &mut GridStrategy, price: f64) {
+        for fill in paper.on_price("BTC/USDT", price) {
+            grid.observe_fill(&fill);
+        }
+        for action in grid.order_actions(&ticker(price)) {
+            let order = match &action {
+                OrderAction::Place(request) => paper.place_order(request).unwrap(),
+                OrderAction::Cancel { order_id } => paper.cancel_order("BTC/USDT", order_id).unwrap(),
+            };
+            grid.observe_order(&order);
+        }
+    }
+
+    #[test]
+    fn test_grid_round_trips_and_shutdown() {
+        let paper = PaperExchange::new(Arc::new(BinanceClient::new("k", "s")), 0.0)
+            .with_balance("USDT", 10_000.0);
+        let config = GridConfig { lower: 90.0, upper: 110.0, levels: 5, quantity: 1.0 }; // 90, 95, 100, 105, 110
+        let mut grid = GridStrategy::new(config).unwrap();
+
+        tick(&paper, &mut grid, 101.0);
+        assert_eq!(grid.state, GridState::Running { reference: 101.0 });
+        assert_eq!(paper.fetch_open_orders("BTC/USDT").unwrap().len(), 3); // Buys at 90, 95, 100
+
+        // Dip fills the 100 buy, which re-arms as a sell at 105
+        tick(&paper, &mut grid, 99.0);
+        assert_eq!(grid.inventory, 1.0);
+        let open = paper.fetch_open_orders("BTC/USDT").unwrap();
+        assert!(open.iter().any(|o| o.side == OrderSide::Sell && o.price == Some(105.0)));
+
+        // Rally fills the sell (one step of profit) and re-arms the buy at 100
+        tick(&paper, &mut grid, 105.5);
+        assert_eq!(grid.inventory, 0.0);
+        assert!((grid.grid_profit() - 5.0).abs() < 1e-9);
+        assert_eq!(paper.fetch_open_orders("BTC/USDT").unwrap().len(), 3);
+
+        // Buy again at 95, then price falls out of the range: everything is cancelled
+        tick(&paper, &mut grid, 94.0);
+        tick(&paper, &mut grid, 89.0); // Fills the 90 buy on the way out
+        assert!(matches!(grid.state, GridState::Stopped { .. }));
+        assert_eq!(paper.fetch_open_orders("BTC/USDT").unwrap().len(), 0);
+        assert_eq!(grid.open_orders(), 0);
+        assert_eq!(grid.inventory, 3.0); // 100, 95 and 90 buys are held
+        assert!(grid.status_line().unwrap().contains("1 round trips"));
+
+        // A stopped grid stays quiet even if price comes back
+        tick(&paper, &mut grid, 100.0);
+        assert_eq!(paper.fetch_open_orders("BTC/USDT").unwrap().len(), 0);
+    }
+}
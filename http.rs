@@ -0,0 +1,180 @@
+// =================================================================================
+// MODULE: Minimal HTTP Client
+// DESCRIPTION:
+// Blocking HTTP/1.1 over `std::net::TcpStream` for outbound integrations
+// (webhooks, chat APIs). Deliberately tiny: one request per connection,
+// `Connection: close`, Content-Length or chunked bodies.
+//
+// NOTE: There is no TLS in a std-only build. `https://` endpoints are rejected
+// with a clear error; point the integration at a local TLS-terminating relay
+// (or a local stand-in during testing) instead.
+// =================================================================================
+
+use crate::monad::{MResult, unit, fail, BotError};
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+impl HttpResponse {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+pub fn get(url: &str, timeout: Duration) -> MResult<HttpResponse> {
+    request("GET", url, None, timeout)
+}
+
+pub fn post_json(url: &str, body: &str, timeout: Duration) -> MResult<HttpResponse> {
+    request("POST", url, Some(body), timeout)
+}
+
+/// Checks that this client can reach `url`, so a misconfigured integration
+/// fails at startup rather than on its first request.
+pub fn validate_url(url: &str) -> MResult<()> {
+    parse_url(url).map(|_| ())
+}
+
+/// Splits `http://host[:port]/path` into (host, port, path).
+fn parse_url(url: &str) -> MResult<(String, u16, String)> {
+    if url.starts_with("https://") {
+        return fail(BotError::ConfigurationError(format!(
+            "HTTPS is not supported without TLS; use a local relay for {}", url
+        )));
+    }
+    let rest = match url.strip_prefix("http://") {
+        Some(r) => r,
+        None => return fail(BotError::ConfigurationError(format!("Unsupported URL: {}", url))),
+    };
+
+    let (authority, path) = match rest.find('/') {
+        Some(i) => (&rest[..i], rest[i..].to_string()),
+        None => (rest, "/".to_string()),
+    };
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((h, p)) => {
+            let port = p.parse().map_err(|_| BotError::ConfigurationError(format!("Invalid port in URL: {}", url)))?;
+            (h.to_string(), port)
+        }
+        None => (authority.to_string(), 80),
+    };
+    if host.is_empty() {
+        return fail(BotError::ConfigurationError(format!("Missing host in URL: {}", url)));
+    }
+    unit((host, port, path))
+}
+
+fn request(method: &str, url: &str, body: Option<&str>, timeout: Duration) -> MResult<HttpResponse> {
+    let (host, port, path) = parse_url(url)?;
+    let net_err = |e: std::io::Error| BotError::NetworkFailure(format!("{} {}: {}", method, host, e));
+
+    let mut stream = connect(&host, port, timeout).map_err(net_err)?;
+    stream.set_read_timeout(Some(timeout)).map_err(net_err)?;
+    stream.set_write_timeout(Some(timeout)).map_err(net_err)?;
+
+    let body = body.unwrap_or("");
+    let head = format!(
+        "{} {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        method, path, host, body.len()
+    );
+    stream.write_all(head.as_bytes()).map_err(net_err)?;
+    stream.write_all(body.as_bytes()).map_err(net_err)?;
+
+    let mut raw = Vec::new();
+    stream.read_to_end(&mut raw).map_err(net_err)?;
+    parse_response(&raw)
+}
+
+/// Connects to the first reachable address of `host`, each attempt bounded
+/// by `timeout` so an unreachable endpoint cannot stall the caller.
+fn connect(host: &str, port: u16, timeout: Duration) -> std::io::Result<TcpStream> {
+    let mut last_err = None;
+    for addr in (host, port).to_socket_addrs()? {
+        match TcpStream::connect_timeout(&addr, timeout) {
+            Ok(stream) => return Ok(stream),
+            Err(e) => last_err = Some(e),
+        }
+    }
+    Err(last_err.unwrap_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "no address resolved")))
+}
+
+/// Splits head from body on raw bytes; the body is only decoded to text once
+/// any chunked framing is removed, so chunk sizes are counted in bytes.
+fn parse_response(raw: &[u8]) -> MResult<HttpResponse> {
+    let split = match raw.windows(4).position(|w| w == b"\r\n\r\n") {
+        Some(i) => i,
+        None => return fail(BotError::NetworkFailure("Malformed HTTP response".to_string())),
+    };
+    let head = String::from_utf8_lossy(&raw[..split]);
+    let body = &raw[split + 4..];
+
+    let status = head.lines().next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|code| code.parse().ok())
+        .ok_or_else(|| BotError::NetworkFailure("Missing HTTP status".to_string()))?;
+
+    let chunked = head.lines().any(|l| {
+        let l = l.to_ascii_lowercase();
+        l.starts_with("transfer-encoding:") && l.contains("chunked")
+    });
+    let body = if chunked { decode_chunked(body)? } else { body.to_vec() };
+
+    unit(HttpResponse { status, body: String::from_utf8_lossy(&body).into_owned() })
+}
+
+fn decode_chunked(mut body: &[u8]) -> MResult<Vec<u8>> {
+    let mut out = Vec::new();
+    loop {
+        let line_end = body.windows(2).position(|w| w == b"\r\n")
+            .ok_or_else(|| BotError::NetworkFailure("Truncated chunked body".to_string()))?;
+        let size_line = String::from_utf8_lossy(&body[..line_end]);
+        let size = usize::from_str_radix(size_line.split(';').next().unwrap_or("").trim(), 16)
+            .map_err(|_| BotError::NetworkFailure("Invalid chunk size".to_string()))?;
+        let rest = &body[line_end + 2..];
+        if size == 0 {
+            return unit(out);
+        }
+        if rest.len() < size {
+            return fail(BotError::NetworkFailure(format!("Truncated chunk: {} of {} bytes", rest.len(), size)));
+        }
+        out.extend_from_slice(&rest[..size]);
+        body = rest[size..].strip_prefix(b"\r\n").unwrap_or(&rest[size..]);
+    }
+}
+
+// =================================================================================
+// UNIT TESTS
+// =================================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_boundary_inside_multibyte_character() {
+        // "héllo" with the two-byte 'é' split across chunks
+        let mut raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n".to_vec();
+        raw.extend_from_slice(b"2\r\nh\xc3\r\n4\r\n\xa9llo\r\n0\r\n\r\n");
+        let response = parse_response(&raw).unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body, "h\u{e9}llo");
+    }
+
+    #[test]
+    fn test_cut_off_chunk_is_an_error() {
+        let raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n10\r\n{\"ok\":true}";
+        assert!(parse_response(raw).is_err());
+    }
+
+    #[test]
+    fn test_content_length_body() {
+        let response = parse_response(b"HTTP/1.1 404 Not Found\r\nContent-Length: 2\r\n\r\n{}").unwrap();
+        assert!(!response.is_success());
+        assert_eq!(response.body, "{}");
+    }
+}
@@ -13,12 +13,48 @@
 // cargo run
 // =================================================================================
 
+// Indicators keep their trading-desk names (SMA, RSI, ATR, ...)
+#![allow(clippy::upper_case_acronyms)]
+
 mod monad;
 mod exchange;
 mod trader;
+mod volume;
+mod flow;
+mod scanner;
+mod json;
+mod http;
+mod alerts;
+mod alert_rules;
+mod expr;
+mod commands;
+mod profile;
+mod candles;
+mod snapshot;
+mod regime;
+mod composite;
+mod orderbook;
+mod mean_reversion;
+mod paper;
+mod grid;
+mod market_maker;
+mod dca;
 mod bot;
 
-use crate::bot::{TradingEngine, BotConfig};
+use crate::bot::{TradingEngine, BotConfig, StrategyKind, PaperConfig};
+use crate::trader::{MarketRegime, RegimeAdjustment, RsiSmoothing};
+use crate::grid::GridConfig;
+use crate::market_maker::MarketMakerConfig;
+use crate::dca::{DcaConfig, DipBoost};
+use crate::volume::SpikeSeverity;
+use crate::candles::Timeframe;
+use crate::composite::CombineMode;
+use crate::profile::BinSize;
+use crate::alerts::{AlertConfig, AlertSeverity};
+use crate::alert_rules::AlertRulesConfig;
+use crate::expr::AlertExpression;
+use crate::commands::{CommandConfig, spawn_listener};
+use crate::monad::{MResult, BotError};
 use std::env;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
@@ -46,22 +82,272 @@ struct ConfigLoader;
 impl ConfigLoader {
     /// Loads configuration from Environment Variables or defaults.
     /// In a real app, this would use the `dotenv` crate.
-    fn load() -> BotConfig {
+    fn load() -> MResult<BotConfig> {
         println!("[INIT] Loading configuration parameters...");
         
         let symbol = env::var("BOT_SYMBOL").unwrap_or_else(|_| "BTC/USDT".to_string());
         let api_key = env::var("BOT_API_KEY").unwrap_or_else(|_| "x799-secure-key-placeholder".to_string());
         let secret = env::var("BOT_SECRET").unwrap_or_else(|_| "s888-secure-secret-placeholder".to_string());
+        let baseline_path = env::var("BOT_BASELINE_PATH").unwrap_or_else(|_| "volume_baseline.txt".to_string());
+        let snapshot_path = env::var("BOT_SNAPSHOT_PATH").unwrap_or_else(|_| "strategy_snapshot.json".to_string());
+        let snapshot_max_age_secs = env::var("BOT_SNAPSHOT_MAX_AGE_SECS").ok().and_then(|v| v.parse().ok()).unwrap_or(900);
+        let watchlist: Vec<String> = env::var("BOT_WATCHLIST")
+            .unwrap_or_default()
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+        let scan_only = env::var("BOT_SCAN_ONLY").map(|v| v == "1" || v.eq_ignore_ascii_case("true")).unwrap_or(false);
         
+        let strategies = env::var("BOT_STRATEGY")
+            .unwrap_or_else(|_| "breakout".to_string())
+            .split(',')
+            .filter(|s| !s.trim().is_empty())
+            .map(StrategyKind::parse)
+            .collect::<MResult<Vec<_>>>()?;
+        let strategies = if strategies.is_empty() { vec![StrategyKind::Breakout] } else { strategies };
+        let strategy_combine = CombineMode::parse(&env::var("BOT_STRATEGY_COMBINE").unwrap_or_else(|_| "vote".to_string()))?;
+        let rsi_smoothing = RsiSmoothing::parse(&env::var("BOT_RSI_SMOOTHING").unwrap_or_else(|_| "wilder".to_string()))?;
+        let profile_bin = BinSize::parse(&env::var("BOT_PROFILE_BIN").unwrap_or_else(|_| "0.05%".to_string()))?;
+
         println!("[INIT] Target Symbol: {}", symbol);
+        let alerts = Self::load_alerts()?;
+        let alert_expressions = Self::load_expressions()?;
+
+        if !watchlist.is_empty() {
+            println!("[INIT] Watchlist: {} symbols{}", watchlist.len(), if scan_only { " (scan only)" } else { "" });
+        }
         println!("[INIT] API Key Loaded: ***{}", &api_key[api_key.len().min(4)..]);
         
-        BotConfig {
+        Ok(BotConfig {
             symbol,
             api_key,
             secret_key: secret,
             strategy_risk_factor: 1.0,
+            regime_risk: Self::load_regime_risk()?,
+            strategies,
+            strategy_combine,
+            rsi_smoothing,
+            grid: Self::load_grid()?,
+            market_maker: Self::load_market_maker()?,
+            dca: Self::load_dca()?,
+            paper: Self::load_paper(),
+            baseline_path,
+            snapshot_path,
+            snapshot_max_age_secs,
+            profile_bin,
+            decision_log_path: env::var("BOT_DECISION_LOG").ok().filter(|v| !v.trim().is_empty()),
+            watchlist,
+            scan_only,
+            alerts,
+            alert_expressions,
+        })
+    }
+
+    /// Per-regime risk scaling as `risk_multiplier,stop_multiplier`,
+    /// e.g. `BOT_RISK_VOLATILE="0.3,2.0"`; unset regimes keep the standard values.
+    fn load_regime_risk() -> MResult<Vec<(MarketRegime, RegimeAdjustment)>> {
+        let mut overrides = Vec::new();
+        for regime in MarketRegime::ALL {
+            let key = format!("BOT_RISK_{}", format!("{:?}", regime).to_ascii_uppercase());
+            let value = match env::var(&key) {
+                Ok(value) => value,
+                Err(_) => continue,
+            };
+            let parts: Vec<Option<f64>> = value.split(',').map(|p| p.trim().parse().ok()).collect();
+            let adjustment = match parts.as_slice() {
+                [Some(risk_multiplier), Some(stop_multiplier)] => RegimeAdjustment { risk_multiplier: *risk_multiplier, stop_multiplier: *stop_multiplier },
+                _ => return Err(BotError::ConfigurationError(format!("{} must be 'risk_multiplier,stop_multiplier', got '{}'", key, value))),
+            };
+            adjustment.validate(regime)?;
+            println!("[INIT] {:?} risk: x{} size, x{} stop", regime, adjustment.risk_multiplier, adjustment.stop_multiplier);
+            overrides.push((regime, adjustment));
+        }
+        Ok(overrides)
+    }
+
+    /// The grid is configured when its range is set, e.g. `BOT_GRID_LOWER=1900 BOT_GRID_UPPER=2100`.
+    fn load_grid() -> MResult<Option<GridConfig>> {
+        let number = |key: &str| env::var(key).ok().and_then(|v| v.trim().parse::<f64>().ok());
+        let (lower, upper) = match (number("BOT_GRID_LOWER"), number("BOT_GRID_UPPER")) {
+            (Some(lower), Some(upper)) => (lower, upper),
+            _ => return Ok(None),
+        };
+        let levels = match env::var("BOT_GRID_LEVELS") {
+            Ok(raw) => raw.trim().parse::<usize>().map_err(|_| BotError::ConfigurationError(
+                format!("BOT_GRID_LEVELS must be a whole number of levels, got '{}'", raw)))?,
+            Err(_) => 11,
+        };
+        let grid = GridConfig {
+            lower,
+            upper,
+            levels,
+            quantity: number("BOT_GRID_QTY").unwrap_or(0.01),
+        };
+        grid.validate()?;
+        println!("[INIT] Grid: {} - {} in {} levels of {}", grid.lower, grid.upper, grid.levels, grid.quantity);
+        Ok(Some(grid))
+    }
+
+    /// Quote size and inventory cap override the standard market-making settings.
+    fn load_market_maker() -> MResult<Option<MarketMakerConfig>> {
+        let number = |key: &str| env::var(key).ok().and_then(|v| v.trim().parse::<f64>().ok());
+        let (size, cap) = (number("BOT_MM_SIZE"), number("BOT_MM_MAX_INVENTORY"));
+        if size.is_none() && cap.is_none() {
+            return Ok(None);
+        }
+        let standard = MarketMakerConfig::standard();
+        let config = MarketMakerConfig {
+            order_size: size.unwrap_or(standard.order_size),
+            max_inventory: cap.unwrap_or(standard.max_inventory),
+            ..standard
+        };
+        config.validate()?;
+        Ok(Some(config))
+    }
+
+    /// DCA is configured by its amount and budget; `BOT_DCA_DIP_PCT` adds the
+    /// dip boost, measured against the 24-bar 1h average and confirmed by High volume.
+    fn load_dca() -> MResult<Option<DcaConfig>> {
+        let number = |key: &str| env::var(key).ok().and_then(|v| v.trim().parse::<f64>().ok());
+        let (quote_amount, budget) = match (number("BOT_DCA_AMOUNT"), number("BOT_DCA_BUDGET")) {
+            (Some(amount), Some(budget)) => (amount, budget),
+            _ => return Ok(None),
+        };
+        let dca = DcaConfig {
+            quote_amount,
+            interval_secs: number("BOT_DCA_INTERVAL_SECS").unwrap_or(86_400.0) as u64,
+            budget,
+            // Paper fills are charged the paper fee, so that is the default allowance
+            fee_rate: number("BOT_DCA_FEE").or_else(|| number("BOT_PAPER_FEE")).unwrap_or(0.001),
+            dip: number("BOT_DCA_DIP_PCT").map(|drop_pct| DipBoost {
+                timeframe: Timeframe::H1,
+                ma_period: 24,
+                drop_pct,
+                multiplier: number("BOT_DCA_DIP_MULTIPLIER").unwrap_or(2.0),
+                min_severity: SpikeSeverity::High,
+            }),
+        };
+        dca.validate()?;
+        println!("[INIT] DCA: {} every {}s up to {}{}", dca.quote_amount, dca.interval_secs, dca.budget,
+            dca.dip.as_ref().map_or(String::new(), |d| format!(", x{} on {}% dips", d.multiplier, d.drop_pct)));
+        Ok(Some(dca))
+    }
+
+    /// `BOT_PAPER=true` routes orders to the in-memory paper exchange.
+    fn load_paper() -> Option<PaperConfig> {
+        let enabled = env::var("BOT_PAPER").map(|v| v == "1" || v.eq_ignore_ascii_case("true")).unwrap_or(false);
+        if !enabled {
+            return None;
+        }
+        let number = |key: &str, default: f64| env::var(key).ok().and_then(|v| v.trim().parse().ok()).unwrap_or(default);
+        Some(PaperConfig {
+            quote_balance: number("BOT_PAPER_BALANCE", 10_000.0),
+            base_balance: number("BOT_PAPER_BASE", 0.0),
+            fee_rate: number("BOT_PAPER_FEE", 0.001),
+        })
+    }
+
+    /// Chat commands are enabled when a bot token and at least one allowed chat are set.
+    fn load_commands() -> MResult<Option<CommandConfig>> {
+        let token = match env::var("BOT_TELEGRAM_TOKEN").ok().filter(|t| !t.is_empty()) {
+            Some(token) => token,
+            None => return Ok(None),
+        };
+        let allowed_chat_ids: Vec<i64> = env::var("BOT_TELEGRAM_ALLOWED_CHATS")
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|id| id.trim().parse().ok())
+            .collect();
+        if allowed_chat_ids.is_empty() {
+            return Ok(None);
         }
+        Ok(Some(CommandConfig {
+            api_base: Self::load_telegram_api()?,
+            token,
+            allowed_chat_ids,
+            poll_timeout_secs: 25,
+        }))
+    }
+
+    /// Base URL for the Telegram Bot API. The std-only HTTP client has no TLS,
+    /// so this must be a plain `http://` relay in front of api.telegram.org;
+    /// there is deliberately no default.
+    fn load_telegram_api() -> MResult<String> {
+        let base = env::var("BOT_TELEGRAM_API").ok().filter(|v| !v.trim().is_empty()).ok_or_else(|| {
+            BotError::ConfigurationError(
+                "BOT_TELEGRAM_API is required when Telegram is enabled: set it to a plain-http TLS relay for api.telegram.org, e.g. http://127.0.0.1:8443".to_string()
+            )
+        })?;
+        http::validate_url(&base).map_err(|_| BotError::ConfigurationError(format!(
+            "BOT_TELEGRAM_API must be a plain http:// relay (no TLS in this build), got '{}'", base
+        )))?;
+        Ok(base)
+    }
+
+    /// Every `BOT_ALERT_EXPR_<NAME>` variable defines one alert expression,
+    /// e.g. `BOT_ALERT_EXPR_VOLUME_DIP="volume_1h > 3 * sma(volume_1h, 24) and rsi(14) < 40"`.
+    fn load_expressions() -> MResult<Vec<AlertExpression>> {
+        let mut vars: Vec<(String, String)> = env::vars()
+            .filter_map(|(key, value)| key.strip_prefix("BOT_ALERT_EXPR_").map(|name| (name.to_ascii_lowercase(), value)))
+            .collect();
+        vars.sort();
+
+        let mut expressions = Vec::with_capacity(vars.len());
+        for (name, source) in vars {
+            let expression = AlertExpression::parse(&name, &source)?;
+            println!("[INIT] Alert expression '{}': {} ({} indicators tracked per symbol)", name, source, expression.indicators().len());
+            expressions.push(expression);
+        }
+        Ok(expressions)
+    }
+
+    /// Alert sinks are enabled by setting their destination variable.
+    fn load_alerts() -> MResult<AlertConfig> {
+        let optional = |key: &str| env::var(key).ok().filter(|v| !v.trim().is_empty());
+        let severity = |key: &str, default: AlertSeverity| match env::var(key) {
+            Ok(v) => AlertSeverity::parse(&v).map_err(|_| BotError::ConfigurationError(
+                format!("{} must be info, warning or critical, got '{}'", key, v))),
+            Err(_) => Ok(default),
+        };
+        let seconds = |key: &str, default: u64| env::var(key).ok().and_then(|v| v.parse().ok()).unwrap_or(default);
+
+        // Rules are on by default; BOT_ALERT_RULES=off delivers every alert raw
+        let rules = if env::var("BOT_ALERT_RULES").map(|v| v.eq_ignore_ascii_case("off")).unwrap_or(false) {
+            None
+        } else {
+            let defaults = AlertRulesConfig::standard();
+            Some(AlertRulesConfig {
+                dedup_window_secs: seconds("BOT_ALERT_DEDUP_SECS", defaults.dedup_window_secs),
+                cooldown_secs: seconds("BOT_ALERT_COOLDOWN_SECS", defaults.cooldown_secs),
+                digest_interval_secs: seconds("BOT_ALERT_DIGEST_SECS", defaults.digest_interval_secs),
+                digest_max_severity: severity("BOT_ALERT_DIGEST_MAX", defaults.digest_max_severity)?,
+                escalate_after: seconds("BOT_ALERT_ESCALATE_AFTER", defaults.escalate_after as u64) as u32,
+                persistence_gap_secs: seconds("BOT_ALERT_PERSISTENCE_SECS", defaults.persistence_gap_secs),
+                audit_path: optional("BOT_ALERT_AUDIT"),
+            })
+        };
+
+        let telegram_token = optional("BOT_TELEGRAM_TOKEN");
+        let telegram_chat_id = optional("BOT_TELEGRAM_CHAT_ID");
+        let telegram_api_base = if telegram_token.is_some() && telegram_chat_id.is_some() {
+            Self::load_telegram_api()?
+        } else {
+            String::new()
+        };
+
+        Ok(AlertConfig {
+            stdout_min: severity("BOT_ALERT_STDOUT_MIN", AlertSeverity::Info)?,
+            jsonl_path: optional("BOT_ALERT_LOG"),
+            jsonl_min: severity("BOT_ALERT_LOG_MIN", AlertSeverity::Info)?,
+            webhook_url: optional("BOT_ALERT_WEBHOOK"),
+            webhook_min: severity("BOT_ALERT_WEBHOOK_MIN", AlertSeverity::Warning)?,
+            telegram_api_base,
+            telegram_token,
+            telegram_chat_id,
+            telegram_min: severity("BOT_TELEGRAM_MIN", AlertSeverity::Warning)?,
+            rules,
+        })
     }
 }
 
@@ -83,11 +369,36 @@ fn main() {
     println!("[SYSTEM] Signal handler registered. Press Ctrl+C to stop (if supported).");
 
     // 3. Load Config
-    let config = ConfigLoader::load();
+    let config = match ConfigLoader::load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("[FATAL] Invalid configuration:\n{}", e);
+            return;
+        }
+    };
 
     // 4. Instantiate Engine
     // The engine owns the high-level components.
-    let mut engine = TradingEngine::new(config);
+    let command_config = match ConfigLoader::load_commands() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("[FATAL] Invalid configuration:\n{}", e);
+            return;
+        }
+    };
+    let mut engine = match TradingEngine::new(config) {
+        Ok(engine) => engine,
+        Err(e) => {
+            eprintln!("[FATAL] Invalid configuration:\n{}", e);
+            return;
+        }
+    };
+
+    // Optional chat control: commands arrive from a background long-poll thread
+    let commands = command_config.map(|c| {
+        println!("[SYSTEM] Chat commands enabled for {} chat(s).", c.allowed_chat_ids.len());
+        spawn_listener(c)
+    });
 
     // 5. Main Event Loop
     println!("[SYSTEM] Starting Main Event Loop...");
@@ -100,6 +411,19 @@ fn main() {
         // The tick method returns a MResult, so we handle top-level errors here.
         let result = engine.tick();
 
+        // Operator commands received since the last tick
+        if let Some((inbox, replier)) = &commands {
+            while let Ok(incoming) = inbox.try_recv() {
+                let reply = match &incoming.command {
+                    Ok(command) => engine.execute_command(command),
+                    Err(usage) => usage.clone(),
+                };
+                if let Err(e) = replier.reply(incoming.chat_id, &reply) {
+                    eprintln!("[WARN] Command reply failed: {:?}", e);
+                }
+            }
+        }
+
         if let Err(e) = result {
             eprintln!("[FATAL] Unhandled error in main loop: {:?}", e);
             // In a real system, we might restart the engine or panic depending on severity.
@@ -107,8 +431,11 @@ fn main() {
         }
 
         // Periodic Status Report (every 10 ticks)
-        if tick_count % 10 == 0 {
+        if tick_count.is_multiple_of(10) {
             engine.report_status();
+            if let Err(e) = engine.save_state() {
+                eprintln!("[WARN] Failed to persist state: {:?}", e);
+            }
         }
 
         // Rate Limiting / Loop Control
@@ -127,5 +454,8 @@ fn main() {
     println!("\n[SYSTEM] Shutdown signal received.");
     println!("[SYSTEM] Closing network connections...");
     println!("[SYSTEM] Saving final state...");
+    if let Err(e) = engine.save_state() {
+        eprintln!("[WARN] Failed to persist state: {:?}", e);
+    }
     println!("[SYSTEM] Goodbye.");
 }
@@ -0,0 +1,416 @@
+// =================================================================================
+// MODULE: Market-Making Strategy
+// DESCRIPTION:
+// Keeps one bid and one ask resting around the mid price. The half-spread
+// follows recent mid volatility (never below a configured floor), and both
+// quotes are shifted against the current inventory so that a long book quotes
+// lower (selling more readily) and a short book quotes higher.
+//
+// Quotes are cancelled and replaced once the desired price drifts more than
+// `requote_bps` from the working one. Inventory is capped both ways counting
+// every open order as if it filled, and a new quote is never placed at or
+// through our own order on the other side (including one still being cancelled).
+//
+// Orders go through `Strategy::order_actions`, so the strategy needs an
+// exchange with real order lifecycles (the paper exchange, or a live venue).
+// =================================================================================
+
+use std::collections::VecDeque;
+use crate::monad::{MResult, unit, fail, BotError, log_info};
+use crate::exchange::{Ticker, OrderBook, OrderSide, OrderType, OrderStatus, OrderRequest, Order, Fill};
+use crate::trader::{Strategy, Signal, OrderAction};
+
+#[derive(Debug, Clone)]
+pub struct MarketMakerConfig {
+    pub order_size: f64,       // Base quantity per quote
+    pub max_inventory: f64,    // Absolute base position allowed long or short
+    pub vol_window: usize,     // Mid samples used for volatility
+    pub vol_multiplier: f64,   // Half-spread in units of per-tick volatility
+    pub min_spread_bps: f64,   // Floor on the full spread
+    pub skew_bps: f64,         // Reservation price shift at full inventory
+    pub requote_bps: f64,      // Drift that triggers a cancel / replace
+    pub book_depth: u32,       // Levels requested from the engine; the touch sets the mid
+}
+
+impl MarketMakerConfig {
+    /// 0.01 per quote, at most 0.1 either way; spread at least 10 bps or 2x
+    /// the volatility of the last 20 mids; 5 bps skew at full inventory.
+    pub fn standard() -> Self {
+        MarketMakerConfig {
+            order_size: 0.01,
+            max_inventory: 0.1,
+            vol_window: 20,
+            vol_multiplier: 2.0,
+            min_spread_bps: 10.0,
+            skew_bps: 5.0,
+            requote_bps: 2.0,
+            book_depth: 5,
+        }
+    }
+
+    pub fn validate(&self) -> MResult<()> {
+        if !(self.order_size > 0.0 && self.max_inventory >= self.order_size) {
+            return fail(BotError::ConfigurationError(format!(
+                "Quote size {} must be positive and within the inventory cap {}", self.order_size, self.max_inventory)));
+        }
+        if self.vol_window < 3 {
+            return fail(BotError::ConfigurationError("Volatility window needs at least 3 samples".to_string()));
+        }
+        if !self.min_spread_bps.is_finite() || self.min_spread_bps <= 0.0 || self.vol_multiplier < 0.0 || self.skew_bps < 0.0 || self.requote_bps < 0.0 {
+            return fail(BotError::ConfigurationError("Spread floor must be positive; multiplier, skew and requote threshold non-negative".to_string()));
+        }
+        unit(())
+    }
+}
+
+/// Price and quantity of a quote we want working; `None` when the cap forbids it.
+type QuoteTarget = Option<(f64, f64)>;
+
+/// One of our resting orders.
+#[derive(Debug, Clone)]
+struct Quote {
+    client_id: String,
+    order_id: Option<String>, // Known once the exchange acknowledges
+    side: OrderSide,
+    price: f64,
+    quantity: f64,
+    filled: f64,
+    cancel_requested: bool,
+}
+
+impl Quote {
+    fn remaining(&self) -> f64 {
+        (self.quantity - self.filled).max(0.0)
+    }
+}
+
+pub struct MarketMakerStrategy {
+    config: MarketMakerConfig,
+    mids: VecDeque<f64>,
+    touch: Option<(f64, f64)>, // Best bid / ask from the latest book
+    quotes: Vec<Quote>,
+    next_seq: u64,
+    inventory: f64,  // Base bought minus base sold by the strategy
+    cash: f64,       // Quote received minus quote spent, net of fees
+    fees: f64,
+    fill_count: u32,
+    last_mid: f64,
+    last_half_spread_bps: f64,
+}
+
+impl MarketMakerStrategy {
+    pub fn new(config: MarketMakerConfig) -> MResult<Self> {
+        config.validate()?;
+        unit(MarketMakerStrategy {
+            mids: VecDeque::with_capacity(config.vol_window),
+            config,
+            touch: None,
+            quotes: Vec::new(),
+            next_seq: 1,
+            inventory: 0.0,
+            cash: 0.0,
+            fees: 0.0,
+            fill_count: 0,
+            last_mid: 0.0,
+            last_half_spread_bps: 0.0,
+        })
+    }
+
+    /// Cash flow plus inventory marked at the last mid.
+    pub fn pnl(&self) -> f64 {
+        self.cash + self.inventory * self.last_mid
+    }
+
+    /// Standard deviation of tick-to-tick mid returns, in bps.
+    fn volatility_bps(&self) -> Option<f64> {
+        if self.mids.len() < self.config.vol_window {
+            return None;
+        }
+        let returns: Vec<f64> = self.mids.iter().zip(self.mids.iter().skip(1))
+            .map(|(a, b)| (b / a).ln() * 10_000.0)
+            .collect();
+        let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+        let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / returns.len() as f64;
+        Some(variance.sqrt())
+    }
+
+    /// Bid and ask we want working now. Live quotes count against the cap
+    /// only with `count_live`.
+    fn targets(&self, mid: f64, vol_bps: f64, count_live: bool) -> (QuoteTarget, QuoteTarget) {
+        let c = &self.config;
+        let half = self.half_spread_bps(vol_bps) / 10_000.0;
+        let skew = (self.inventory / c.max_inventory).clamp(-1.0, 1.0) * c.skew_bps / 10_000.0;
+        let reservation = mid * (1.0 - skew);
+        let (mut bid, mut ask) = (reservation * (1.0 - half), reservation * (1.0 + half));
+
+        // Join the touch at most; the skew never turns a quote into a taker
+        if let Some((best_bid, best_ask)) = self.touch {
+            bid = bid.min(best_bid);
+            ask = ask.max(best_ask);
+        }
+
+        // Worst case counts every open order on the side as filled
+        let open = |side: OrderSide| self.quotes.iter()
+            .filter(|q| q.side == side && (count_live || q.cancel_requested))
+            .map(Quote::remaining)
+            .sum::<f64>();
+        let bid_room = c.max_inventory - self.inventory - open(OrderSide::Buy);
+        let ask_room = c.max_inventory + self.inventory - open(OrderSide::Sell);
+        let size = |room: f64| if room >= c.order_size * 0.1 { Some(room.min(c.order_size)) } else { None };
+        (size(bid_room).map(|q| (bid, q)), size(ask_room).map(|q| (ask, q)))
+    }
+
+    fn half_spread_bps(&self, vol_bps: f64) -> f64 {
+        (self.config.min_spread_bps / 2.0).max(vol_bps * self.config.vol_multiplier)
+    }
+
+    /// Cancels the live quote on `side` when it is no longer wanted, has
+    /// drifted or is larger than the cap now allows; returns true while a
+    /// live quote remains on the side.
+    fn review(&mut self, side: &OrderSide, target: QuoteTarget, actions: &mut Vec<OrderAction>) -> bool {
+        let requote = self.config.requote_bps;
+        let quote = match self.quotes.iter_mut().find(|q| &q.side == side && !q.cancel_requested) {
+            Some(quote) => quote,
+            None => return false,
+        };
+        let order_id = match &quote.order_id {
+            Some(id) => id.clone(),
+            None => return true, // Not acknowledged yet
+        };
+        let drifted = target.is_none_or(|(price, quantity)| {
+            ((price - quote.price) / quote.price).abs() * 10_000.0 > requote || quote.remaining() > quantity + 1e-12
+        });
+        if !drifted {
+            return true;
+        }
+        quote.cancel_requested = true;
+        actions.push(OrderAction::Cancel { order_id });
+        false
+    }
+
+    fn place(&mut self, symbol: &str, side: OrderSide, price: f64, quantity: f64, actions: &mut Vec<OrderAction>) {
+        // Never at or through our own order on the other side
+        let crosses = self.quotes.iter().any(|q| q.side != side && match side {
+            OrderSide::Buy => price >= q.price,
+            OrderSide::Sell => price <= q.price,
+        });
+        if crosses {
+            return;
+        }
+        let client_id = format!("mm-{}", self.next_seq);
+        self.next_seq += 1;
+        actions.push(OrderAction::Place(OrderRequest {
+            client_id: client_id.clone(),
+            symbol: symbol.to_string(),
+            side: side.clone(),
+            order_type: OrderType::Limit,
+            quantity,
+            price: Some(price),
+        }));
+        self.quotes.push(Quote { client_id, order_id: None, side, price, quantity, filled: 0.0, cancel_requested: false });
+    }
+
+    fn quote_of(&self, client_id: &str) -> Option<usize> {
+        self.quotes.iter().position(|q| q.client_id == client_id)
+    }
+}
+
+impl Strategy for MarketMakerStrategy {
+    /// Quotes are managed only through `order_actions`.
+    fn process_tick(&mut self, _ticker: &Ticker) -> MResult<Option<Signal>> {
+        unit(None)
+    }
+
+    fn book_depth(&self) -> u32 {
+        self.config.book_depth
+    }
+
+    fn observe_book(&mut self, book: &OrderBook) {
+        self.touch = match (book.bids.first(), book.asks.first()) {
+            (Some(bid), Some(ask)) if bid.price < ask.price => Some((bid.price, ask.price)),
+            _ => None,
+        };
+    }
+
+    fn order_actions(&mut self, ticker: &Ticker) -> Vec<OrderAction> {
+        let mid = match self.touch.take() {
+            Some((bid, ask)) => (bid + ask) / 2.0,
+            None if ticker.bid > 0.0 && ticker.ask > ticker.bid => (ticker.bid + ticker.ask) / 2.0,
+            None => ticker.price,
+        };
+        if !mid.is_finite() || mid <= 0.0 {
+            return Vec::new();
+        }
+        if self.mids.len() == self.config.vol_window {
+            self.mids.pop_front();
+        }
+        self.mids.push_back(mid);
+        self.last_mid = mid;
+
+        let vol_bps = match self.volatility_bps() {
+            Some(vol) => vol,
+            None => return Vec::new(), // Warming up
+        };
+        self.last_half_spread_bps = self.half_spread_bps(vol_bps);
+
+        // Cancel first so cancelled size no longer blocks the cap next tick
+        let mut actions = Vec::new();
+        let (bid, ask) = self.targets(mid, vol_bps, false);
+        let bid_live = self.review(&OrderSide::Buy, bid, &mut actions);
+        let ask_live = self.review(&OrderSide::Sell, ask, &mut actions);
+
+        // Replacement size is re-checked against the cap with the cancelled order still counted
+        let (bid, ask) = self.targets(mid, vol_bps, true);
+        if let (false, Some((price, quantity))) = (bid_live, bid) {
+            self.place(&ticker.symbol, OrderSide::Buy, price, quantity, &mut actions);
+        }
+        if let (false, Some((price, quantity))) = (ask_live, ask) {
+            self.place(&ticker.symbol, OrderSide::Sell, price, quantity, &mut actions);
+        }
+        actions
+    }
+
+    fn observe_order(&mut self, order: &Order) {
+        let index = match self.quote_of(&order.client_id) {
+            Some(index) => index,
+            None => return,
+        };
+        match order.status {
+            OrderStatus::Rejected => {
+                log_info(&format!("[MM] {:?} quote at {:.2} rejected: {}", order.side, self.quotes[index].price, order.reason.as_deref().unwrap_or("-")));
+                self.quotes.remove(index);
+            }
+            OrderStatus::Canceled => { self.quotes.remove(index); }
+            // Filled quotes are removed once their fills are booked
+            _ => self.quotes[index].order_id = Some(order.id.clone()),
+        }
+    }
+
+    fn observe_fill(&mut self, fill: &Fill) {
+        let index = match self.quote_of(&fill.client_id) {
+            Some(index) => index,
+            None => return,
+        };
+        self.fees += fill.fee;
+        self.fill_count += 1;
+        match fill.side {
+            OrderSide::Buy => {
+                self.inventory += fill.quantity;
+                self.cash -= fill.notional() + fill.fee;
+            }
+            OrderSide::Sell => {
+                self.inventory -= fill.quantity;
+                self.cash += fill.notional() - fill.fee;
+            }
+        }
+        let quote = &mut self.quotes[index];
+        quote.filled += fill.quantity;
+        if quote.filled + 1e-12 >= quote.quantity {
+            self.quotes.remove(index);
+        }
+    }
+
+    fn status_line(&self) -> Option<String> {
+        let show = |side: OrderSide| self.quotes.iter()
+            .find(|q| q.side == side && !q.cancel_requested)
+            .map_or("-".to_string(), |q| format!("{:.2} x {}", q.price, q.remaining()));
+        Some(format!("Market maker | bid {} / ask {} | half-spread {:.1} bps | inventory {:.4} (cap {}) | pnl {:.2} (fees {:.2}) | {} fills",
+            show(OrderSide::Buy), show(OrderSide::Sell), self.last_half_spread_bps,
+            self.inventory, self.config.max_inventory, self.pnl(), self.fees, self.fill_count))
+    }
+}
+
+// =================================================================================
+// UNIT TESTS
+// =================================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::{ExchangeClient, BinanceClient};
+    use crate::paper::PaperExchange;
+    use std::sync::Arc;
+
+    fn ticker(price: f64) -> Ticker {
+        Ticker {
+            symbol: "BTC/USDT".to_string(),
+            price,
+            volume_24h: 0.0,
+            volume_1h: 0.0,
+            open: price,
+            high: price,
+            low: price,
+            bid: price - 0.01,
+            ask: price + 0.01,
+            timestamp: 0,
+        }
+    }
+
+    /// One engine tick: match resting orders, report fills, run the quoting actions.
+    fn tick(paper: &PaperExchange, mm: &mut MarketMakerStrategy, price: f64) {
+        for fill in paper.on_price("BTC/USDT", price) {
+            mm.observe_fill(&fill);
+        }
+        for action in mm.order_actions(&ticker(price)) {
+            let order = match &action {
+                OrderAction::Place(request) => paper.place_order(request).unwrap(),
+                OrderAction::Cancel { order_id } => paper.cancel_order("BTC/USDT", order_id).unwrap(),
+            };
+            mm.observe_order(&order);
+        }
+
+        // Our bids always sit below our asks
+        let open = paper.fetch_open_orders("BTC/USDT").unwrap();
+        let best_bid = open.iter().filter(|o| o.side == OrderSide::Buy).filter_map(|o| o.price).fold(0.0, f64::max);
+        let lowest_ask = open.iter().filter(|o| o.side == OrderSide::Sell).filter_map(|o| o.price).fold(f64::MAX, f64::min);
+        assert!(best_bid < lowest_ask, "crossed: bid {} ask {}", best_bid, lowest_ask);
+    }
+
+    fn quote(paper: &PaperExchange, side: OrderSide) -> Option<f64> {
+        paper.fetch_open_orders("BTC/USDT").unwrap().iter().find(|o| o.side == side).and_then(|o| o.price)
+    }
+
+    #[test]
+    fn test_quotes_skew_and_cap_inventory() {
+        let paper = PaperExchange::new(Arc::new(BinanceClient::new("k", "s")), 0.0)
+            .with_balance("USDT", 10_000.0)
+            .with_balance("BTC", 10.0);
+        let config = MarketMakerConfig {
+            order_size: 1.0,
+            max_inventory: 2.0,
+            vol_window: 5,
+            vol_multiplier: 1.0,
+            min_spread_bps: 20.0,
+            skew_bps: 10.0,
+            requote_bps: 2.0,
+            book_depth: 0,
+        };
+        let mut mm = MarketMakerStrategy::new(config).unwrap();
+
+        // Quiet warm-up: quotes appear once the window is full, at the 10 bps floor
+        for _ in 0..5 {
+            tick(&paper, &mut mm, 100.0);
+        }
+        let (bid, ask) = (quote(&paper, OrderSide::Buy).unwrap(), quote(&paper, OrderSide::Sell).unwrap());
+        assert!((bid - 99.9).abs() < 1e-9 && (ask - 100.1).abs() < 1e-9, "{} / {}", bid, ask);
+
+        // A dip fills the bid; the long book now quotes lower, leaning toward selling
+        tick(&paper, &mut mm, 99.85);
+        assert_eq!(mm.inventory, 1.0);
+        let (bid, ask) = (quote(&paper, OrderSide::Buy).unwrap(), quote(&paper, OrderSide::Sell).unwrap());
+        assert!(ask < 100.1, "ask was not replaced: {}", ask);
+        assert!(99.85 - bid > ask - 99.85, "no skew: bid {} ask {}", bid, ask);
+        assert_eq!(paper.fetch_open_orders("BTC/USDT").unwrap().len(), 2);
+
+        // A second fill reaches the cap: only the ask is quoted
+        tick(&paper, &mut mm, 99.5);
+        assert_eq!(mm.inventory, 2.0);
+        assert!(quote(&paper, OrderSide::Buy).is_none());
+        assert!(quote(&paper, OrderSide::Sell).is_some());
+
+        // A rally sells inventory back and the bid returns
+        tick(&paper, &mut mm, 100.0);
+        assert_eq!(mm.inventory, 1.0);
+        assert!(quote(&paper, OrderSide::Buy).is_some());
+        assert!(mm.status_line().unwrap().contains("3 fills"));
+    }
+}
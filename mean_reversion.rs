@@ -0,0 +1,497 @@
+// =================================================================================
+// MODULE: Mean-Reversion Strategy
+// DESCRIPTION:
+// Counter-trend companion to `VolumeBreakoutStrategy`: buys when a closed bar
+// touches the lower Bollinger band with RSI oversold, and exits once price has
+// reverted toward the middle band. Long-only; one position at a time.
+//
+// The position is what actually filled: the order placed on the tick of a
+// signal is adopted as the strategy's own and its fills are booked by order
+// id. A refused entry leaves the strategy flat; the exit sells the filled
+// quantity.
+//
+// Entries are skipped in trending regimes (Bullish / Bearish), where a lower
+// band touch is more often continuation than exhaustion. Exits are never
+// filtered.
+// =================================================================================
+
+use crate::monad::{MResult, unit, fail, BotError, log_info};
+use crate::exchange::{Ticker, OrderSide, OrderStatus, Order, Fill};
+use crate::candles::{Candle, Timeframe, TimeframeIndicator};
+use crate::regime::RegimeReading;
+use crate::json::JsonValue;
+use crate::snapshot::{self, read_optional_f64};
+use crate::trader::{Strategy, Signal, SignalContext, StrengthComponent, MarketRegime, clamp01, weighted_strength, MultiIndicator, BollingerBands, RSI, RsiSmoothing};
+
+#[derive(Debug, Clone)]
+pub struct MeanReversionConfig {
+    pub timeframe: Timeframe,
+    pub bb_period: usize,
+    pub bb_multiplier: f64,
+    pub rsi_period: usize,
+    pub rsi_smoothing: RsiSmoothing,
+    pub oversold: f64,          // RSI at or below this confirms an entry
+    pub entry_percent_b: f64,   // %B at or below this counts as a lower band touch
+    pub exit_percent_b: f64,    // %B at or above this closes the position (0.5 = middle band)
+    pub trend_filter: bool,     // Skip entries while the regime is Bullish or Bearish
+}
+
+impl MeanReversionConfig {
+    /// BB(20, 2) and RSI(14) on 5m bars; enter at the lower band with RSI ≤ 30,
+    /// exit at the middle band; no entries in trends.
+    pub fn standard() -> Self {
+        MeanReversionConfig {
+            timeframe: Timeframe::M5,
+            bb_period: 20,
+            bb_multiplier: 2.0,
+            rsi_period: 14,
+            rsi_smoothing: RsiSmoothing::Wilder,
+            oversold: 30.0,
+            entry_percent_b: 0.0,
+            exit_percent_b: 0.5,
+            trend_filter: true,
+        }
+    }
+
+    pub fn validate(&self) -> MResult<()> {
+        if self.bb_period < 2 || self.rsi_period < 1 {
+            return fail(BotError::ConfigurationError("Mean reversion periods must be at least 2 (bands) and 1 (RSI)".to_string()));
+        }
+        if !self.bb_multiplier.is_finite() || self.bb_multiplier <= 0.0 {
+            return fail(BotError::ConfigurationError(format!("Band multiplier {} must be positive", self.bb_multiplier)));
+        }
+        if !(self.oversold > 0.0 && self.oversold < 50.0) {
+            return fail(BotError::ConfigurationError(format!("Oversold level {} outside (0, 50)", self.oversold)));
+        }
+        if self.exit_percent_b <= self.entry_percent_b {
+            return fail(BotError::ConfigurationError(format!(
+                "Exit %B {} must be above entry %B {}", self.exit_percent_b, self.entry_percent_b)));
+        }
+        unit(())
+    }
+}
+
+/// Base quantities below this count as flat.
+const DUST: f64 = 1e-9;
+
+/// An accepted entry or exit whose fills are still being booked.
+#[derive(Debug, Clone)]
+struct WorkingOrder {
+    id: String,
+    side: OrderSide,
+    remaining: f64,
+}
+
+pub struct MeanReversionStrategy {
+    config: MeanReversionConfig,
+    bands: TimeframeIndicator<BollingerBands>,
+    rsi: TimeframeIndicator<RSI>,
+    last_close: Option<f64>,
+    fresh_bar: bool, // A subscribed bar closed since the last evaluation
+    position: f64,   // Base quantity filled and not yet sold
+    awaiting: Option<OrderSide>, // Signal emitted this tick, order not seen yet
+    working: Option<WorkingOrder>,
+    regime: Option<MarketRegime>,
+}
+
+impl MeanReversionStrategy {
+    pub fn new(config: MeanReversionConfig) -> MResult<Self> {
+        config.validate()?;
+        unit(MeanReversionStrategy {
+            bands: TimeframeIndicator::new(BollingerBands::new(config.bb_period, config.bb_multiplier), Some(config.timeframe)),
+            rsi: TimeframeIndicator::new(RSI::with_smoothing(config.rsi_period, config.rsi_smoothing), Some(config.timeframe)),
+            config,
+            last_close: None,
+            fresh_bar: false,
+            position: 0.0,
+            awaiting: None,
+            working: None,
+            regime: None,
+        })
+    }
+
+    /// True while an entry has filled and not been sold.
+    pub fn in_position(&self) -> bool {
+        self.position > DUST
+    }
+
+    fn trending(&self) -> bool {
+        matches!(self.regime, Some(MarketRegime::Bullish) | Some(MarketRegime::Bearish))
+    }
+}
+
+impl Strategy for MeanReversionStrategy {
+    fn observe_regime(&mut self, reading: &RegimeReading) {
+        self.regime = Some(reading.regime);
+    }
+
+    fn timeframes(&self) -> Vec<Timeframe> {
+        vec![self.config.timeframe]
+    }
+
+    fn observe_candle(&mut self, candle: &Candle) {
+        if self.bands.on_candle(candle) | self.rsi.on_candle(candle) {
+            self.last_close = Some(candle.close);
+            self.fresh_bar = true;
+        }
+    }
+
+    fn warmup_bars(&self) -> usize {
+        self.config.bb_period.max(self.config.rsi_period + 1)
+    }
+
+    /// Rebuilds the indicators from history; the last historical bar is not
+    /// treated as fresh and position state is left alone.
+    fn warm_up(&mut self, candles: &[Candle]) {
+        self.bands.reset();
+        self.rsi.reset();
+        candles.iter().for_each(|c| self.observe_candle(c));
+        self.fresh_bar = false;
+    }
+
+    /// Adopts the order placed for this tick's signal; a refusal leaves the
+    /// position as it was.
+    fn observe_order(&mut self, order: &Order) {
+        let dead = matches!(order.status, OrderStatus::Rejected | OrderStatus::Canceled);
+        if let Some(working) = &self.working {
+            if working.id == order.id && dead {
+                log_info(&format!("[MEANREV] {:?} {} {:?} with {:.6} unfilled", working.side, order.id, order.status, working.remaining));
+                self.working = None;
+            }
+            return;
+        }
+        if self.awaiting.as_ref() != Some(&order.side) {
+            return;
+        }
+        self.awaiting = None;
+        if dead {
+            log_info(&format!("[MEANREV] {:?} not placed: {}", order.side, order.reason.as_deref().unwrap_or("-")));
+            return;
+        }
+        self.working = Some(WorkingOrder { id: order.id.clone(), side: order.side.clone(), remaining: order.quantity });
+    }
+
+    fn observe_fill(&mut self, fill: &Fill) {
+        let working = match self.working.as_mut() {
+            Some(w) if w.id == fill.order_id => w,
+            _ => return,
+        };
+        match fill.side {
+            OrderSide::Buy => self.position += fill.quantity,
+            OrderSide::Sell => self.position = (self.position - fill.quantity).max(0.0),
+        }
+        working.remaining -= fill.quantity;
+        if working.remaining <= DUST {
+            self.working = None;
+        }
+    }
+
+    fn status_line(&self) -> Option<String> {
+        let working = match &self.working {
+            Some(w) => format!(" | {:?} {} filling ({:.6} left)", w.side, w.id, w.remaining),
+            None => String::new(),
+        };
+        Some(format!("Mean reversion | position {:.6}{}", self.position, working))
+    }
+
+    /// Only the position is kept; the indicators are rebuilt by the warm-up.
+    fn snapshot(&self) -> Option<JsonValue> {
+        let working = match &self.working {
+            Some(w) => JsonValue::object()
+                .with("id", w.id.as_str())
+                .with("side", if w.side == OrderSide::Buy { "buy" } else { "sell" })
+                .with("remaining", w.remaining),
+            None => JsonValue::Null,
+        };
+        Some(JsonValue::object()
+            .with("kind", "mean_reversion")
+            .with("position", self.position)
+            .with("working", working))
+    }
+
+    fn restore(&mut self, state: &JsonValue) -> MResult<()> {
+        if state.get("kind").and_then(|v| v.as_str()) != Some("mean_reversion") {
+            return fail(snapshot::invalid("not a mean_reversion snapshot"));
+        }
+        let position = read_optional_f64(state, "position")?
+            .filter(|p| *p >= 0.0)
+            .ok_or_else(|| snapshot::invalid("'position' must be a non-negative number"))?;
+        let working = match state.get("working") {
+            Some(JsonValue::Null) => None,
+            Some(w) => {
+                let id = w.get("id").and_then(|v| v.as_str()).ok_or_else(|| snapshot::invalid("working order without 'id'"))?;
+                let side = match w.get("side").and_then(|v| v.as_str()) {
+                    Some("buy") => OrderSide::Buy,
+                    Some("sell") => OrderSide::Sell,
+                    _ => return fail(snapshot::invalid("working order 'side' must be buy or sell")),
+                };
+                let remaining = read_optional_f64(w, "remaining")?
+                    .ok_or_else(|| snapshot::invalid("working order without 'remaining'"))?;
+                Some(WorkingOrder { id: id.to_string(), side, remaining })
+            }
+            None => return fail(snapshot::invalid("missing 'working'")),
+        };
+        self.position = position;
+        self.working = working;
+        self.awaiting = None;
+        unit(())
+    }
+
+    fn process_tick(&mut self, ticker: &Ticker) -> MResult<Option<Signal>> {
+        // A signal that did not turn into an order by now never will
+        self.awaiting = None;
+
+        // Evaluate once per closed bar, and not while an order is still filling
+        if !std::mem::replace(&mut self.fresh_bar, false) || self.working.is_some() {
+            return unit(None);
+        }
+        let (bands, rsi, close) = match (self.bands.inner().output(), self.rsi.value(), self.last_close) {
+            (Some(b), Some(r), Some(c)) => (b, r, c),
+            _ => return unit(None),
+        };
+        let c = &self.config;
+        let trending = self.trending();
+
+        let context = SignalContext::new()
+            .indicator("close", close)
+            .indicator("bb_upper", bands.upper)
+            .indicator("bb_middle", bands.middle)
+            .indicator("bb_lower", bands.lower)
+            .indicator("percent_b", bands.percent_b)
+            .indicator("rsi", rsi);
+
+        if self.in_position() {
+            let reverted = bands.percent_b >= c.exit_percent_b;
+            let context = context.check("exit_reverted", format!("percent_b >= {}", c.exit_percent_b), bands.percent_b, reverted);
+            if !reverted {
+                return unit(None);
+            }
+            self.awaiting = Some(OrderSide::Sell);
+            return unit(Some(Signal {
+                symbol: ticker.symbol.clone(),
+                side: OrderSide::Sell,
+                strength: 1.0,
+                quantity: Some(self.position), // Close exactly what filled
+                regime: self.regime.unwrap_or(MarketRegime::Sideways),
+                timestamp: ticker.timestamp,
+                reason: format!("Reverted to mean: close {:.2} vs middle band {:.2} (%B {:.2})", close, bands.middle, bands.percent_b),
+                components: vec![StrengthComponent { name: "exit", value: bands.percent_b, score: 1.0, weight: 1.0 }],
+                context,
+            }));
+        }
+
+        let touched = bands.percent_b <= c.entry_percent_b;
+        let oversold = rsi <= c.oversold;
+        let context = context
+            .check("entry_lower_band", format!("percent_b <= {}", c.entry_percent_b), bands.percent_b, touched)
+            .check("entry_rsi_oversold", format!("rsi <= {}", c.oversold), rsi, oversold)
+            .check("entry_not_trending", "regime not Bullish/Bearish (1 = trending)", if trending { 1.0 } else { 0.0 }, !(c.trend_filter && trending));
+        if !(touched && oversold) || (c.trend_filter && trending) {
+            return unit(None);
+        }
+
+        // Strength: band penetration 0.4, RSI depth 0.4, regime 0.2
+        let regime_score = match self.regime {
+            Some(MarketRegime::Sideways) => 1.0,
+            None => 0.5,
+            Some(MarketRegime::Volatile) => 0.25,
+            Some(_) => 0.0, // Trending, only reachable with the filter off
+        };
+        let components = vec![
+            // %B at the band scores 0.5, a quarter band-width below it scores 1
+            StrengthComponent { name: "band", value: bands.percent_b, score: clamp01(0.5 + (c.entry_percent_b - bands.percent_b) * 2.0), weight: 0.4 },
+            // RSI at the oversold level scores 0.5, 10 points deeper scores 1
+            StrengthComponent { name: "rsi", value: rsi, score: clamp01(0.5 + (c.oversold - rsi) / 20.0), weight: 0.4 },
+            StrengthComponent { name: "regime", value: regime_score, score: regime_score, weight: 0.2 },
+        ];
+        let strength = weighted_strength(&components);
+
+        self.awaiting = Some(OrderSide::Buy);
+        unit(Some(Signal {
+            symbol: ticker.symbol.clone(),
+            side: OrderSide::Buy,
+            strength,
+            quantity: None,
+            regime: self.regime.unwrap_or(MarketRegime::Sideways),
+            timestamp: ticker.timestamp,
+            reason: format!("Lower band touch: close {:.2} <= {:.2} (%B {:.2}) + RSI {:.1} oversold", close, bands.lower, bands.percent_b, rsi),
+            components,
+            context,
+        }))
+    }
+}
+
+// =================================================================================
+// UNIT TESTS
+// =================================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::{OrderType, OrderRequest};
+
+    fn bar(i: u64, close: f64) -> Candle {
+        Candle {
+            symbol: "TEST".to_string(),
+            timeframe: Timeframe::M5,
+            open_time: i * 300,
+            open: close,
+            high: close + 0.2,
+            low: close - 0.2,
+            close,
+            volume: 100.0,
+            trade_count: 10,
+        }
+    }
+
+    fn ticker(i: u64, price: f64) -> Ticker {
+        Ticker {
+            symbol: "TEST".to_string(),
+            price,
+            volume_24h: 0.0,
+            volume_1h: 0.0,
+            open: price,
+            high: price,
+            low: price,
+            bid: price,
+            ask: price,
+            timestamp: i,
+        }
+    }
+
+    fn regime(regime: MarketRegime) -> RegimeReading {
+        RegimeReading { regime, adx: 0.0, plus_di: 0.0, minus_di: 0.0, slope_pct: 0.0, volatility: 0.0, volatility_ratio: 1.0, timestamp: 0 }
+    }
+
+    /// Ranging market, then a sharp five-bar sell-off, then a recovery.
+    fn series() -> Vec<f64> {
+        let mut closes: Vec<f64> = (0..30).map(|i| 100.0 + if i % 2 == 0 { 0.5 } else { -0.5 }).collect();
+        closes.extend([98.0, 96.0, 94.0, 92.0, 90.0]);
+        closes.extend([92.0, 95.0, 98.0, 100.0, 101.0]);
+        closes
+    }
+
+    /// Feeds each close as a bar then a tick. Each signal's order is reported
+    /// back as `execute` decides: its status and the quantity filled. A
+    /// partial fill is followed by a cancel. Returns (bar index, signal).
+    fn run_with<F>(strategy: &mut MeanReversionStrategy, closes: &[f64], mut execute: F) -> Vec<(usize, Signal)>
+    where
+        F: FnMut(&Signal) -> (OrderStatus, f64),
+    {
+        let mut signals = Vec::new();
+        for (i, close) in closes.iter().enumerate() {
+            strategy.observe_candle(&bar(i as u64, *close));
+            if let Some(signal) = strategy.process_tick(&ticker(i as u64, *close)).unwrap() {
+                let (status, filled) = execute(&signal);
+                let mut order = Order::rejected(&OrderRequest {
+                    client_id: format!("sig-{}", i),
+                    symbol: "TEST".to_string(),
+                    side: signal.side.clone(),
+                    order_type: OrderType::Limit,
+                    quantity: signal.quantity.unwrap_or(1.0),
+                    price: Some(*close),
+                }, &BotError::ExchangeError("-".to_string()), i as u64 * 1000);
+                order.id = format!("o{}", i);
+                order.status = status;
+                order.filled_quantity = filled;
+                strategy.observe_order(&order);
+                if filled > 0.0 {
+                    strategy.observe_fill(&Fill {
+                        id: i as u64,
+                        order_id: order.id.clone(),
+                        client_id: order.client_id.clone(),
+                        symbol: "TEST".to_string(),
+                        side: signal.side.clone(),
+                        price: *close,
+                        quantity: filled,
+                        fee: 0.0,
+                        timestamp_ms: i as u64 * 1000,
+                    });
+                }
+                if order.status == OrderStatus::PartiallyFilled {
+                    order.status = OrderStatus::Canceled;
+                    strategy.observe_order(&order);
+                }
+                signals.push((i, signal));
+            }
+        }
+        signals
+    }
+
+    /// Every order fills in full; returns (bar index, side) per signal.
+    fn run(strategy: &mut MeanReversionStrategy, closes: &[f64]) -> Vec<(usize, OrderSide)> {
+        run_with(strategy, closes, |s| (OrderStatus::Filled, s.quantity.unwrap_or(1.0)))
+            .into_iter()
+            .map(|(i, s)| (i, s.side))
+            .collect()
+    }
+
+    #[test]
+    fn test_enters_on_oversold_band_touch_and_exits_at_mean() {
+        let mut strategy = MeanReversionStrategy::new(MeanReversionConfig::standard()).unwrap();
+        strategy.observe_regime(&regime(MarketRegime::Sideways));
+        let signals = run(&mut strategy, &series());
+
+        // One entry during the sell-off, one exit during the recovery, nothing else
+        assert_eq!(signals.len(), 2, "{:?}", signals);
+        assert_eq!(signals[0].1, OrderSide::Buy);
+        assert!((30..35).contains(&signals[0].0));
+        assert_eq!(signals[1].1, OrderSide::Sell);
+        assert!(signals[1].0 >= 35);
+        assert!(!strategy.in_position());
+    }
+
+    #[test]
+    fn test_position_follows_fills_not_signals() {
+        // A refused entry never opens a position, so there is nothing to exit
+        let mut strategy = MeanReversionStrategy::new(MeanReversionConfig::standard()).unwrap();
+        strategy.observe_regime(&regime(MarketRegime::Sideways));
+        let signals = run_with(&mut strategy, &series(), |_| (OrderStatus::Rejected, 0.0));
+        assert!(!signals.is_empty());
+        assert!(signals.iter().all(|(_, s)| s.side == OrderSide::Buy));
+        assert!(!strategy.in_position());
+
+        // A partly filled, then canceled entry exits exactly what filled
+        let mut strategy = MeanReversionStrategy::new(MeanReversionConfig::standard()).unwrap();
+        strategy.observe_regime(&regime(MarketRegime::Sideways));
+        let signals = run_with(&mut strategy, &series(), |s| match s.side {
+            OrderSide::Buy => (OrderStatus::PartiallyFilled, 0.4),
+            OrderSide::Sell => (OrderStatus::Filled, s.quantity.unwrap()),
+        });
+        let sides: Vec<OrderSide> = signals.iter().map(|(_, s)| s.side.clone()).collect();
+        assert_eq!(sides, vec![OrderSide::Buy, OrderSide::Sell]);
+        assert_eq!(signals[1].1.quantity, Some(0.4));
+        assert!(!strategy.in_position());
+    }
+
+    #[test]
+    fn test_position_survives_snapshot() {
+        let mut strategy = MeanReversionStrategy::new(MeanReversionConfig::standard()).unwrap();
+        strategy.observe_regime(&regime(MarketRegime::Sideways));
+        // Stop right after the sell-off, holding the entry
+        run(&mut strategy, &series()[..35]);
+        assert_eq!(strategy.position, 1.0);
+
+        let saved = JsonValue::parse(&strategy.snapshot().unwrap().to_string()).unwrap();
+        let mut restarted = MeanReversionStrategy::new(MeanReversionConfig::standard()).unwrap();
+        restarted.restore(&saved).unwrap();
+        assert!(restarted.in_position());
+        assert_eq!(restarted.position, 1.0);
+        assert!(restarted.restore(&JsonValue::object().with("kind", "dca")).is_err());
+    }
+
+    #[test]
+    fn test_trend_filter_blocks_entries() {
+        let mut strategy = MeanReversionStrategy::new(MeanReversionConfig::standard()).unwrap();
+        strategy.observe_regime(&regime(MarketRegime::Bearish));
+        assert!(run(&mut strategy, &series()).is_empty());
+
+        let unfiltered = MeanReversionConfig { trend_filter: false, ..MeanReversionConfig::standard() };
+        let mut strategy = MeanReversionStrategy::new(unfiltered).unwrap();
+        strategy.observe_regime(&regime(MarketRegime::Bearish));
+        assert_eq!(run(&mut strategy, &series()).len(), 2);
+
+        // An exit level below the entry level is rejected
+        let bad = MeanReversionConfig { exit_percent_b: -0.5, ..MeanReversionConfig::standard() };
+        assert!(MeanReversionStrategy::new(bad).is_err());
+    }
+}
@@ -0,0 +1,381 @@
+// =================================================================================
+// MODULE: Order Book Analytics
+// DESCRIPTION:
+// Turns depth snapshots into pressure metrics and trades on them when they
+// agree with the tape.
+//
+// Key components:
+// - BookMetrics: top-N depth imbalance, microprice and spread of one snapshot
+// - BookTracker: rolling window of snapshots for microprice drift and spread
+//   relative to its recent mean
+// - OrderBookImbalanceStrategy: signals when a volume spike and aggressive
+//   flow on one side are confirmed by resting depth and microprice drift
+// =================================================================================
+
+use crate::monad::{MResult, unit, fail, BotError};
+use crate::exchange::{OrderBook, OrderSide, Ticker};
+use crate::volume::{VolumeSpike, SpikeSeverity};
+use crate::flow::FlowWindow;
+use crate::regime::RegimeReading;
+use crate::trader::{Strategy, Signal, SignalContext, StrengthComponent, MarketRegime, clamp01, weighted_strength};
+use std::collections::VecDeque;
+
+/// Pressure metrics for one order book snapshot.
+#[derive(Debug, Clone)]
+pub struct BookMetrics {
+    pub symbol: String,
+    pub best_bid: f64,
+    pub best_ask: f64,
+    pub mid: f64,
+    pub spread_bps: f64,
+    pub microprice: f64, // Touch prices weighted by the opposite side's size
+    pub bid_depth: f64,  // Quantity summed over the top N bid levels
+    pub ask_depth: f64,
+    pub imbalance: f64,  // (bid - ask) / (bid + ask) depth, in [-1, 1]
+    pub update_id: u64,
+}
+
+impl BookMetrics {
+    /// Fails on an empty side or a crossed book, which no metric can be read from.
+    pub fn from_book(book: &OrderBook, depth: usize) -> MResult<Self> {
+        let (bid, ask) = match (book.bids.first(), book.asks.first()) {
+            (Some(b), Some(a)) => (b, a),
+            _ => return fail(BotError::ExchangeError(format!("Order book for {} has an empty side", book.symbol))),
+        };
+        if bid.price >= ask.price {
+            return fail(BotError::ExchangeError(format!("Order book for {} is crossed ({} >= {})", book.symbol, bid.price, ask.price)));
+        }
+
+        let depth = depth.max(1);
+        let bid_depth: f64 = book.bids.iter().take(depth).map(|l| l.quantity).sum();
+        let ask_depth: f64 = book.asks.iter().take(depth).map(|l| l.quantity).sum();
+        let total = bid_depth + ask_depth;
+        let mid = (bid.price + ask.price) / 2.0;
+        let touch = bid.quantity + ask.quantity;
+
+        unit(BookMetrics {
+            symbol: book.symbol.clone(),
+            best_bid: bid.price,
+            best_ask: ask.price,
+            mid,
+            spread_bps: (ask.price - bid.price) / mid * 10_000.0,
+            // A heavy bid pulls the fair price toward the ask, and vice versa
+            microprice: if touch > 0.0 { (bid.price * ask.quantity + ask.price * bid.quantity) / touch } else { mid },
+            bid_depth,
+            ask_depth,
+            imbalance: if total > 0.0 { (bid_depth - ask_depth) / total } else { 0.0 },
+            update_id: book.last_update_id,
+        })
+    }
+}
+
+/// Rolling window of book snapshots, newest last.
+pub struct BookTracker {
+    window: usize,
+    history: VecDeque<BookMetrics>,
+}
+
+impl BookTracker {
+    pub fn new(window: usize) -> Self {
+        let window = window.max(2);
+        BookTracker { window, history: VecDeque::with_capacity(window) }
+    }
+
+    pub fn push(&mut self, metrics: BookMetrics) {
+        if self.history.len() >= self.window {
+            self.history.pop_front();
+        }
+        self.history.push_back(metrics);
+    }
+
+    pub fn latest(&self) -> Option<&BookMetrics> {
+        self.history.back()
+    }
+
+    /// Microprice change across the window, in bps. `None` until two snapshots.
+    pub fn microprice_drift_bps(&self) -> Option<f64> {
+        if self.history.len() < 2 {
+            return None;
+        }
+        let (oldest, newest) = (self.history.front()?, self.history.back()?);
+        Some((newest.microprice / oldest.microprice - 1.0) * 10_000.0)
+    }
+
+    /// Latest spread over the window's mean spread (1.0 = normal, >1 = widening).
+    pub fn spread_ratio(&self) -> Option<f64> {
+        let latest = self.history.back()?;
+        let mean = self.history.iter().map(|m| m.spread_bps).sum::<f64>() / self.history.len() as f64;
+        Some(if mean > 0.0 { latest.spread_bps / mean } else { 1.0 })
+    }
+}
+
+// --- Strategy ---
+
+#[derive(Debug, Clone)]
+pub struct BookImbalanceConfig {
+    pub depth: usize,           // Levels per side summed into the imbalance
+    pub window: usize,          // Snapshots kept for drift and spread baselines
+    pub min_imbalance: f64,     // |imbalance| required on the signal's side
+    pub min_drift_bps: f64,     // Microprice move across the window in the signal's direction
+    pub max_spread_ratio: f64,  // Spread vs its window mean; wider means liquidity is pulling out
+    pub min_flow_ratio: f64,    // Aggressor share of window volume on the signal's side
+    pub min_severity: SpikeSeverity,
+}
+
+impl BookImbalanceConfig {
+    /// Top 10 levels, 10 snapshots, 0.2 imbalance, 0.5 bps drift, spread at
+    /// most 1.5x normal, 55% aggressor share, Elevated volume.
+    pub fn standard() -> Self {
+        BookImbalanceConfig {
+            depth: 10,
+            window: 10,
+            min_imbalance: 0.2,
+            min_drift_bps: 0.5,
+            max_spread_ratio: 1.5,
+            min_flow_ratio: 0.55,
+            min_severity: SpikeSeverity::Elevated,
+        }
+    }
+}
+
+pub struct OrderBookImbalanceStrategy {
+    config: BookImbalanceConfig,
+    book: BookTracker,
+    fresh_book: bool, // A snapshot arrived since the last evaluation
+    latest_spike: Option<VolumeSpike>,
+    latest_flow: Option<FlowWindow>,
+    regime: Option<MarketRegime>,
+}
+
+impl OrderBookImbalanceStrategy {
+    pub fn new(config: BookImbalanceConfig) -> Self {
+        OrderBookImbalanceStrategy {
+            book: BookTracker::new(config.window),
+            config,
+            fresh_book: false,
+            latest_spike: None,
+            latest_flow: None,
+            regime: None,
+        }
+    }
+}
+
+impl Strategy for OrderBookImbalanceStrategy {
+    fn observe_volume(&mut self, spike: &VolumeSpike) {
+        self.latest_spike = Some(spike.clone());
+    }
+
+    fn observe_flow(&mut self, flow: &FlowWindow) {
+        self.latest_flow = Some(flow.clone());
+    }
+
+    fn observe_regime(&mut self, reading: &RegimeReading) {
+        self.regime = Some(reading.regime);
+    }
+
+    fn book_depth(&self) -> u32 {
+        self.config.depth as u32
+    }
+
+    fn status_line(&self) -> Option<String> {
+        let m = self.book.latest()?;
+        Some(format!(
+            "Book {} #{} | {:.2} / {:.2} | depth {:.3} / {:.3} | imbalance {:+.2}",
+            m.symbol, m.update_id, m.best_bid, m.best_ask, m.bid_depth, m.ask_depth, m.imbalance
+        ))
+    }
+
+    /// Unreadable (empty or crossed) books are skipped.
+    fn observe_book(&mut self, book: &OrderBook) {
+        if let Ok(metrics) = BookMetrics::from_book(book, self.config.depth) {
+            self.book.push(metrics);
+            self.fresh_book = true;
+        }
+    }
+
+    fn process_tick(&mut self, ticker: &Ticker) -> MResult<Option<Signal>> {
+        // Only a book taken this tick says anything about current pressure
+        if !std::mem::replace(&mut self.fresh_book, false) {
+            return unit(None);
+        }
+        let c = &self.config;
+        let (metrics, drift, spread_ratio) = match (self.book.latest(), self.book.microprice_drift_bps(), self.book.spread_ratio()) {
+            (Some(m), Some(d), Some(s)) => (m, d, s),
+            _ => return unit(None),
+        };
+        let flow = match &self.latest_flow {
+            Some(f) if f.total_volume() > 0.0 => f,
+            _ => return unit(None),
+        };
+        let spike = match &self.latest_spike {
+            Some(s) if s.timestamp == ticker.timestamp && s.severity >= c.min_severity => s,
+            _ => return unit(None),
+        };
+
+        let buy_ratio = flow.buy_ratio();
+        let spread_ok = spread_ratio <= c.max_spread_ratio;
+        let buy = (metrics.imbalance >= c.min_imbalance, drift >= c.min_drift_bps, buy_ratio >= c.min_flow_ratio);
+        let sell = (-metrics.imbalance >= c.min_imbalance, -drift >= c.min_drift_bps, 1.0 - buy_ratio >= c.min_flow_ratio);
+
+        let context = SignalContext::new()
+            .indicator("imbalance", metrics.imbalance)
+            .indicator("microprice", metrics.microprice)
+            .indicator("mid", metrics.mid)
+            .indicator("microprice_drift_bps", drift)
+            .indicator("spread_bps", metrics.spread_bps)
+            .indicator("spread_ratio", spread_ratio)
+            .indicator("buy_ratio", buy_ratio)
+            .indicator("volume_score", spike.score())
+            .indicator("price", ticker.price)
+            .check("volume_spike", format!("severity >= {}", c.min_severity), spike.score(), true)
+            .check("spread_stable", format!("spread_ratio <= {}", c.max_spread_ratio), spread_ratio, spread_ok)
+            .check("buy_book_imbalance", format!("imbalance >= {}", c.min_imbalance), metrics.imbalance, buy.0)
+            .check("buy_microprice_drift", format!("drift_bps >= {}", c.min_drift_bps), drift, buy.1)
+            .check("buy_aggressor_flow", format!("buy_ratio >= {}", c.min_flow_ratio), buy_ratio, buy.2)
+            .check("sell_book_imbalance", format!("imbalance <= -{}", c.min_imbalance), metrics.imbalance, sell.0)
+            .check("sell_microprice_drift", format!("drift_bps <= -{}", c.min_drift_bps), drift, sell.1)
+            .check("sell_aggressor_flow", format!("buy_ratio <= {}", 1.0 - c.min_flow_ratio), buy_ratio, sell.2);
+
+        let side = match (buy, sell) {
+            _ if !spread_ok => return unit(None), // Liquidity pulling out; the book can't be trusted
+            ((true, true, true), _) => OrderSide::Buy,
+            (_, (true, true, true)) => OrderSide::Sell,
+            _ => return unit(None),
+        };
+
+        // Strength: volume 0.3, depth imbalance 0.3, microprice drift 0.2, flow 0.2
+        let direction = if side == OrderSide::Buy { 1.0 } else { -1.0 };
+        let side_ratio = if side == OrderSide::Buy { buy_ratio } else { 1.0 - buy_ratio };
+        let components = vec![
+            StrengthComponent { name: "volume", value: spike.score(), score: clamp01(spike.score() / 6.0), weight: 0.3 },
+            StrengthComponent { name: "imbalance", value: metrics.imbalance, score: clamp01(direction * metrics.imbalance), weight: 0.3 },
+            StrengthComponent { name: "drift", value: drift, score: clamp01(direction * drift / (4.0 * c.min_drift_bps.max(0.1))), weight: 0.2 },
+            StrengthComponent { name: "flow", value: buy_ratio, score: clamp01((side_ratio - 0.5) * 2.0), weight: 0.2 },
+        ];
+        let strength = weighted_strength(&components);
+
+        unit(Some(Signal {
+            symbol: ticker.symbol.clone(),
+            reason: format!("Book {:?} pressure: imbalance {:+.2}, microprice {:+.1}bps, {:.0}% aggressor + Vol ({} z:{:.1})",
+                side, metrics.imbalance, drift, side_ratio * 100.0, spike.severity, spike.score()),
+            side,
+            strength,
+            quantity: None,
+            regime: self.regime.unwrap_or(MarketRegime::Sideways),
+            timestamp: ticker.timestamp,
+            components,
+            context,
+        }))
+    }
+}
+
+// =================================================================================
+// UNIT TESTS
+// =================================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::PriceLevel;
+
+    /// Five levels a side; `lean` > 0 thickens the bid and thins the ask.
+    fn book(mid: f64, half_spread: f64, lean: f64) -> OrderBook {
+        let levels = |sign: f64, size: f64| (0..5)
+            .map(|i| PriceLevel { price: mid + sign * (half_spread + i as f64 * 0.5), quantity: size })
+            .collect();
+        OrderBook {
+            symbol: "BTCUSDT".to_string(),
+            bids: levels(-1.0, 1.0 + lean),
+            asks: levels(1.0, 1.0 - lean),
+            last_update_id: 1,
+        }
+    }
+
+    fn ticker(timestamp: u64) -> Ticker {
+        Ticker {
+            symbol: "BTCUSDT".to_string(),
+            price: 100.0,
+            volume_24h: 0.0,
+            volume_1h: 0.0,
+            open: 100.0,
+            high: 100.0,
+            low: 100.0,
+            bid: 99.9,
+            ask: 100.1,
+            timestamp,
+        }
+    }
+
+    fn spike(timestamp: u64) -> VolumeSpike {
+        VolumeSpike {
+            symbol: "BTCUSDT".to_string(),
+            volume: 5000.0,
+            mean: 1000.0,
+            std_dev: 1000.0,
+            z_score: 4.0,
+            median: 1000.0,
+            mad: 500.0,
+            robust_score: 3.0,
+            relative_volume: 5.0,
+            seasonal: None,
+            severity: SpikeSeverity::High,
+            timestamp,
+        }
+    }
+
+    fn flow(buy_volume: f64, sell_volume: f64) -> FlowWindow {
+        FlowWindow {
+            window_ms: 60_000,
+            buy_volume,
+            sell_volume,
+            buy_notional: buy_volume * 100.0,
+            sell_notional: sell_volume * 100.0,
+            delta: buy_volume - sell_volume,
+            cumulative_volume_delta: buy_volume - sell_volume,
+            trade_count: 10,
+        }
+    }
+
+    #[test]
+    fn test_book_metrics() {
+        let metrics = BookMetrics::from_book(&book(100.0, 0.05, 0.5), 3).unwrap();
+        assert!((metrics.imbalance - 0.5).abs() < 1e-12); // (4.5 - 1.5) / 6
+        assert!((metrics.spread_bps - 10.0).abs() < 1e-9);
+        // Heavy bid: fair price sits toward the ask
+        assert!((metrics.microprice - (99.95 * 0.5 + 100.05 * 1.5) / 2.0).abs() < 1e-12);
+
+        assert!(BookMetrics::from_book(&book(100.0, -0.05, 0.0), 3).is_err()); // Crossed
+    }
+
+    #[test]
+    fn test_signals_need_book_confirmation() {
+        let mut strategy = OrderBookImbalanceStrategy::new(BookImbalanceConfig::standard());
+        let mut run = |t: u64, mid: f64, half_spread: f64, lean: f64, flow_window: FlowWindow| {
+            strategy.observe_book(&book(mid, half_spread, lean));
+            strategy.observe_flow(&flow_window);
+            strategy.observe_volume(&spike(t));
+            strategy.process_tick(&ticker(t)).unwrap()
+        };
+
+        // Building bid pressure with buyers lifting the offer
+        for t in 0..5 {
+            run(t, 100.0 + t as f64 * 0.01, 0.05, 0.1, flow(50.0, 50.0));
+        }
+        let signal = run(5, 100.1, 0.05, 0.5, flow(70.0, 30.0)).expect("buy pressure");
+        assert_eq!(signal.side, OrderSide::Buy);
+        assert!(signal.strength > 0.4 && signal.strength <= 1.0);
+
+        // Same tape, but the book leans the other way: no confirmation
+        assert!(run(6, 100.1, 0.05, -0.5, flow(70.0, 30.0)).is_none());
+
+        // Spread blowing out vetoes an otherwise valid signal
+        assert!(run(7, 100.3, 0.5, 0.5, flow(70.0, 30.0)).is_none());
+
+        // Mirror image: sellers hitting a thinning bid
+        for t in 8..18 {
+            run(t, 100.3 - (t - 8) as f64 * 0.03, 0.05, -0.1, flow(50.0, 50.0));
+        }
+        let signal = run(18, 99.9, 0.05, -0.5, flow(30.0, 70.0)).expect("sell pressure");
+        assert_eq!(signal.side, OrderSide::Sell);
+        assert!(strategy.status_line().unwrap().starts_with("Book BTCUSDT #1 | 99.85 / 99.95"));
+    }
+}
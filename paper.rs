@@ -0,0 +1,349 @@
+// =================================================================================
+// MODULE: Paper Exchange
+// DESCRIPTION:
+// An `ExchangeClient` that takes market data from a real (or simulated) client
+// but keeps orders, balances and fills in memory. Limit orders rest until the
+// market trades through them, so strategies that work resting orders (grids,
+// market making) can be exercised end to end without risking funds.
+//
+// Matching model:
+// - Market orders and marketable limit orders fill in full at the last price
+// - Resting limit orders fill in full at their limit once the last price
+//   reaches it (buy: last <= limit, sell: last >= limit)
+// - Fees are charged in the quote asset; funds are reserved while an order rests
+// =================================================================================
+
+use crate::monad::{MResult, unit, fail, BotError, log_info};
+use crate::exchange::{ExchangeClient, Ticker, OrderBook, Trade, Balance, OrderSide, OrderType, OrderStatus, OrderRequest, Order, Fill};
+use crate::candles::{Candle, Timeframe};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Fills kept for `fetch_fills`.
+const FILL_HISTORY: usize = 1000;
+
+fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64
+}
+
+/// Splits "BTC/USDT" or "BTCUSDT" into base and quote assets.
+pub fn split_symbol(symbol: &str) -> MResult<(String, String)> {
+    if let Some((base, quote)) = symbol.split_once('/') {
+        return unit((base.to_string(), quote.to_string()));
+    }
+    ["USDT", "USDC", "BUSD", "BTC", "ETH"].iter()
+        .find(|q| symbol.len() > q.len() && symbol.ends_with(*q))
+        .map(|q| (symbol[..symbol.len() - q.len()].to_string(), q.to_string()))
+        .ok_or_else(|| BotError::ExchangeError(format!("Cannot tell base and quote apart in '{}'", symbol)))
+}
+
+/// A resting order and the funds locked for it.
+struct Resting {
+    order: Order,
+    reserved: f64, // Quote for buys, base for sells
+}
+
+struct PaperState {
+    balances: HashMap<String, Balance>,
+    resting: Vec<Resting>,
+    fills: VecDeque<Fill>,
+    last_fill_id: u64,
+    next_order_id: u64,
+    last_prices: HashMap<String, f64>,
+}
+
+impl PaperState {
+    fn balance(&mut self, asset: &str) -> &mut Balance {
+        self.balances.entry(asset.to_string())
+            .or_insert_with(|| Balance { asset: asset.to_string(), free: 0.0, locked: 0.0 })
+    }
+
+    /// Moves `amount` of `asset` from free to locked.
+    fn reserve(&mut self, asset: &str, amount: f64) -> MResult<()> {
+        let balance = self.balance(asset);
+        if balance.free + 1e-9 < amount {
+            return fail(BotError::ExchangeError(format!("Insufficient {} balance: need {:.8}, free {:.8}", asset, amount, balance.free)));
+        }
+        balance.free -= amount;
+        balance.locked += amount;
+        unit(())
+    }
+
+    fn release(&mut self, asset: &str, amount: f64) {
+        let balance = self.balance(asset);
+        balance.locked -= amount;
+        balance.free += amount;
+    }
+
+    /// Settles a full execution of `order` at `price` against funds already
+    /// reserved for it, and records the fill.
+    fn settle(&mut self, order: &mut Order, reserved: f64, price: f64, fee_rate: f64) -> MResult<Fill> {
+        let (base, quote) = split_symbol(&order.symbol)?;
+        let quantity = order.remaining();
+        let notional = quantity * price;
+        let fee = notional * fee_rate;
+
+        match order.side {
+            OrderSide::Buy => {
+                self.balance(&quote).locked -= reserved;
+                self.balance(&quote).free += reserved - notional - fee;
+                self.balance(&base).free += quantity;
+            }
+            OrderSide::Sell => {
+                self.balance(&base).locked -= reserved;
+                self.balance(&base).free += reserved - quantity;
+                self.balance(&quote).free += notional - fee;
+            }
+        }
+
+        let now = now_ms();
+        order.filled_quantity = order.quantity;
+        order.avg_fill_price = price;
+        order.status = OrderStatus::Filled;
+        order.updated_ms = now;
+
+        self.last_fill_id += 1;
+        let fill = Fill {
+            id: self.last_fill_id,
+            order_id: order.id.clone(),
+            client_id: order.client_id.clone(),
+            symbol: order.symbol.clone(),
+            side: order.side.clone(),
+            price,
+            quantity,
+            fee,
+            timestamp_ms: now,
+        };
+        if self.fills.len() >= FILL_HISTORY {
+            self.fills.pop_front();
+        }
+        self.fills.push_back(fill.clone());
+        unit(fill)
+    }
+}
+
+pub struct PaperExchange {
+    market: Arc<dyn ExchangeClient>,
+    fee_rate: f64,
+    state: Mutex<PaperState>,
+}
+
+impl PaperExchange {
+    /// `fee_rate` is a fraction of notional, e.g. 0.001 for 10 bps.
+    pub fn new(market: Arc<dyn ExchangeClient>, fee_rate: f64) -> Self {
+        PaperExchange {
+            market,
+            fee_rate: fee_rate.max(0.0),
+            state: Mutex::new(PaperState {
+                balances: HashMap::new(),
+                resting: Vec::new(),
+                fills: VecDeque::new(),
+                last_fill_id: 0,
+                next_order_id: 1,
+                last_prices: HashMap::new(),
+            }),
+        }
+    }
+
+    /// Credits a starting balance.
+    pub fn with_balance(self, asset: &str, amount: f64) -> Self {
+        self.state.lock().unwrap().balance(asset).free += amount;
+        self
+    }
+
+    /// Records the last price for `symbol` and fills every resting order it
+    /// trades through. Called on each ticker fetch.
+    pub fn on_price(&self, symbol: &str, price: f64) -> Vec<Fill> {
+        let mut state = self.state.lock().unwrap();
+        state.last_prices.insert(symbol.to_string(), price);
+
+        let (touched, resting): (Vec<Resting>, Vec<Resting>) = state.resting.drain(..).partition(|r| {
+            r.order.symbol == symbol && match (&r.order.side, r.order.price) {
+                (OrderSide::Buy, Some(limit)) => price <= limit,
+                (OrderSide::Sell, Some(limit)) => price >= limit,
+                _ => false,
+            }
+        });
+        state.resting = resting;
+
+        let mut fills = Vec::new();
+        for mut r in touched {
+            let limit = r.order.price.unwrap_or(price);
+            match state.settle(&mut r.order, r.reserved, limit, self.fee_rate) {
+                Ok(fill) => fills.push(fill),
+                Err(e) => log_info(&format!("[PAPER] Could not settle {}: {:?}", r.order.id, e)),
+            }
+        }
+        fills
+    }
+
+    fn submit(&self, request: &OrderRequest) -> MResult<Order> {
+        if !request.quantity.is_finite() || request.quantity <= 0.0 {
+            return fail(BotError::StrategyError("Order quantity must be positive".to_string()));
+        }
+        let limit = match (&request.order_type, request.price) {
+            (OrderType::Limit, Some(p)) if p > 0.0 => Some(p),
+            (OrderType::Limit, _) => return fail(BotError::StrategyError("Limit order requires valid price".to_string())),
+            (OrderType::Market, _) => None,
+            (other, _) => return fail(BotError::ExchangeError(format!("{:?} orders are not supported by the paper exchange", other))),
+        };
+        let (base, quote) = split_symbol(&request.symbol)?;
+
+        let mut state = self.state.lock().unwrap();
+        let last = state.last_prices.get(&request.symbol).copied();
+        let marketable = match (limit, last, &request.side) {
+            (None, Some(_), _) => true,
+            (None, None, _) => return fail(BotError::ExchangeError(format!("No market price for {} yet", request.symbol))),
+            (Some(l), Some(p), OrderSide::Buy) => p <= l,
+            (Some(l), Some(p), OrderSide::Sell) => p >= l,
+            (Some(_), None, _) => false,
+        };
+
+        // Buys lock enough quote for the worst price they can fill at, plus fees
+        let reserve_price = limit.or(last).unwrap_or(0.0);
+        let (asset, reserved) = match request.side {
+            OrderSide::Buy => (quote, request.quantity * reserve_price * (1.0 + self.fee_rate)),
+            OrderSide::Sell => (base, request.quantity),
+        };
+        state.reserve(&asset, reserved)?;
+
+        let mut order = Order {
+            id: format!("PAPER-{}", state.next_order_id),
+            client_id: request.client_id.clone(),
+            symbol: request.symbol.clone(),
+            side: request.side.clone(),
+            order_type: request.order_type.clone(),
+            price: limit,
+            quantity: request.quantity,
+            filled_quantity: 0.0,
+            avg_fill_price: 0.0,
+            status: OrderStatus::New,
+            reason: None,
+            updated_ms: now_ms(),
+        };
+        state.next_order_id += 1;
+
+        match last.filter(|_| marketable) {
+            Some(price) => { state.settle(&mut order, reserved, price, self.fee_rate)?; }
+            None => state.resting.push(Resting { order: order.clone(), reserved }),
+        }
+        unit(order)
+    }
+}
+
+impl ExchangeClient for PaperExchange {
+    fn fetch_ticker(&self, symbol: &str) -> MResult<Ticker> {
+        self.market.fetch_ticker(symbol)
+            .inspect(|ticker| { self.on_price(symbol, ticker.price); })
+    }
+
+    fn fetch_order_book(&self, symbol: &str, depth: u32) -> MResult<OrderBook> {
+        self.market.fetch_order_book(symbol, depth)
+    }
+
+    fn fetch_trades(&self, symbol: &str, since_id: Option<u64>, limit: u32) -> MResult<Vec<Trade>> {
+        self.market.fetch_trades(symbol, since_id, limit)
+    }
+
+    fn fetch_klines(&self, symbol: &str, interval: Timeframe, limit: u32) -> MResult<Vec<Candle>> {
+        self.market.fetch_klines(symbol, interval, limit)
+    }
+
+    fn fetch_balance(&self, asset: &str) -> MResult<Balance> {
+        unit(self.state.lock().unwrap().balance(asset).clone())
+    }
+
+    fn place_order(&self, request: &OrderRequest) -> MResult<Order> {
+        self.submit(request)
+            .inspect(|order| log_info(&format!("[PAPER] {} {:?} {} {} @ {:?} -> {:?}", order.id, order.side, order.quantity, order.symbol, order.price, order.status)))
+    }
+
+    fn cancel_order(&self, symbol: &str, order_id: &str) -> MResult<Order> {
+        let mut state = self.state.lock().unwrap();
+        let index = state.resting.iter().position(|r| r.order.id == order_id && r.order.symbol == symbol)
+            .ok_or_else(|| BotError::ExchangeError(format!("Unknown order {}", order_id)))?;
+        let Resting { mut order, reserved } = state.resting.remove(index);
+
+        let (base, quote) = split_symbol(symbol)?;
+        let asset = if order.side == OrderSide::Buy { quote } else { base };
+        state.release(&asset, reserved);
+        order.status = OrderStatus::Canceled;
+        order.updated_ms = now_ms();
+        unit(order)
+    }
+
+    fn fetch_open_orders(&self, symbol: &str) -> MResult<Vec<Order>> {
+        let state = self.state.lock().unwrap();
+        unit(state.resting.iter().filter(|r| r.order.symbol == symbol).map(|r| r.order.clone()).collect())
+    }
+
+    fn fetch_fills(&self, symbol: &str, since_id: Option<u64>) -> MResult<Vec<Fill>> {
+        let state = self.state.lock().unwrap();
+        unit(state.fills.iter()
+            .filter(|f| f.symbol == symbol && since_id.is_none_or(|id| f.id > id))
+            .cloned()
+            .collect())
+    }
+
+    fn check_connectivity(&self) -> MResult<u64> {
+        self.market.check_connectivity()
+    }
+}
+
+// =================================================================================
+// UNIT TESTS
+// =================================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::BinanceClient;
+
+    fn limit(client_id: &str, side: OrderSide, quantity: f64, price: f64) -> OrderRequest {
+        OrderRequest {
+            client_id: client_id.to_string(),
+            symbol: "BTC/USDT".to_string(),
+            side,
+            order_type: OrderType::Limit,
+            quantity,
+            price: Some(price),
+        }
+    }
+
+    #[test]
+    fn test_resting_orders_fill_when_price_trades_through() {
+        let paper = PaperExchange::new(Arc::new(BinanceClient::new("k", "s")), 0.001)
+            .with_balance("USDT", 1_000.0);
+        paper.on_price("BTC/USDT", 100.0);
+
+        // Resting buy locks quote including fees
+        let order = paper.place_order(&limit("b1", OrderSide::Buy, 2.0, 95.0)).unwrap();
+        assert_eq!(order.status, OrderStatus::New);
+        let usdt = paper.fetch_balance("USDT").unwrap();
+        assert!((usdt.locked - 190.19).abs() < 1e-9);
+
+        assert!(paper.on_price("BTC/USDT", 96.0).is_empty());
+        let fills = paper.on_price("BTC/USDT", 94.5);
+        assert_eq!(fills.len(), 1);
+        assert_eq!((fills[0].client_id.as_str(), fills[0].price, fills[0].quantity), ("b1", 95.0, 2.0));
+        assert!((paper.fetch_balance("USDT").unwrap().free - (1_000.0 - 190.19)).abs() < 1e-9);
+        assert_eq!(paper.fetch_balance("BTC").unwrap().free, 2.0);
+
+        // Selling more than we hold is refused; a marketable sell fills at the last price
+        assert!(paper.place_order(&limit("s0", OrderSide::Sell, 3.0, 90.0)).is_err());
+        let sold = paper.place_order(&limit("s1", OrderSide::Sell, 1.0, 90.0)).unwrap();
+        assert_eq!((sold.status, sold.avg_fill_price), (OrderStatus::Filled, 94.5));
+
+        // Cancel returns the reservation
+        let resting = paper.place_order(&limit("s2", OrderSide::Sell, 1.0, 120.0)).unwrap();
+        assert_eq!(paper.fetch_balance("BTC").unwrap().locked, 1.0);
+        let canceled = paper.cancel_order("BTC/USDT", &resting.id).unwrap();
+        assert_eq!(canceled.status, OrderStatus::Canceled);
+        assert_eq!(paper.fetch_balance("BTC").unwrap().free, 1.0);
+        assert!(paper.cancel_order("BTC/USDT", &resting.id).is_err());
+
+        let fills = paper.fetch_fills("BTC/USDT", Some(1)).unwrap();
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].client_id, "s1");
+    }
+}
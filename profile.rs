@@ -0,0 +1,334 @@
+// =================================================================================
+// MODULE: Volume Profile
+// DESCRIPTION:
+// Where volume traded, not just how much. Trades are binned by price level
+// over fixed sessions to derive the classic auction-market reference levels
+// used for breakout decisions.
+//
+// Key components:
+// - Price-binned volume histogram per session
+// - Point of Control (POC): the price level with the most volume
+// - Value Area High/Low: the range holding ~70% of session volume
+// - High/Low Volume Nodes (HVN/LVN): acceptance and rejection levels
+// =================================================================================
+
+use crate::exchange::Trade;
+use crate::monad::{MResult, unit, fail, BotError};
+use std::collections::BTreeMap;
+
+/// Share of session volume that defines the value area.
+const VALUE_AREA_SHARE: f64 = 0.70;
+
+/// Node thresholds relative to the mean non-empty bin volume.
+const HVN_FACTOR: f64 = 1.5;
+const LVN_FACTOR: f64 = 0.5;
+
+/// How wide each price bin is.
+#[derive(Debug, Clone, Copy)]
+pub enum BinSize {
+    Absolute(f64), // Fixed price increment
+    Relative(f64), // Fraction of the session's first traded price, e.g. 0.0005
+}
+
+impl BinSize {
+    /// `"0.05%"` bins relative to the session's first price, `"0.5"` in fixed
+    /// price steps.
+    pub fn parse(value: &str) -> MResult<BinSize> {
+        let value = value.trim();
+        let (number, relative) = match value.strip_suffix('%') {
+            Some(pct) => (pct.trim(), true),
+            None => (value, false),
+        };
+        match number.parse::<f64>() {
+            Ok(size) if size.is_finite() && size > 0.0 => match relative {
+                true => unit(BinSize::Relative(size / 100.0)),
+                false => unit(BinSize::Absolute(size)),
+            },
+            _ => fail(BotError::ConfigurationError(format!(
+                "Profile bin size must be a positive price step or percentage like 0.05%, got '{}'", value))),
+        }
+    }
+}
+
+/// Price-binned volume for a single session.
+#[derive(Debug, Clone)]
+pub struct VolumeProfile {
+    pub session_start: u64, // Unix seconds
+    bin_size: f64,
+    bins: BTreeMap<i64, f64>,
+    total_volume: f64,
+}
+
+impl VolumeProfile {
+    pub fn new(session_start: u64, bin_size: f64) -> Self {
+        VolumeProfile {
+            session_start,
+            bin_size,
+            bins: BTreeMap::new(),
+            total_volume: 0.0,
+        }
+    }
+
+    pub fn add(&mut self, price: f64, volume: f64) {
+        let valid = |x: f64| x.is_finite() && x > 0.0;
+        if !valid(price) || !valid(volume) {
+            return;
+        }
+        let index = (price / self.bin_size).floor() as i64;
+        *self.bins.entry(index).or_insert(0.0) += volume;
+        self.total_volume += volume;
+    }
+
+    /// Centre price of a bin.
+    fn price_of(&self, index: i64) -> f64 {
+        (index as f64 + 0.5) * self.bin_size
+    }
+
+    pub fn total_volume(&self) -> f64 {
+        self.total_volume
+    }
+
+    pub fn bin_size(&self) -> f64 {
+        self.bin_size
+    }
+
+    /// `(price, volume)` per non-empty bin, lowest price first.
+    pub fn levels(&self) -> Vec<(f64, f64)> {
+        self.bins.iter().map(|(i, v)| (self.price_of(*i), *v)).collect()
+    }
+
+    fn poc_index(&self) -> Option<i64> {
+        // Ties resolve to the lower price for determinism
+        self.bins.iter()
+            .fold(None, |best: Option<(i64, f64)>, (i, v)| match best {
+                Some((_, bv)) if bv >= *v => best,
+                _ => Some((*i, *v)),
+            })
+            .map(|(i, _)| i)
+    }
+
+    pub fn point_of_control(&self) -> Option<f64> {
+        self.poc_index().map(|i| self.price_of(i))
+    }
+
+    /// Expands outward from the POC, always taking the heavier neighbouring
+    /// bin, until the value-area share of volume is covered.
+    /// Returns `(value_area_low, value_area_high)`.
+    pub fn value_area(&self) -> Option<(f64, f64)> {
+        let poc = self.poc_index()?;
+        let (min, max) = (*self.bins.keys().next()?, *self.bins.keys().next_back()?);
+        let volume_at = |i: i64| self.bins.get(&i).copied().unwrap_or(0.0);
+
+        let target = self.total_volume * VALUE_AREA_SHARE;
+        let (mut low, mut high) = (poc, poc);
+        let mut covered = volume_at(poc);
+
+        while covered < target && (low > min || high < max) {
+            let below = if low > min { volume_at(low - 1) } else { -1.0 };
+            let above = if high < max { volume_at(high + 1) } else { -1.0 };
+            if above >= below {
+                high += 1;
+                covered += above;
+            } else {
+                low -= 1;
+                covered += below;
+            }
+        }
+
+        Some((low as f64 * self.bin_size, (high + 1) as f64 * self.bin_size))
+    }
+
+    /// Local volume peaks well above the average bin.
+    pub fn high_volume_nodes(&self) -> Vec<f64> {
+        self.nodes(|v, mean, prev, next| v >= mean * HVN_FACTOR && v >= prev && v >= next)
+    }
+
+    /// Local volume troughs well below the average bin, inside the traded range.
+    pub fn low_volume_nodes(&self) -> Vec<f64> {
+        self.nodes(|v, mean, prev, next| v <= mean * LVN_FACTOR && v <= prev && v <= next)
+    }
+
+    /// Scans every bin between the lowest and highest traded price (empty bins
+    /// count as zero volume) and keeps those matching `is_node`.
+    fn nodes<F>(&self, is_node: F) -> Vec<f64>
+    where
+        F: Fn(f64, f64, f64, f64) -> bool,
+    {
+        let (min, max) = match (self.bins.keys().next(), self.bins.keys().next_back()) {
+            (Some(a), Some(b)) if b - a >= 2 => (*a, *b),
+            _ => return Vec::new(),
+        };
+        let span = (max - min + 1) as f64;
+        let mean = self.total_volume / span;
+        let volume_at = |i: i64| self.bins.get(&i).copied().unwrap_or(0.0);
+
+        ((min + 1)..max)
+            .filter(|i| is_node(volume_at(*i), mean, volume_at(i - 1), volume_at(i + 1)))
+            .map(|i| self.price_of(i))
+            .collect()
+    }
+
+    pub fn summary(&self) -> Option<ProfileSummary> {
+        let (value_area_low, value_area_high) = self.value_area()?;
+        Some(ProfileSummary {
+            point_of_control: self.point_of_control()?,
+            value_area_low,
+            value_area_high,
+            high_volume_nodes: self.high_volume_nodes(),
+            low_volume_nodes: self.low_volume_nodes(),
+            total_volume: self.total_volume,
+        })
+    }
+}
+
+/// Reference levels derived from a session profile.
+#[derive(Debug, Clone)]
+pub struct ProfileSummary {
+    pub point_of_control: f64,
+    pub value_area_low: f64,
+    pub value_area_high: f64,
+    pub high_volume_nodes: Vec<f64>,
+    pub low_volume_nodes: Vec<f64>,
+    pub total_volume: f64,
+}
+
+impl ProfileSummary {
+    /// Where `price` sits relative to the value area.
+    pub fn locate(&self, price: f64) -> ValueAreaPosition {
+        if price > self.value_area_high {
+            ValueAreaPosition::Above
+        } else if price < self.value_area_low {
+            ValueAreaPosition::Below
+        } else {
+            ValueAreaPosition::Inside
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ValueAreaPosition {
+    Above,
+    Inside,
+    Below,
+}
+
+/// Maintains the current session profile and keeps the last completed one.
+pub struct VolumeProfileTracker {
+    session_secs: u64,
+    bin_size: BinSize,
+    current: Option<VolumeProfile>,
+    previous: Option<VolumeProfile>,
+}
+
+impl VolumeProfileTracker {
+    /// `session_secs` of 86_400 gives UTC daily sessions, 3_600 hourly ones.
+    pub fn new(session_secs: u64, bin_size: BinSize) -> Self {
+        VolumeProfileTracker {
+            session_secs: session_secs.max(1),
+            bin_size,
+            current: None,
+            previous: None,
+        }
+    }
+
+    /// Adds trades (oldest first), rolling the session when a trade falls
+    /// past the current session boundary.
+    pub fn ingest(&mut self, trades: &[Trade]) {
+        for trade in trades {
+            self.add(trade.price, trade.quantity, trade.timestamp_ms / 1000);
+        }
+    }
+
+    /// Adds a single `(price, volume)` observation at `timestamp` (unix seconds).
+    pub fn add(&mut self, price: f64, volume: f64, timestamp: u64) {
+        let session_start = timestamp - timestamp % self.session_secs;
+        let stale = self.current.as_ref().is_none_or(|p| p.session_start != session_start);
+        if stale {
+            // Late prints from an already closed session are dropped
+            if self.current.as_ref().is_some_and(|p| session_start < p.session_start) {
+                return;
+            }
+            let bin_size = match self.bin_size {
+                BinSize::Absolute(size) => size,
+                BinSize::Relative(fraction) => (price * fraction).max(f64::EPSILON),
+            };
+            self.previous = self.current.take();
+            self.current = Some(VolumeProfile::new(session_start, bin_size));
+        }
+        if let Some(profile) = self.current.as_mut() {
+            profile.add(price, volume);
+        }
+    }
+
+    pub fn current(&self) -> Option<&VolumeProfile> {
+        self.current.as_ref()
+    }
+
+    pub fn previous(&self) -> Option<&VolumeProfile> {
+        self.previous.as_ref()
+    }
+
+    pub fn summary(&self) -> Option<ProfileSummary> {
+        self.current.as_ref().and_then(|p| p.summary())
+    }
+}
+
+// =================================================================================
+// UNIT TESTS
+// =================================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::OrderSide;
+
+    fn trade(id: u64, price: f64, quantity: f64, timestamp_ms: u64) -> Trade {
+        Trade { symbol: "MONUSDT".to_string(), id, price, quantity, aggressor: OrderSide::Buy, timestamp_ms }
+    }
+
+    #[test]
+    fn test_profile_levels_from_known_trades() {
+        let mut tracker = VolumeProfileTracker::new(3_600, BinSize::Absolute(1.0));
+        // Volume per 1.0-wide bin from 100 to 108 (300 total)
+        let volumes = [10.0, 20.0, 50.0, 5.0, 40.0, 100.0, 60.0, 5.0, 10.0];
+        let mut trades = Vec::new();
+        for (i, volume) in volumes.iter().enumerate() {
+            let price = 100.25 + i as f64;
+            // Two prints per bin at different prices inside it
+            trades.push(trade(2 * i as u64, price, volume / 2.0, 1_000));
+            trades.push(trade(2 * i as u64 + 1, price + 0.5, volume / 2.0, 2_000));
+        }
+        tracker.ingest(&trades);
+
+        let summary = tracker.summary().unwrap();
+        assert_eq!(summary.total_volume, 300.0);
+        assert_eq!(summary.point_of_control, 105.5);
+        // 70% of 300 is reached after adding 106, 104, 107 and 108 to the POC
+        assert_eq!((summary.value_area_low, summary.value_area_high), (104.0, 109.0));
+        assert_eq!(summary.high_volume_nodes, vec![102.5, 105.5]);
+        assert_eq!(summary.low_volume_nodes, vec![103.5, 107.5]);
+        assert_eq!(summary.locate(103.0), ValueAreaPosition::Below);
+        assert_eq!(summary.locate(106.0), ValueAreaPosition::Inside);
+    }
+
+    #[test]
+    fn test_sessions_roll_and_drop_late_prints() {
+        let mut tracker = VolumeProfileTracker::new(3_600, BinSize::Absolute(1.0));
+        tracker.ingest(&[trade(1, 100.5, 1.0, 10_000)]);
+        tracker.ingest(&[trade(2, 200.5, 2.0, 3_700_000)]);
+        assert_eq!(tracker.previous().unwrap().total_volume(), 1.0);
+        assert_eq!(tracker.current().unwrap().session_start, 3_600);
+
+        // A print from the closed session changes neither profile
+        tracker.ingest(&[trade(3, 100.5, 5.0, 20_000)]);
+        assert_eq!(tracker.previous().unwrap().total_volume(), 1.0);
+        assert_eq!(tracker.current().unwrap().total_volume(), 2.0);
+    }
+
+    #[test]
+    fn test_bin_size_parsing() {
+        assert!(matches!(BinSize::parse("0.05%").unwrap(), BinSize::Relative(f) if (f - 0.0005).abs() < 1e-12));
+        assert!(matches!(BinSize::parse(" 2.5 ").unwrap(), BinSize::Absolute(s) if s == 2.5));
+        assert!(BinSize::parse("-1").is_err());
+        assert!(BinSize::parse("wide").is_err());
+    }
+}
@@ -0,0 +1,231 @@
+// =================================================================================
+// MODULE: Market Regime Classification
+// DESCRIPTION:
+// Labels each closed bar with a `MarketRegime` so strategies and the risk
+// manager can adapt instead of assuming one market condition.
+//
+// Model (evaluated in this order):
+// - Volatile: short-window realized volatility is well above its longer-run level
+// - Bullish / Bearish: ADX shows a trend and the EMA slope gives its direction
+// - Sideways: everything else (weak ADX or a flat EMA)
+// =================================================================================
+
+use crate::candles::{Candle, Timeframe};
+use crate::trader::{Indicator, MultiIndicator, MarketRegime, ADX, EMA};
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone)]
+pub struct RegimeConfig {
+    pub timeframe: Timeframe,
+    pub adx_period: usize,
+    pub trend_adx: f64,          // ADX at or above this counts as trending
+    pub ema_period: usize,
+    pub slope_lookback: usize,   // Bars over which the EMA slope is measured
+    pub min_slope_pct: f64,      // Minimum |slope| in % per bar for a direction
+    pub volatility_period: usize,
+    pub baseline_period: usize,  // Longer window the short volatility is compared to
+    pub volatile_ratio: f64,     // Short / baseline volatility that flags Volatile
+}
+
+impl RegimeConfig {
+    /// ADX(14) ≥ 25 on 5m bars, EMA(20) slope over 5 bars, 20-bar volatility
+    /// at 1.5x its 100-bar level.
+    pub fn standard() -> Self {
+        RegimeConfig {
+            timeframe: Timeframe::M5,
+            adx_period: 14,
+            trend_adx: 25.0,
+            ema_period: 20,
+            slope_lookback: 5,
+            min_slope_pct: 0.01,
+            volatility_period: 20,
+            baseline_period: 100,
+            volatile_ratio: 1.5,
+        }
+    }
+}
+
+/// The classifier's view of one bar, with the inputs behind the label.
+#[derive(Debug, Clone)]
+pub struct RegimeReading {
+    pub regime: MarketRegime,
+    pub adx: f64,
+    pub plus_di: f64,
+    pub minus_di: f64,
+    pub slope_pct: f64,        // EMA change per bar, in percent
+    pub volatility: f64,       // Std-dev of log returns over the short window
+    pub volatility_ratio: f64, // Short / baseline volatility
+    pub timestamp: u64,        // Open time of the classified bar
+}
+
+pub struct RegimeClassifier {
+    config: RegimeConfig,
+    adx: ADX,
+    ema: EMA,
+    ema_history: VecDeque<f64>,
+    returns: VecDeque<f64>,
+    prev_close: Option<f64>,
+    latest: Option<RegimeReading>,
+}
+
+impl RegimeClassifier {
+    pub fn new(config: RegimeConfig) -> Self {
+        RegimeClassifier {
+            adx: ADX::new(config.adx_period),
+            ema: EMA::new(config.ema_period),
+            ema_history: VecDeque::with_capacity(config.slope_lookback + 1),
+            returns: VecDeque::with_capacity(config.baseline_period),
+            prev_close: None,
+            latest: None,
+            config,
+        }
+    }
+
+    pub fn timeframe(&self) -> Timeframe {
+        self.config.timeframe
+    }
+
+    /// Bars of history after which every input is ready.
+    pub fn warmup_bars(&self) -> usize {
+        let c = &self.config;
+        (2 * c.adx_period + 1)
+            .max(c.ema_period + c.slope_lookback)
+            .max(c.baseline_period + 1)
+    }
+
+    /// Feeds one closed bar of the configured timeframe. Returns the new
+    /// reading once ADX, slope and volatility are all available.
+    pub fn update(&mut self, candle: &Candle) -> Option<&RegimeReading> {
+        if candle.timeframe != self.config.timeframe {
+            return None;
+        }
+
+        self.adx.update_candle(candle);
+        self.ema.update_candle(candle);
+        if let Some(ema) = self.ema.value() {
+            if self.ema_history.len() > self.config.slope_lookback {
+                self.ema_history.pop_front();
+            }
+            self.ema_history.push_back(ema);
+        }
+        if let Some(prev) = self.prev_close.filter(|p| *p > 0.0 && candle.close > 0.0) {
+            if self.returns.len() >= self.config.baseline_period {
+                self.returns.pop_front();
+            }
+            self.returns.push_back((candle.close / prev).ln());
+        }
+        self.prev_close = Some(candle.close);
+
+        let reading = self.classify(candle.open_time)?;
+        self.latest = Some(reading);
+        self.latest.as_ref()
+    }
+
+    fn classify(&self, timestamp: u64) -> Option<RegimeReading> {
+        let c = &self.config;
+        let dmi = self.adx.output()?;
+        if self.ema_history.len() <= c.slope_lookback || self.returns.len() < c.volatility_period {
+            return None;
+        }
+
+        let (oldest, newest) = (*self.ema_history.front()?, *self.ema_history.back()?);
+        let slope_pct = if oldest > 0.0 { (newest / oldest - 1.0) * 100.0 / c.slope_lookback as f64 } else { 0.0 };
+
+        let recent: Vec<f64> = self.returns.iter().rev().take(c.volatility_period).copied().collect();
+        let volatility = std_dev(&recent);
+        let baseline = std_dev(&self.returns.iter().copied().collect::<Vec<_>>());
+        let volatility_ratio = if baseline > 0.0 { volatility / baseline } else { 1.0 };
+
+        let regime = if volatility_ratio >= c.volatile_ratio {
+            MarketRegime::Volatile
+        } else if dmi.adx >= c.trend_adx && slope_pct >= c.min_slope_pct {
+            MarketRegime::Bullish
+        } else if dmi.adx >= c.trend_adx && slope_pct <= -c.min_slope_pct {
+            MarketRegime::Bearish
+        } else {
+            MarketRegime::Sideways
+        };
+
+        Some(RegimeReading {
+            regime,
+            adx: dmi.adx,
+            plus_di: dmi.plus_di,
+            minus_di: dmi.minus_di,
+            slope_pct,
+            volatility,
+            volatility_ratio,
+            timestamp,
+        })
+    }
+
+    pub fn latest(&self) -> Option<&RegimeReading> {
+        self.latest.as_ref()
+    }
+
+    pub fn reset(&mut self) {
+        self.adx.reset();
+        self.ema.reset();
+        self.ema_history.clear();
+        self.returns.clear();
+        self.prev_close = None;
+        self.latest = None;
+    }
+}
+
+fn std_dev(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n).sqrt()
+}
+
+// =================================================================================
+// UNIT TESTS
+// =================================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar(i: u64, close: f64, range: f64) -> Candle {
+        Candle {
+            symbol: "TEST".to_string(),
+            timeframe: Timeframe::M5,
+            open_time: i * 300,
+            open: close,
+            high: close + range,
+            low: close - range,
+            close,
+            volume: 100.0,
+            trade_count: 10,
+        }
+    }
+
+    fn run(closes: impl Iterator<Item = (f64, f64)>) -> MarketRegime {
+        let mut classifier = RegimeClassifier::new(RegimeConfig::standard());
+        for (i, (close, range)) in closes.enumerate() {
+            classifier.update(&bar(i as u64, close, range));
+        }
+        classifier.latest().expect("classifier not warm").regime
+    }
+
+    #[test]
+    fn test_regime_labels() {
+        // Small alternating wiggle around a level, with a fixed range
+        let wiggle = |i: usize| if i.is_multiple_of(2) { 0.1 } else { -0.1 };
+
+        let uptrend = (0..150).map(|i| (100.0 + i as f64 * 0.5 + wiggle(i), 0.3));
+        assert_eq!(run(uptrend), MarketRegime::Bullish);
+
+        let downtrend = (0..150).map(|i| (200.0 - i as f64 * 0.5 + wiggle(i), 0.3));
+        assert_eq!(run(downtrend), MarketRegime::Bearish);
+
+        let flat = (0..150).map(|i| (100.0 + wiggle(i), 0.3));
+        assert_eq!(run(flat), MarketRegime::Sideways);
+
+        // Calm, then large swings over the last 20 bars
+        let burst = (0..150).map(|i| if i < 130 { (100.0 + wiggle(i), 0.3) } else { (100.0 + wiggle(i) * 30.0, 3.0) });
+        assert_eq!(run(burst), MarketRegime::Volatile);
+    }
+}
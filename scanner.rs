@@ -0,0 +1,238 @@
+// =================================================================================
+// MODULE: Watchlist Scanner
+// DESCRIPTION:
+// Polls a watchlist of symbols every cycle and ranks them by how unusual their
+// current volume is. The resulting leaderboard is the "top movers" view used by
+// the status report and by any component that wants to react across markets.
+//
+// Key components:
+// - Per-symbol volume spike detectors
+// - Price change and spread per symbol
+// - Ranked leaderboard (volume score, then absolute price change)
+// =================================================================================
+
+use crate::monad::{MResult, unit, fail, BotError, Bind, log_info};
+use crate::exchange::{ExchangeClient, Ticker};
+use crate::volume::{VolumeSpikeDetector, SpikeThresholds, SpikeSeverity};
+use std::collections::HashMap;
+
+/// Caps infinite scores (flat history) so rankings stay comparable.
+const MAX_VOLUME_SCORE: f64 = 10.0;
+
+/// One row of the leaderboard.
+#[derive(Debug, Clone)]
+pub struct ScanEntry {
+    pub symbol: String,
+    pub price: f64,
+    pub volume_1h: f64,
+    pub volume_score: f64,
+    pub severity: SpikeSeverity,
+    pub relative_volume: f64,
+    pub price_change_pct: f64, // Versus the ticker's open
+    pub spread_bps: f64,
+}
+
+/// Ranked snapshot of the watchlist, best mover first.
+#[derive(Debug, Clone)]
+pub struct Leaderboard {
+    pub entries: Vec<ScanEntry>,
+    pub failed_symbols: Vec<String>,
+    pub timestamp: u64,
+}
+
+impl Leaderboard {
+    pub fn top(&self, n: usize) -> &[ScanEntry] {
+        &self.entries[..n.min(self.entries.len())]
+    }
+
+    pub fn print(&self, n: usize) {
+        println!("| --- Top Volume Movers ---");
+        for (i, e) in self.top(n).iter().enumerate() {
+            println!(
+                "| #{:<2} {:<12} {:>10.2} | vol {:>10.0} {:<8} score {:>5.2} x{:.2} | {:+.2}% | spread {:.1}bps",
+                i + 1, e.symbol, e.price, e.volume_1h, e.severity, e.volume_score, e.relative_volume, e.price_change_pct, e.spread_bps
+            );
+        }
+        if !self.failed_symbols.is_empty() {
+            println!("| Unavailable: {}", self.failed_symbols.join(", "));
+        }
+        println!("| -------------------------");
+    }
+}
+
+/// Multi-symbol scanner with one volume detector per symbol.
+pub struct MarketScanner {
+    watchlist: Vec<String>,
+    detectors: HashMap<String, VolumeSpikeDetector>,
+    lookback: usize,
+    latest: Option<Leaderboard>,
+    latest_tickers: Vec<Ticker>,
+}
+
+impl MarketScanner {
+    pub fn new(watchlist: Vec<String>, lookback: usize) -> Self {
+        MarketScanner {
+            watchlist,
+            detectors: HashMap::new(),
+            lookback,
+            latest: None,
+            latest_tickers: Vec::new(),
+        }
+    }
+
+    pub fn watchlist(&self) -> &[String] {
+        &self.watchlist
+    }
+
+    /// Polls every symbol once and rebuilds the leaderboard.
+    /// Individual symbol failures are reported on the board; the scan only
+    /// fails if no symbol could be fetched.
+    pub fn scan(&mut self, client: &dyn ExchangeClient) -> MResult<&Leaderboard> {
+        let mut entries = Vec::with_capacity(self.watchlist.len());
+        let mut failed_symbols = Vec::new();
+        let mut timestamp = 0;
+        self.latest_tickers.clear();
+
+        for symbol in self.watchlist.clone() {
+            let result = client.fetch_ticker(&symbol)
+                .inspect(|ticker| {
+                    timestamp = timestamp.max(ticker.timestamp);
+                    self.latest_tickers.push(ticker.clone());
+                })
+                .bind(|ticker| self.evaluate(&ticker));
+
+            match result {
+                Ok(entry) => entries.push(entry),
+                Err(e) => {
+                    log_info(&format!("Scanner skipped {}: {:?}", symbol, e));
+                    failed_symbols.push(symbol);
+                }
+            }
+        }
+
+        if entries.is_empty() && !self.watchlist.is_empty() {
+            return fail(BotError::NetworkFailure("Scanner could not fetch any watchlist symbol".to_string()));
+        }
+
+        entries.sort_by(|a, b| {
+            b.volume_score.partial_cmp(&a.volume_score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(b.price_change_pct.abs().partial_cmp(&a.price_change_pct.abs()).unwrap_or(std::cmp::Ordering::Equal))
+        });
+
+        self.latest = Some(Leaderboard { entries, failed_symbols, timestamp });
+        unit(self.latest.as_ref().unwrap())
+    }
+
+    fn evaluate(&mut self, ticker: &Ticker) -> MResult<ScanEntry> {
+        let lookback = self.lookback;
+        let detector = self.detectors
+            .entry(ticker.symbol.clone())
+            .or_insert_with(|| VolumeSpikeDetector::new(lookback, SpikeThresholds::standard()));
+
+        detector.update(ticker).map_data(|spike| {
+            let (volume_score, severity, relative_volume) = match spike {
+                Some(s) => (s.score().clamp(0.0, MAX_VOLUME_SCORE), s.severity, s.relative_volume),
+                None => (0.0, SpikeSeverity::Normal, 1.0), // Still warming up
+            };
+            let mid = (ticker.bid + ticker.ask) / 2.0;
+
+            ScanEntry {
+                symbol: ticker.symbol.clone(),
+                price: ticker.price,
+                volume_1h: ticker.volume_1h,
+                volume_score,
+                severity,
+                relative_volume,
+                price_change_pct: if ticker.open > 0.0 { (ticker.price - ticker.open) / ticker.open * 100.0 } else { 0.0 },
+                spread_bps: if mid > 0.0 { (ticker.ask - ticker.bid) / mid * 10_000.0 } else { 0.0 },
+            }
+        })
+    }
+
+    /// Raw tickers fetched during the most recent scan.
+    pub fn latest_tickers(&self) -> &[Ticker] {
+        &self.latest_tickers
+    }
+
+    /// Leaderboard from the most recent scan.
+    pub fn leaderboard(&self) -> Option<&Leaderboard> {
+        self.latest.as_ref()
+    }
+}
+
+// =================================================================================
+// UNIT TESTS
+// =================================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::stub::StubExchange;
+
+    fn ticker(symbol: &str, price: f64, volume_1h: f64, timestamp: u64) -> Ticker {
+        Ticker {
+            symbol: symbol.to_string(),
+            price,
+            volume_24h: volume_1h * 24.0,
+            volume_1h,
+            open: 100.0,
+            high: price.max(100.0),
+            low: price.min(100.0),
+            bid: price - 0.1,
+            ask: price + 0.1,
+            timestamp,
+        }
+    }
+
+    /// Serves a fixed set of tickers; anything else is unavailable.
+    fn client(tickers: Vec<Ticker>) -> StubExchange {
+        StubExchange::new().with_tickers(tickers)
+    }
+
+    #[test]
+    fn test_ranks_watchlist_by_volume_then_price_change() {
+        let watchlist = ["FLAT", "SPIKE", "DROP", "RISE", "GONE"].iter().map(|s| s.to_string()).collect();
+        let mut scanner = MarketScanner::new(watchlist, 10);
+
+        // Warm-up: FLAT never moves, the others wander around 100
+        for (i, vol) in [90.0, 100.0, 110.0, 95.0, 105.0].iter().enumerate() {
+            let ts = i as u64;
+            scanner.scan(&client(vec![
+                ticker("FLAT", 100.0, 100.0, ts),
+                ticker("SPIKE", 100.0, *vol, ts),
+                ticker("DROP", 100.0, *vol, ts),
+                ticker("RISE", 100.0, *vol, ts),
+            ])).unwrap();
+        }
+
+        let board = scanner.scan(&client(vec![
+            ticker("FLAT", 100.0, 500.0, 5),
+            ticker("SPIKE", 101.0, 130.0, 5),
+            ticker("DROP", 95.0, 100.0, 5),
+            ticker("RISE", 102.0, 100.0, 5),
+        ])).unwrap().clone();
+
+        let order: Vec<&str> = board.entries.iter().map(|e| e.symbol.as_str()).collect();
+        assert_eq!(order, vec!["FLAT", "SPIKE", "DROP", "RISE"]);
+        assert_eq!(board.failed_symbols, vec!["GONE".to_string()]);
+        assert_eq!(board.timestamp, 5);
+
+        // A spike over a flat history is infinitely unusual; the cap keeps it comparable
+        assert_eq!(board.entries[0].volume_score, MAX_VOLUME_SCORE);
+        let spike = &board.entries[1];
+        assert!(spike.volume_score > 3.0 && spike.volume_score < MAX_VOLUME_SCORE);
+        assert!((spike.price_change_pct - 1.0).abs() < 1e-9);
+
+        let drop = &board.entries[2];
+        assert_eq!(drop.volume_score, 0.0);
+        assert!((drop.price_change_pct + 5.0).abs() < 1e-9);
+        assert!((drop.spread_bps - 0.2 / 95.0 * 10_000.0).abs() < 1e-9);
+        assert_eq!(board.top(2).len(), 2);
+    }
+
+    #[test]
+    fn test_scan_fails_when_nothing_is_reachable() {
+        let mut scanner = MarketScanner::new(vec!["GONE".to_string()], 10);
+        assert!(scanner.scan(&client(Vec::new())).is_err());
+    }
+}
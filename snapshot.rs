@@ -0,0 +1,184 @@
+// =================================================================================
+// MODULE: State Snapshots
+// DESCRIPTION:
+// Persists indicator and strategy state across restarts so the engine does not
+// have to re-learn everything from scratch. Snapshots are versioned JSON
+// documents; anything from another format version, another symbol, or older
+// than the configured maximum age is discarded and the engine falls back to
+// the historical warm-up.
+// =================================================================================
+
+use crate::monad::{MResult, unit, fail, BotError, Bind, log_info};
+use crate::json::JsonValue;
+use std::fs;
+
+/// Bump whenever an indicator or strategy changes its snapshot layout.
+pub const SNAPSHOT_VERSION: u64 = 1;
+
+#[derive(Debug, Clone)]
+pub struct StateSnapshot {
+    pub version: u64,
+    pub saved_at: u64, // Unix seconds
+    pub symbol: String,
+    pub strategy: JsonValue,
+}
+
+impl StateSnapshot {
+    pub fn new(symbol: &str, strategy: JsonValue, saved_at: u64) -> Self {
+        StateSnapshot {
+            version: SNAPSHOT_VERSION,
+            saved_at,
+            symbol: symbol.to_string(),
+            strategy,
+        }
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        JsonValue::object()
+            .with("version", self.version)
+            .with("saved_at", self.saved_at)
+            .with("symbol", self.symbol.as_str())
+            .with("strategy", self.strategy.clone())
+    }
+
+    pub fn from_json(value: &JsonValue) -> MResult<Self> {
+        let field = |key: &str| value.get(key).ok_or_else(|| invalid(&format!("snapshot is missing '{}'", key)));
+        let version = field("version")?.as_i64().ok_or_else(|| invalid("snapshot version is not an integer"))?;
+        let saved_at = field("saved_at")?.as_i64().ok_or_else(|| invalid("snapshot time is not an integer"))?;
+        let symbol = field("symbol")?.as_str().ok_or_else(|| invalid("snapshot symbol is not a string"))?;
+        unit(StateSnapshot {
+            version: version.max(0) as u64,
+            saved_at: saved_at.max(0) as u64,
+            symbol: symbol.to_string(),
+            strategy: field("strategy")?.clone(),
+        })
+    }
+
+    /// Writes to a temporary file first so a crash mid-write never leaves a
+    /// truncated snapshot behind.
+    pub fn save(&self, path: &str) -> MResult<()> {
+        let tmp = format!("{}.tmp", path);
+        fs::write(&tmp, self.to_json().to_string())
+            .and_then(|_| fs::rename(&tmp, path))
+            .map_err(|e| BotError::InternalStateError(format!("Cannot write snapshot {}: {}", path, e)))
+    }
+
+    /// Loads the snapshot for `symbol` if it is usable at `now`.
+    /// Missing, stale, foreign or other-version snapshots yield `None`.
+    pub fn load(path: &str, symbol: &str, max_age_secs: u64, now: u64) -> MResult<Option<Self>> {
+        let content = match fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return unit(None),
+            Err(e) => return fail(BotError::InternalStateError(format!("Cannot read snapshot {}: {}", path, e))),
+        };
+
+        JsonValue::parse(&content)
+            .map_err(|e| invalid(&format!("{} is not valid JSON: {:?}", path, e)))
+            .bind(|json| Self::from_json(&json))
+            .map_data(|snapshot| {
+                let age = now.saturating_sub(snapshot.saved_at);
+                if snapshot.version != SNAPSHOT_VERSION {
+                    log_info(&format!("Discarding snapshot v{} (expected v{})", snapshot.version, SNAPSHOT_VERSION));
+                    None
+                } else if snapshot.symbol != symbol {
+                    log_info(&format!("Discarding snapshot for {} (trading {})", snapshot.symbol, symbol));
+                    None
+                } else if age > max_age_secs {
+                    log_info(&format!("Discarding snapshot from {}s ago (max age {}s)", age, max_age_secs));
+                    None
+                } else {
+                    Some(snapshot)
+                }
+            })
+    }
+}
+
+// --- Helpers for snapshot implementations ---
+
+pub fn invalid(message: &str) -> BotError {
+    BotError::InternalStateError(format!("Invalid snapshot: {}", message))
+}
+
+pub fn f64_array(values: impl IntoIterator<Item = f64>) -> JsonValue {
+    JsonValue::Array(values.into_iter().map(JsonValue::Number).collect())
+}
+
+/// Reads `state[key]` as an array of numbers.
+pub fn read_f64_array(state: &JsonValue, key: &str) -> MResult<Vec<f64>> {
+    state.get(key)
+        .and_then(|v| v.as_array())
+        .and_then(|items| items.iter().map(|v| v.as_f64()).collect::<Option<Vec<f64>>>())
+        .ok_or_else(|| invalid(&format!("'{}' is not an array of numbers", key)))
+}
+
+/// Reads `state[key]` as a number, where `null` means `None`.
+pub fn read_optional_f64(state: &JsonValue, key: &str) -> MResult<Option<f64>> {
+    match state.get(key) {
+        Some(JsonValue::Null) => unit(None),
+        Some(v) => v.as_f64().map(Some).ok_or_else(|| invalid(&format!("'{}' is not a number", key))),
+        None => fail(invalid(&format!("missing '{}'", key))),
+    }
+}
+
+/// Fails unless `state["period"]` equals the indicator's configured period.
+pub fn expect_period(state: &JsonValue, period: usize) -> MResult<()> {
+    match state.get("period").and_then(|v| v.as_i64()) {
+        Some(p) if p == period as i64 => unit(()),
+        other => fail(invalid(&format!("period {:?} does not match configured {}", other, period))),
+    }
+}
+
+// =================================================================================
+// UNIT TESTS
+// =================================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candles::{Candle, Timeframe};
+    use crate::trader::{Strategy, VolumeBreakoutStrategy};
+    use crate::volume::SpikeSeverity;
+
+    fn minute_bar(i: u64) -> Candle {
+        let close = 100.0 + (i as f64 * 0.7).sin() * 3.0;
+        Candle {
+            symbol: "BTCUSDT".to_string(),
+            timeframe: Timeframe::M1,
+            open_time: i * 60,
+            open: close,
+            high: close + 0.5,
+            low: close - 0.5,
+            close,
+            volume: 10.0,
+            trade_count: 5,
+        }
+    }
+
+    #[test]
+    fn test_strategy_snapshot_round_trip_and_staleness() {
+        let mut original = VolumeBreakoutStrategy::new(SpikeSeverity::Elevated, Some(Timeframe::M1));
+        original.warm_up(&(0..30).map(minute_bar).collect::<Vec<_>>());
+
+        let path = std::env::temp_dir().join(format!("snapshot_test_{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        StateSnapshot::new("BTCUSDT", original.snapshot().unwrap(), 10_000).save(path).unwrap();
+
+        assert!(StateSnapshot::load(path, "BTCUSDT", 900, 10_901).unwrap().is_none()); // Stale
+        assert!(StateSnapshot::load(path, "ETHUSDT", 900, 10_100).unwrap().is_none()); // Other symbol
+        let loaded = StateSnapshot::load(path, "BTCUSDT", 900, 10_100).unwrap().unwrap();
+        std::fs::remove_file(path).ok();
+
+        let mut restored = VolumeBreakoutStrategy::new(SpikeSeverity::Elevated, Some(Timeframe::M1));
+        restored.restore(&loaded.strategy).unwrap();
+        assert_eq!(restored.snapshot().unwrap().to_string(), original.snapshot().unwrap().to_string());
+
+        // Catching up only replays the bars after the snapshot
+        let catch_up: Vec<Candle> = (20..35).map(minute_bar).collect();
+        original.warm_up(&catch_up);
+        restored.warm_up(&catch_up);
+        assert_eq!(restored.snapshot().unwrap().to_string(), original.snapshot().unwrap().to_string());
+
+        // A different timeframe is rejected and leaves the strategy cold
+        let mut other = VolumeBreakoutStrategy::new(SpikeSeverity::Elevated, Some(Timeframe::M5));
+        assert!(other.restore(&loaded.strategy).is_err());
+    }
+}
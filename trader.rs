@@ -5,19 +5,27 @@
 // indicators, Strategy Signal generation, and a rigorous Risk Management layer.
 //
 // Key components:
-// - Technical Indicators (RSI, MA, VWAP)
+// - Technical Indicators (SMA/EMA/WMA/DEMA, RSI, MACD, Bollinger, Stochastic, ADX;
+//   ATR, OBV, VWAP, MFI, CMF on OHLCV bars)
 // - Strategy Interfaces
 // - Signal Aggregation
 // - Position Sizing (Kelly Criterion / Fixed Fractional)
 // =================================================================================
 
 use crate::monad::{MResult, unit, fail, BotError, Bind};
-use crate::exchange::{Ticker, OrderSide, Balance};
-use std::collections::VecDeque;
+use crate::exchange::{Ticker, OrderSide, Balance, OrderBook, Order, OrderRequest, Fill};
+use crate::volume::{VolumeSpike, SpikeSeverity};
+use crate::flow::FlowWindow;
+use crate::profile::ProfileSummary;
+use crate::candles::{Candle, Timeframe, TimeframeIndicator};
+use crate::json::JsonValue;
+use crate::regime::RegimeReading;
+use crate::snapshot::{self, f64_array, read_f64_array, read_optional_f64, expect_period};
+use std::collections::{HashMap, VecDeque};
 
 // --- Signal & Analysis Structures ---
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum MarketRegime {
     Bullish,
     Bearish,
@@ -25,52 +33,942 @@ pub enum MarketRegime {
     Volatile,
 }
 
+impl MarketRegime {
+    pub const ALL: [MarketRegime; 4] = [MarketRegime::Bullish, MarketRegime::Bearish, MarketRegime::Sideways, MarketRegime::Volatile];
+}
+
 #[derive(Debug, Clone)]
 pub struct Signal {
     pub symbol: String,
     pub side: OrderSide,
     pub strength: f64, // 0.0 to 1.0
+    pub quantity: Option<f64>, // Exact base amount, e.g. to close a tracked position; `None` is sized by risk
     pub regime: MarketRegime,
     pub timestamp: u64,
     pub reason: String,
+    pub components: Vec<StrengthComponent>, // How `strength` was scored; empty if not broken down
+    pub context: SignalContext, // Indicator values and conditions at the moment of decision
+}
+
+impl Signal {
+    /// Everything needed to reconstruct the decision, for logs next to the order.
+    pub fn explain(&self) -> JsonValue {
+        let side = if self.side == OrderSide::Buy { "buy" } else { "sell" };
+        let components = self.components.iter()
+            .map(|c| JsonValue::object()
+                .with("name", c.name)
+                .with("value", c.value)
+                .with("score", c.score)
+                .with("weight", c.weight))
+            .collect();
+        JsonValue::object()
+            .with("symbol", self.symbol.as_str())
+            .with("side", side)
+            .with("strength", self.strength)
+            .with("quantity", self.quantity)
+            .with("regime", format!("{:?}", self.regime))
+            .with("timestamp", self.timestamp)
+            .with("reason", self.reason.as_str())
+            .with("components", JsonValue::Array(components))
+            .with("indicators", self.context.indicators_json())
+            .with("conditions", self.context.conditions_json())
+    }
+}
+
+/// One condition a strategy evaluated before emitting a signal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConditionCheck {
+    pub name: &'static str,
+    pub expected: String, // Human-readable threshold, e.g. "50 < rsi < 70"
+    pub actual: f64,
+    pub passed: bool,
+}
+
+/// Structured snapshot of a strategy's inputs when it decided to signal.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SignalContext {
+    pub indicators: Vec<(&'static str, f64)>,
+    pub conditions: Vec<ConditionCheck>,
+}
+
+impl SignalContext {
+    pub fn new() -> Self {
+        SignalContext::default()
+    }
+
+    pub fn indicator(mut self, name: &'static str, value: f64) -> Self {
+        self.indicators.push((name, value));
+        self
+    }
+
+    pub fn check(mut self, name: &'static str, expected: impl Into<String>, actual: f64, passed: bool) -> Self {
+        self.conditions.push(ConditionCheck { name, expected: expected.into(), actual, passed });
+        self
+    }
+
+    fn indicators_json(&self) -> JsonValue {
+        self.indicators.iter().fold(JsonValue::object(), |obj, (name, value)| obj.with(name, *value))
+    }
+
+    fn conditions_json(&self) -> JsonValue {
+        JsonValue::Array(self.conditions.iter()
+            .map(|c| JsonValue::object()
+                .with("name", c.name)
+                .with("expected", c.expected.as_str())
+                .with("actual", c.actual)
+                .with("passed", c.passed))
+            .collect())
+    }
+}
+
+// --- Signal Strength Model ---
+//
+// Strength is the weighted mean of four component scores, each in [0, 1]:
+//
+//   component  weight  score
+//   volume     0.35    spike z-score / 6 (a 2-sigma spike scores 0.33, 6+ sigma scores 1)
+//   trend      0.30    fast/slow SMA gap in the signal's direction / 1% (1% or wider scores 1)
+//   momentum   0.20    buy:  1 - |RSI - 60| / 10 (best with room to run; 0 at 50 or 70)
+//                      sell: (RSI - 70) / 20 (0 at 70, 1 at 90)
+//   regime     0.15    1 aligned (Bullish buy / Bearish sell), 0.5 Sideways or unknown,
+//                      0.25 Volatile, 0 against the trend
+//
+// `RiskManager::calculate_entry` scales risk by the result, so a marginal setup
+// trades small and a textbook one trades full size.
+
+/// One scored input to a signal's strength.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrengthComponent {
+    pub name: &'static str,
+    pub value: f64,  // Raw input, e.g. the z-score or RSI
+    pub score: f64,  // Normalized to [0, 1]
+    pub weight: f64,
+}
+
+/// Inputs the strength model needs from a strategy.
+#[derive(Debug, Clone, Copy)]
+pub struct StrengthInputs {
+    pub volume_z: f64,
+    pub fast_ma: f64,
+    pub slow_ma: f64,
+    pub rsi: f64,
+    pub regime: Option<MarketRegime>,
+}
+
+/// Normalizes a raw score to [0, 1]. `+inf` (a spike over a flat history)
+/// saturates to 1; NaN and `-inf` score 0.
+pub fn clamp01(x: f64) -> f64 {
+    if x.is_nan() { 0.0 } else { x.clamp(0.0, 1.0) }
+}
+
+/// Weighted mean of the component scores, in [0, 1].
+pub fn weighted_strength(components: &[StrengthComponent]) -> f64 {
+    let total_weight: f64 = components.iter().map(|c| c.weight).sum();
+    if total_weight <= 0.0 {
+        return 0.0;
+    }
+    clamp01(components.iter().map(|c| c.score * c.weight).sum::<f64>() / total_weight)
+}
+
+/// Scores a signal per the model above. Returns `(strength, components)`.
+pub fn score_signal(side: &OrderSide, inputs: &StrengthInputs) -> (f64, Vec<StrengthComponent>) {
+    let direction = if *side == OrderSide::Buy { 1.0 } else { -1.0 };
+    let gap_pct = if inputs.slow_ma != 0.0 { (inputs.fast_ma / inputs.slow_ma - 1.0) * 100.0 } else { 0.0 };
+
+    let momentum = match side {
+        OrderSide::Buy => 1.0 - (inputs.rsi - 60.0).abs() / 10.0,
+        OrderSide::Sell => (inputs.rsi - 70.0) / 20.0,
+    };
+    let regime = match (inputs.regime, side) {
+        (Some(MarketRegime::Bullish), OrderSide::Buy) | (Some(MarketRegime::Bearish), OrderSide::Sell) => 1.0,
+        (Some(MarketRegime::Bullish), OrderSide::Sell) | (Some(MarketRegime::Bearish), OrderSide::Buy) => 0.0,
+        (Some(MarketRegime::Volatile), _) => 0.25,
+        (Some(MarketRegime::Sideways), _) | (None, _) => 0.5,
+    };
+    let regime_value = match inputs.regime {
+        Some(MarketRegime::Bullish) => 1.0,
+        Some(MarketRegime::Bearish) => -1.0,
+        _ => 0.0,
+    };
+
+    let components = vec![
+        StrengthComponent { name: "volume", value: inputs.volume_z, score: clamp01(inputs.volume_z / 6.0), weight: 0.35 },
+        StrengthComponent { name: "trend", value: gap_pct, score: clamp01(direction * gap_pct), weight: 0.30 },
+        StrengthComponent { name: "momentum", value: inputs.rsi, score: clamp01(momentum), weight: 0.20 },
+        StrengthComponent { name: "regime", value: regime_value, score: regime, weight: 0.15 },
+    ];
+    (weighted_strength(&components), components)
+}
+
+/// `volume 0.33 | trend 0.80 | ...` for logs.
+pub fn format_components(components: &[StrengthComponent]) -> String {
+    components.iter()
+        .map(|c| format!("{} {:.2}", c.name, c.score))
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+/// The output of the Risk Manager: A fully validated instruction.
+#[derive(Debug)]
+pub struct TradeInstruction {
+    pub symbol: String,
+    pub side: OrderSide,
+    pub amount: f64,
+    pub limit_price: Option<f64>,
+    pub stop_loss: Option<f64>,
+    pub take_profit: Option<f64>,
+    pub explanation: JsonValue, // `Signal::explain` of the signal this instruction came from
+}
+
+// --- Technical Analysis Components ---
+
+/// Trait for any technical indicator.
+///
+/// Price-only indicators implement `update` and inherit `update_candle`, which
+/// feeds them the bar's close. Range- and volume-based indicators override
+/// `update_candle`; their `update` treats a bare price as a flat, zero-volume bar.
+pub trait Indicator {
+    fn update(&mut self, price: f64);
+    fn value(&self) -> Option<f64>;
+    fn reset(&mut self);
+
+    fn update_candle(&mut self, candle: &Candle) {
+        self.update(candle.close);
+    }
+
+    /// Internal state for persistence; `None` if the indicator does not support snapshots.
+    fn snapshot(&self) -> Option<JsonValue> {
+        None
+    }
+
+    /// Restores state produced by `snapshot`. Fails, leaving the indicator
+    /// untouched, if the state does not match this indicator's configuration.
+    fn restore(&mut self, _state: &JsonValue) -> MResult<()> {
+        fail(snapshot::invalid("indicator does not support snapshots"))
+    }
+}
+
+/// Bar view of a bare price, for OHLCV indicators fed through `update`.
+fn flat_candle(price: f64) -> Candle {
+    Candle {
+        symbol: String::new(),
+        timeframe: Timeframe::M1,
+        open_time: 0,
+        open: price,
+        high: price,
+        low: price,
+        close: price,
+        volume: 0.0,
+        trade_count: 0,
+    }
+}
+
+/// Compensated (Kahan-Neumaier) running sum. Adding and removing values for
+/// millions of ticks keeps the error at a few ulps instead of drifting.
+#[derive(Debug, Clone, Copy, Default)]
+struct RunningSum {
+    sum: f64,
+    compensation: f64,
+}
+
+impl RunningSum {
+    fn add(&mut self, value: f64) {
+        let total = self.sum + value;
+        if self.sum.abs() >= value.abs() {
+            self.compensation += (self.sum - total) + value;
+        } else {
+            self.compensation += (value - total) + self.sum;
+        }
+        self.sum = total;
+    }
+
+    fn value(&self) -> f64 {
+        self.sum + self.compensation
+    }
+}
+
+/// Simple Moving Average Implementation.
+/// Constant-time updates: the window keeps a running sum instead of re-summing.
+pub struct SMA {
+    period: usize,
+    history: VecDeque<f64>,
+    sum: RunningSum,
+}
+
+impl SMA {
+    pub fn new(period: usize) -> Self {
+        SMA {
+            period,
+            history: VecDeque::with_capacity(period),
+            sum: RunningSum::default(),
+        }
+    }
+}
+
+impl Indicator for SMA {
+    fn update(&mut self, price: f64) {
+        if self.history.len() >= self.period {
+            if let Some(oldest) = self.history.pop_front() {
+                self.sum.add(-oldest);
+            }
+        }
+        self.history.push_back(price);
+        self.sum.add(price);
+    }
+
+    fn value(&self) -> Option<f64> {
+        if self.history.len() < self.period {
+            return None;
+        }
+        Some(self.sum.value() / self.period as f64)
+    }
+
+    fn reset(&mut self) {
+        self.history.clear();
+        self.sum = RunningSum::default();
+    }
+
+    fn snapshot(&self) -> Option<JsonValue> {
+        Some(JsonValue::object()
+            .with("period", self.period as u64)
+            .with("history", f64_array(self.history.iter().copied())))
+    }
+
+    fn restore(&mut self, state: &JsonValue) -> MResult<()> {
+        expect_period(state, self.period)?;
+        let history = read_f64_array(state, "history")?;
+        if history.len() > self.period {
+            return fail(snapshot::invalid("SMA history longer than its period"));
+        }
+        self.reset();
+        history.into_iter().for_each(|price| self.update(price));
+        unit(())
+    }
+}
+
+/// How RSI averages gains and losses.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RsiSmoothing {
+    /// Wilder's smoothing: seeded with the simple average of the first
+    /// `period` changes, then `avg = (avg * (n - 1) + x) / n`. The standard.
+    Wilder,
+    /// Plain average of the last `period` changes, as earlier versions of
+    /// this bot computed it. Kept for comparing against old results.
+    Simple,
+}
+
+impl RsiSmoothing {
+    pub fn parse(value: &str) -> MResult<RsiSmoothing> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "wilder" => unit(RsiSmoothing::Wilder),
+            "simple" => unit(RsiSmoothing::Simple),
+            other => fail(BotError::ConfigurationError(format!("Unknown RSI smoothing '{}' (use wilder or simple)", other))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RsiSmoothing::Wilder => "wilder",
+            RsiSmoothing::Simple => "simple",
+        }
+    }
+}
+
+/// Relative Strength Index Implementation.
+/// Both smoothing modes update in constant time.
+pub struct RSI {
+    period: usize,
+    smoothing: RsiSmoothing,
+    changes: VecDeque<(f64, f64)>, // (gain, loss): seed window (Wilder) or full window (Simple)
+    gain_sum: RunningSum,
+    loss_sum: RunningSum,
+    averages: Option<(f64, f64)>, // Wilder (avg gain, avg loss) once seeded
+    prev_price: Option<f64>,
+}
+
+impl RSI {
+    pub fn new(period: usize) -> Self {
+        RSI::with_smoothing(period, RsiSmoothing::Wilder)
+    }
+
+    pub fn with_smoothing(period: usize, smoothing: RsiSmoothing) -> Self {
+        RSI {
+            period: period.max(1),
+            smoothing,
+            changes: VecDeque::with_capacity(period),
+            gain_sum: RunningSum::default(),
+            loss_sum: RunningSum::default(),
+            averages: None,
+            prev_price: None,
+        }
+    }
+
+    fn push_change(&mut self, gain: f64, loss: f64) {
+        if self.changes.len() >= self.period {
+            if let Some((old_gain, old_loss)) = self.changes.pop_front() {
+                self.gain_sum.add(-old_gain);
+                self.loss_sum.add(-old_loss);
+            }
+        }
+        self.changes.push_back((gain, loss));
+        self.gain_sum.add(gain);
+        self.loss_sum.add(loss);
+    }
+}
+
+impl Indicator for RSI {
+    fn update(&mut self, price: f64) {
+        if let Some(prev) = self.prev_price {
+            let change = price - prev;
+            let gain = if change > 0.0 { change } else { 0.0 };
+            let loss = if change < 0.0 { -change } else { 0.0 };
+            let n = self.period as f64;
+
+            match (self.smoothing, self.averages) {
+                (RsiSmoothing::Wilder, Some((avg_gain, avg_loss))) => {
+                    self.averages = Some(((avg_gain * (n - 1.0) + gain) / n, (avg_loss * (n - 1.0) + loss) / n));
+                }
+                (RsiSmoothing::Wilder, None) => {
+                    self.push_change(gain, loss);
+                    if self.changes.len() == self.period {
+                        self.averages = Some((self.gain_sum.value() / n, self.loss_sum.value() / n));
+                        self.changes.clear();
+                    }
+                }
+                (RsiSmoothing::Simple, _) => self.push_change(gain, loss),
+            }
+        }
+        self.prev_price = Some(price);
+    }
+
+    fn value(&self) -> Option<f64> {
+        let (avg_gain, avg_loss) = match self.smoothing {
+            RsiSmoothing::Wilder => self.averages?,
+            RsiSmoothing::Simple => {
+                if self.changes.len() < self.period { return None; }
+                let n = self.period as f64;
+                (self.gain_sum.value() / n, self.loss_sum.value() / n)
+            }
+        };
+
+        if avg_loss <= 0.0 { return Some(100.0); }
+
+        let rs = avg_gain / avg_loss;
+        Some(100.0 - (100.0 / (1.0 + rs)))
+    }
+
+    fn reset(&mut self) {
+        self.changes.clear();
+        self.gain_sum = RunningSum::default();
+        self.loss_sum = RunningSum::default();
+        self.averages = None;
+        self.prev_price = None;
+    }
+
+    fn snapshot(&self) -> Option<JsonValue> {
+        Some(JsonValue::object()
+            .with("period", self.period as u64)
+            .with("smoothing", self.smoothing.name())
+            .with("prev_price", self.prev_price)
+            .with("gains", f64_array(self.changes.iter().map(|c| c.0)))
+            .with("losses", f64_array(self.changes.iter().map(|c| c.1)))
+            .with("avg_gain", self.averages.map(|a| a.0))
+            .with("avg_loss", self.averages.map(|a| a.1)))
+    }
+
+    fn restore(&mut self, state: &JsonValue) -> MResult<()> {
+        expect_period(state, self.period)?;
+        if state.get("smoothing").and_then(|v| v.as_str()) != Some(self.smoothing.name()) {
+            return fail(snapshot::invalid("RSI smoothing mode does not match"));
+        }
+        let prev_price = read_optional_f64(state, "prev_price")?;
+        let gains = read_f64_array(state, "gains")?;
+        let losses = read_f64_array(state, "losses")?;
+        let averages = match (read_optional_f64(state, "avg_gain")?, read_optional_f64(state, "avg_loss")?) {
+            (Some(g), Some(l)) => Some((g, l)),
+            (None, None) => None,
+            _ => return fail(snapshot::invalid("RSI averages are incomplete")),
+        };
+        if gains.len() != losses.len() || gains.len() > self.period {
+            return fail(snapshot::invalid("RSI change windows are inconsistent"));
+        }
+
+        self.reset();
+        gains.into_iter().zip(losses).for_each(|(g, l)| self.push_change(g, l));
+        self.averages = averages;
+        self.prev_price = prev_price;
+        unit(())
+    }
+}
+
+// --- Range & Volume Indicators (OHLCV input) ---
+
+/// Average True Range with Wilder smoothing, seeded by the mean of the
+/// first `period` true ranges.
+pub struct ATR {
+    period: usize,
+    prev_close: Option<f64>,
+    seed: Vec<f64>,
+    atr: Option<f64>,
+}
+
+impl ATR {
+    pub fn new(period: usize) -> Self {
+        ATR { period: period.max(1), prev_close: None, seed: Vec::with_capacity(period), atr: None }
+    }
+}
+
+impl Indicator for ATR {
+    fn update(&mut self, price: f64) {
+        self.update_candle(&flat_candle(price));
+    }
+
+    fn update_candle(&mut self, candle: &Candle) {
+        let range = candle.high - candle.low;
+        let true_range = match self.prev_close {
+            Some(pc) => range.max((candle.high - pc).abs()).max((candle.low - pc).abs()),
+            None => range,
+        };
+        self.prev_close = Some(candle.close);
+
+        match self.atr {
+            Some(atr) => {
+                let n = self.period as f64;
+                self.atr = Some((atr * (n - 1.0) + true_range) / n);
+            }
+            None => {
+                self.seed.push(true_range);
+                if self.seed.len() == self.period {
+                    self.atr = Some(self.seed.iter().sum::<f64>() / self.period as f64);
+                    self.seed.clear();
+                }
+            }
+        }
+    }
+
+    fn value(&self) -> Option<f64> {
+        self.atr
+    }
+
+    fn reset(&mut self) {
+        self.prev_close = None;
+        self.seed.clear();
+        self.atr = None;
+    }
+}
+
+/// On-Balance Volume: cumulative volume signed by the close-to-close direction.
+#[derive(Default)]
+pub struct OBV {
+    prev_close: Option<f64>,
+    total: f64,
+}
+
+impl OBV {
+    pub fn new() -> Self {
+        OBV { prev_close: None, total: 0.0 }
+    }
+}
+
+impl Indicator for OBV {
+    fn update(&mut self, price: f64) {
+        self.update_candle(&flat_candle(price));
+    }
+
+    fn update_candle(&mut self, candle: &Candle) {
+        if let Some(pc) = self.prev_close {
+            if candle.close > pc {
+                self.total += candle.volume;
+            } else if candle.close < pc {
+                self.total -= candle.volume;
+            }
+        }
+        self.prev_close = Some(candle.close);
+    }
+
+    fn value(&self) -> Option<f64> {
+        self.prev_close.map(|_| self.total)
+    }
+
+    fn reset(&mut self) {
+        self.prev_close = None;
+        self.total = 0.0;
+    }
+}
+
+/// Volume-Weighted Average Price of the typical price, anchored to sessions
+/// of `session_secs` (86_400 for the usual UTC-daily VWAP).
+pub struct VWAP {
+    session_secs: u64,
+    session_start: Option<u64>,
+    price_volume: f64,
+    volume: f64,
+}
+
+impl VWAP {
+    pub fn new(session_secs: u64) -> Self {
+        VWAP { session_secs: session_secs.max(1), session_start: None, price_volume: 0.0, volume: 0.0 }
+    }
+}
+
+impl Indicator for VWAP {
+    fn update(&mut self, price: f64) {
+        self.update_candle(&flat_candle(price));
+    }
+
+    fn update_candle(&mut self, candle: &Candle) {
+        if candle.trade_count > 0 || candle.volume > 0.0 {
+            let session = candle.open_time - candle.open_time % self.session_secs;
+            if self.session_start.is_some_and(|s| s != session) {
+                self.price_volume = 0.0;
+                self.volume = 0.0;
+            }
+            self.session_start = Some(session);
+        }
+        self.price_volume += candle.typical_price() * candle.volume;
+        self.volume += candle.volume;
+    }
+
+    fn value(&self) -> Option<f64> {
+        if self.volume > 0.0 { Some(self.price_volume / self.volume) } else { None }
+    }
+
+    fn reset(&mut self) {
+        self.session_start = None;
+        self.price_volume = 0.0;
+        self.volume = 0.0;
+    }
+}
+
+/// Money Flow Index: a volume-weighted RSI over typical prices.
+pub struct MFI {
+    period: usize,
+    prev_typical: Option<f64>,
+    flows: VecDeque<(f64, f64)>, // (positive, negative) raw money flow
+}
+
+impl MFI {
+    pub fn new(period: usize) -> Self {
+        MFI { period: period.max(1), prev_typical: None, flows: VecDeque::with_capacity(period) }
+    }
+}
+
+impl Indicator for MFI {
+    fn update(&mut self, price: f64) {
+        self.update_candle(&flat_candle(price));
+    }
+
+    fn update_candle(&mut self, candle: &Candle) {
+        let typical = candle.typical_price();
+        if let Some(prev) = self.prev_typical {
+            let flow = typical * candle.volume;
+            let entry = if typical > prev {
+                (flow, 0.0)
+            } else if typical < prev {
+                (0.0, flow)
+            } else {
+                (0.0, 0.0)
+            };
+            if self.flows.len() >= self.period {
+                self.flows.pop_front();
+            }
+            self.flows.push_back(entry);
+        }
+        self.prev_typical = Some(typical);
+    }
+
+    fn value(&self) -> Option<f64> {
+        if self.flows.len() < self.period {
+            return None;
+        }
+        let positive: f64 = self.flows.iter().map(|f| f.0).sum();
+        let negative: f64 = self.flows.iter().map(|f| f.1).sum();
+        if negative == 0.0 {
+            return Some(if positive == 0.0 { 50.0 } else { 100.0 });
+        }
+        Some(100.0 - 100.0 / (1.0 + positive / negative))
+    }
+
+    fn reset(&mut self) {
+        self.prev_typical = None;
+        self.flows.clear();
+    }
+}
+
+/// Chaikin Money Flow: where closes sit within their bars, volume-weighted
+/// over `period` bars. Ranges from -1 (closing on lows) to +1 (on highs).
+pub struct CMF {
+    period: usize,
+    bars: VecDeque<(f64, f64)>, // (money flow volume, volume)
+}
+
+impl CMF {
+    pub fn new(period: usize) -> Self {
+        CMF { period: period.max(1), bars: VecDeque::with_capacity(period) }
+    }
+}
+
+impl Indicator for CMF {
+    fn update(&mut self, price: f64) {
+        self.update_candle(&flat_candle(price));
+    }
+
+    fn update_candle(&mut self, candle: &Candle) {
+        let range = candle.high - candle.low;
+        let multiplier = if range > 0.0 {
+            ((candle.close - candle.low) - (candle.high - candle.close)) / range
+        } else {
+            0.0
+        };
+        if self.bars.len() >= self.period {
+            self.bars.pop_front();
+        }
+        self.bars.push_back((multiplier * candle.volume, candle.volume));
+    }
+
+    fn value(&self) -> Option<f64> {
+        if self.bars.len() < self.period {
+            return None;
+        }
+        let volume: f64 = self.bars.iter().map(|b| b.1).sum();
+        if volume <= 0.0 {
+            return None;
+        }
+        Some(self.bars.iter().map(|b| b.0).sum::<f64>() / volume)
+    }
+
+    fn reset(&mut self) {
+        self.bars.clear();
+    }
+}
+
+// --- Moving Averages ---
+
+/// Exponential Moving Average, seeded with the SMA of the first `period` prices.
+pub struct EMA {
+    period: usize,
+    alpha: f64,
+    seed: Vec<f64>,
+    ema: Option<f64>,
+}
+
+impl EMA {
+    pub fn new(period: usize) -> Self {
+        let period = period.max(1);
+        EMA { period, alpha: 2.0 / (period as f64 + 1.0), seed: Vec::with_capacity(period), ema: None }
+    }
+}
+
+impl Indicator for EMA {
+    fn update(&mut self, price: f64) {
+        match self.ema {
+            Some(ema) => self.ema = Some(ema + self.alpha * (price - ema)),
+            None => {
+                self.seed.push(price);
+                if self.seed.len() == self.period {
+                    self.ema = Some(self.seed.iter().sum::<f64>() / self.period as f64);
+                    self.seed.clear();
+                }
+            }
+        }
+    }
+
+    fn value(&self) -> Option<f64> {
+        self.ema
+    }
+
+    fn reset(&mut self) {
+        self.seed.clear();
+        self.ema = None;
+    }
+
+    fn snapshot(&self) -> Option<JsonValue> {
+        Some(JsonValue::object()
+            .with("period", self.period as u64)
+            .with("seed", f64_array(self.seed.iter().copied()))
+            .with("ema", self.ema))
+    }
+
+    fn restore(&mut self, state: &JsonValue) -> MResult<()> {
+        expect_period(state, self.period)?;
+        let seed = read_f64_array(state, "seed")?;
+        let ema = read_optional_f64(state, "ema")?;
+        if seed.len() >= self.period {
+            return fail(snapshot::invalid("EMA seed longer than its period"));
+        }
+        self.seed = seed;
+        self.ema = ema;
+        unit(())
+    }
+}
+
+/// Linearly Weighted Moving Average; the newest price weighs `period`, the oldest 1.
+pub struct WMA {
+    period: usize,
+    history: VecDeque<f64>,
+}
+
+impl WMA {
+    pub fn new(period: usize) -> Self {
+        WMA { period: period.max(1), history: VecDeque::with_capacity(period) }
+    }
+}
+
+impl Indicator for WMA {
+    fn update(&mut self, price: f64) {
+        if self.history.len() >= self.period {
+            self.history.pop_front();
+        }
+        self.history.push_back(price);
+    }
+
+    fn value(&self) -> Option<f64> {
+        if self.history.len() < self.period {
+            return None;
+        }
+        let weighted: f64 = self.history.iter().enumerate().map(|(i, p)| (i + 1) as f64 * p).sum();
+        let weights = (self.period * (self.period + 1)) as f64 / 2.0;
+        Some(weighted / weights)
+    }
+
+    fn reset(&mut self) {
+        self.history.clear();
+    }
+
+    fn snapshot(&self) -> Option<JsonValue> {
+        Some(JsonValue::object()
+            .with("period", self.period as u64)
+            .with("history", f64_array(self.history.iter().copied())))
+    }
+
+    fn restore(&mut self, state: &JsonValue) -> MResult<()> {
+        expect_period(state, self.period)?;
+        let history = read_f64_array(state, "history")?;
+        if history.len() > self.period {
+            return fail(snapshot::invalid("WMA history longer than its period"));
+        }
+        self.history = history.into_iter().collect();
+        unit(())
+    }
+}
+
+/// Double EMA: `2 * EMA - EMA(EMA)`, trading some smoothness for less lag.
+pub struct DEMA {
+    ema: EMA,
+    ema_of_ema: EMA,
+}
+
+impl DEMA {
+    pub fn new(period: usize) -> Self {
+        DEMA { ema: EMA::new(period), ema_of_ema: EMA::new(period) }
+    }
+}
+
+impl Indicator for DEMA {
+    fn update(&mut self, price: f64) {
+        self.ema.update(price);
+        if let Some(ema) = self.ema.value() {
+            self.ema_of_ema.update(ema);
+        }
+    }
+
+    fn value(&self) -> Option<f64> {
+        Some(2.0 * self.ema.value()? - self.ema_of_ema.value()?)
+    }
+
+    fn reset(&mut self) {
+        self.ema.reset();
+        self.ema_of_ema.reset();
+    }
+}
+
+// --- Multi-output Indicators ---
+
+/// Indicators with several outputs. `Indicator::value` reports the primary
+/// line; `output` returns all of them once every line is ready.
+pub trait MultiIndicator: Indicator {
+    type Output;
+    fn output(&self) -> Option<Self::Output>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MacdOutput {
+    pub macd: f64,
+    pub signal: f64,
+    pub histogram: f64,
+}
+
+/// Moving Average Convergence/Divergence (classically 12/26/9).
+pub struct MACD {
+    fast: EMA,
+    slow: EMA,
+    signal: EMA,
+}
+
+impl MACD {
+    pub fn new(fast: usize, slow: usize, signal: usize) -> Self {
+        MACD { fast: EMA::new(fast), slow: EMA::new(slow), signal: EMA::new(signal) }
+    }
+
+    pub fn standard() -> Self {
+        MACD::new(12, 26, 9)
+    }
 }
 
-/// The output of the Risk Manager: A fully validated instruction.
-#[derive(Debug)]
-pub struct TradeInstruction {
-    pub symbol: String,
-    pub side: OrderSide,
-    pub amount: f64,
-    pub limit_price: Option<f64>,
-    pub stop_loss: Option<f64>,
-    pub take_profit: Option<f64>,
+impl Indicator for MACD {
+    fn update(&mut self, price: f64) {
+        self.fast.update(price);
+        self.slow.update(price);
+        if let Some(line) = self.value() {
+            self.signal.update(line);
+        }
+    }
+
+    /// The MACD line (fast EMA minus slow EMA).
+    fn value(&self) -> Option<f64> {
+        Some(self.fast.value()? - self.slow.value()?)
+    }
+
+    fn reset(&mut self) {
+        self.fast.reset();
+        self.slow.reset();
+        self.signal.reset();
+    }
 }
 
-// --- Technical Analysis Components ---
+impl MultiIndicator for MACD {
+    type Output = MacdOutput;
 
-/// Trait for any technical indicator.
-pub trait Indicator {
-    fn update(&mut self, price: f64);
-    fn value(&self) -> Option<f64>;
-    fn reset(&mut self);
+    fn output(&self) -> Option<MacdOutput> {
+        let macd = self.value()?;
+        let signal = self.signal.value()?;
+        Some(MacdOutput { macd, signal, histogram: macd - signal })
+    }
 }
 
-/// Simple Moving Average Implementation.
-pub struct SMA {
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BollingerOutput {
+    pub upper: f64,
+    pub middle: f64,
+    pub lower: f64,
+    pub bandwidth: f64, // (upper - lower) / middle
+    pub percent_b: f64, // 0 at the lower band, 1 at the upper
+}
+
+/// Bollinger Bands: SMA ± `multiplier` population standard deviations.
+pub struct BollingerBands {
     period: usize,
+    multiplier: f64,
     history: VecDeque<f64>,
 }
 
-impl SMA {
-    pub fn new(period: usize) -> Self {
-        SMA {
-            period,
-            history: VecDeque::with_capacity(period),
-        }
+impl BollingerBands {
+    pub fn new(period: usize, multiplier: f64) -> Self {
+        BollingerBands { period: period.max(1), multiplier, history: VecDeque::with_capacity(period) }
     }
 }
 
-impl Indicator for SMA {
+impl Indicator for BollingerBands {
     fn update(&mut self, price: f64) {
         if self.history.len() >= self.period {
             self.history.pop_front();
@@ -78,113 +976,553 @@ impl Indicator for SMA {
         self.history.push_back(price);
     }
 
+    /// The middle band.
     fn value(&self) -> Option<f64> {
+        self.output().map(|b| b.middle)
+    }
+
+    fn reset(&mut self) {
+        self.history.clear();
+    }
+}
+
+impl MultiIndicator for BollingerBands {
+    type Output = BollingerOutput;
+
+    fn output(&self) -> Option<BollingerOutput> {
         if self.history.len() < self.period {
             return None;
         }
-        let sum: f64 = self.history.iter().sum();
-        Some(sum / self.period as f64)
+        let n = self.period as f64;
+        let middle = self.history.iter().sum::<f64>() / n;
+        let std_dev = (self.history.iter().map(|p| (p - middle).powi(2)).sum::<f64>() / n).sqrt();
+        let upper = middle + self.multiplier * std_dev;
+        let lower = middle - self.multiplier * std_dev;
+        let last = *self.history.back()?;
+        let width = upper - lower;
+
+        Some(BollingerOutput {
+            upper,
+            middle,
+            lower,
+            bandwidth: if middle != 0.0 { width / middle } else { 0.0 },
+            percent_b: if width > 0.0 { (last - lower) / width } else { 0.5 },
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StochasticOutput {
+    pub k: f64,
+    pub d: f64,
+}
+
+/// Stochastic oscillator: %K places the close within the `k_period` high-low
+/// range, %D is the SMA of %K over `d_period` bars.
+pub struct Stochastic {
+    k_period: usize,
+    bars: VecDeque<(f64, f64)>, // (high, low)
+    k: Option<f64>,
+    d: SMA,
+}
+
+impl Stochastic {
+    pub fn new(k_period: usize, d_period: usize) -> Self {
+        Stochastic { k_period: k_period.max(1), bars: VecDeque::with_capacity(k_period), k: None, d: SMA::new(d_period.max(1)) }
+    }
+}
+
+impl Indicator for Stochastic {
+    fn update(&mut self, price: f64) {
+        self.update_candle(&flat_candle(price));
+    }
+
+    fn update_candle(&mut self, candle: &Candle) {
+        if self.bars.len() >= self.k_period {
+            self.bars.pop_front();
+        }
+        self.bars.push_back((candle.high, candle.low));
+        if self.bars.len() < self.k_period {
+            return;
+        }
+
+        let highest = self.bars.iter().map(|b| b.0).fold(f64::MIN, f64::max);
+        let lowest = self.bars.iter().map(|b| b.1).fold(f64::MAX, f64::min);
+        let k = if highest > lowest { 100.0 * (candle.close - lowest) / (highest - lowest) } else { 50.0 };
+        self.k = Some(k);
+        self.d.update(k);
+    }
+
+    /// %K.
+    fn value(&self) -> Option<f64> {
+        self.k
     }
 
     fn reset(&mut self) {
-        self.history.clear();
+        self.bars.clear();
+        self.k = None;
+        self.d.reset();
     }
 }
 
-/// Relative Strength Index Implementation.
-pub struct RSI {
+impl MultiIndicator for Stochastic {
+    type Output = StochasticOutput;
+
+    fn output(&self) -> Option<StochasticOutput> {
+        Some(StochasticOutput { k: self.k?, d: self.d.value()? })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdxOutput {
+    pub adx: f64,
+    pub plus_di: f64,
+    pub minus_di: f64,
+}
+
+/// Average Directional Index (Wilder): trend strength regardless of direction,
+/// with the +DI / -DI lines that give the direction.
+pub struct ADX {
     period: usize,
-    gains: VecDeque<f64>,
-    losses: VecDeque<f64>,
-    prev_price: Option<f64>,
+    prev: Option<(f64, f64, f64)>, // (high, low, close)
+    seeded: usize,
+    smoothed: (f64, f64, f64),     // Wilder sums of (TR, +DM, -DM)
+    di: Option<(f64, f64)>,
+    dx_seed: Vec<f64>,
+    adx: Option<f64>,
 }
 
-impl RSI {
+impl ADX {
     pub fn new(period: usize) -> Self {
-        RSI {
-            period,
-            gains: VecDeque::new(),
-            losses: VecDeque::new(),
-            prev_price: None,
+        ADX {
+            period: period.max(1),
+            prev: None,
+            seeded: 0,
+            smoothed: (0.0, 0.0, 0.0),
+            di: None,
+            dx_seed: Vec::with_capacity(period),
+            adx: None,
         }
     }
 }
 
-impl Indicator for RSI {
+impl Indicator for ADX {
     fn update(&mut self, price: f64) {
-        if let Some(prev) = self.prev_price {
-            let change = price - prev;
-            let gain = if change > 0.0 { change } else { 0.0 };
-            let loss = if change < 0.0 { -change } else { 0.0 };
+        self.update_candle(&flat_candle(price));
+    }
+
+    fn update_candle(&mut self, candle: &Candle) {
+        let (prev_high, prev_low, prev_close) = match self.prev.replace((candle.high, candle.low, candle.close)) {
+            Some(p) => p,
+            None => return,
+        };
 
-            if self.gains.len() >= self.period { self.gains.pop_front(); }
-            if self.losses.len() >= self.period { self.losses.pop_front(); }
+        let up = candle.high - prev_high;
+        let down = prev_low - candle.low;
+        let plus_dm = if up > down && up > 0.0 { up } else { 0.0 };
+        let minus_dm = if down > up && down > 0.0 { down } else { 0.0 };
+        let true_range = (candle.high - candle.low)
+            .max((candle.high - prev_close).abs())
+            .max((candle.low - prev_close).abs());
 
-            self.gains.push_back(gain);
-            self.losses.push_back(loss);
+        let n = self.period as f64;
+        let (tr, pdm, mdm) = &mut self.smoothed;
+        if self.seeded < self.period {
+            *tr += true_range;
+            *pdm += plus_dm;
+            *mdm += minus_dm;
+            self.seeded += 1;
+            if self.seeded < self.period {
+                return;
+            }
+        } else {
+            *tr += true_range - *tr / n;
+            *pdm += plus_dm - *pdm / n;
+            *mdm += minus_dm - *mdm / n;
+        }
+
+        let (plus_di, minus_di) = if *tr > 0.0 { (100.0 * *pdm / *tr, 100.0 * *mdm / *tr) } else { (0.0, 0.0) };
+        self.di = Some((plus_di, minus_di));
+        let di_sum = plus_di + minus_di;
+        let dx = if di_sum > 0.0 { 100.0 * (plus_di - minus_di).abs() / di_sum } else { 0.0 };
+
+        match self.adx {
+            Some(adx) => self.adx = Some((adx * (n - 1.0) + dx) / n),
+            None => {
+                self.dx_seed.push(dx);
+                if self.dx_seed.len() == self.period {
+                    self.adx = Some(self.dx_seed.iter().sum::<f64>() / n);
+                    self.dx_seed.clear();
+                }
+            }
         }
-        self.prev_price = Some(price);
     }
 
     fn value(&self) -> Option<f64> {
-        if self.gains.len() < self.period { return None; }
-        
-        let avg_gain: f64 = self.gains.iter().sum::<f64>() / self.period as f64;
-        let avg_loss: f64 = self.losses.iter().sum::<f64>() / self.period as f64;
-
-        if avg_loss == 0.0 { return Some(100.0); }
-        
-        let rs = avg_gain / avg_loss;
-        Some(100.0 - (100.0 / (1.0 + rs)))
+        self.adx
     }
 
     fn reset(&mut self) {
-        self.gains.clear();
-        self.losses.clear();
-        self.prev_price = None;
+        self.prev = None;
+        self.seeded = 0;
+        self.smoothed = (0.0, 0.0, 0.0);
+        self.di = None;
+        self.dx_seed.clear();
+        self.adx = None;
+    }
+}
+
+impl MultiIndicator for ADX {
+    type Output = AdxOutput;
+
+    fn output(&self) -> Option<AdxOutput> {
+        let (plus_di, minus_di) = self.di?;
+        Some(AdxOutput { adx: self.adx?, plus_di, minus_di })
+    }
+}
+
+// --- Indicator Panel ---
+
+const PANEL_MA_PERIOD: usize = 20;
+const PANEL_STOCHASTIC: (usize, usize) = (14, 3); // %K, %D periods
+const PANEL_ATR_PERIOD: usize = 14;
+const PANEL_MFI_PERIOD: usize = 14;
+const PANEL_CMF_PERIOD: usize = 20;
+
+/// Indicator readings on one timeframe's closed bars, for the status report:
+/// trend (WMA, DEMA, MACD), momentum (Stochastic), volatility (ATR), volume
+/// trend (OBV), the session's fair price (UTC-daily VWAP) and money flow
+/// (MFI, CMF).
+pub struct IndicatorPanel {
+    timeframe: Timeframe,
+    wma: WMA,
+    dema: DEMA,
+    macd: MACD,
+    stochastic: Stochastic,
+    atr: ATR,
+    obv: OBV,
+    vwap: VWAP,
+    mfi: MFI,
+    cmf: CMF,
+}
+
+impl IndicatorPanel {
+    pub fn new(timeframe: Timeframe) -> Self {
+        IndicatorPanel {
+            timeframe,
+            wma: WMA::new(PANEL_MA_PERIOD),
+            dema: DEMA::new(PANEL_MA_PERIOD),
+            macd: MACD::standard(),
+            stochastic: Stochastic::new(PANEL_STOCHASTIC.0, PANEL_STOCHASTIC.1),
+            atr: ATR::new(PANEL_ATR_PERIOD),
+            obv: OBV::new(),
+            vwap: VWAP::new(86_400),
+            mfi: MFI::new(PANEL_MFI_PERIOD),
+            cmf: CMF::new(PANEL_CMF_PERIOD),
+        }
+    }
+
+    pub fn timeframe(&self) -> Timeframe {
+        self.timeframe
+    }
+
+    /// Bars needed before every reading is ready. Smoothed lines need their
+    /// input line first (DEMA's EMA of the EMA, MACD's signal, %D), and ATR
+    /// and MFI work on bar-to-bar changes, so they need one extra.
+    pub fn warmup_bars(&self) -> usize {
+        let (k, d) = PANEL_STOCHASTIC;
+        (2 * PANEL_MA_PERIOD - 1)
+            .max(26 + 9 - 1) // MACD::standard
+            .max(k + d - 1)
+            .max(PANEL_ATR_PERIOD + 1)
+            .max(PANEL_MFI_PERIOD + 1)
+            .max(PANEL_CMF_PERIOD)
+    }
+
+    /// Feeds a closed bar; bars of other timeframes are ignored.
+    pub fn on_candle(&mut self, candle: &Candle) {
+        if candle.timeframe != self.timeframe {
+            return;
+        }
+        self.wma.update_candle(candle);
+        self.dema.update_candle(candle);
+        self.macd.update_candle(candle);
+        self.stochastic.update_candle(candle);
+        self.atr.update_candle(candle);
+        self.obv.update_candle(candle);
+        self.vwap.update_candle(candle);
+        self.mfi.update_candle(candle);
+        self.cmf.update_candle(candle);
+    }
+
+    /// Rebuilds every reading from historical bars, oldest first.
+    pub fn warm_up(&mut self, candles: &[Candle]) {
+        self.wma.reset();
+        self.dema.reset();
+        self.macd.reset();
+        self.stochastic.reset();
+        self.atr.reset();
+        self.obv.reset();
+        self.vwap.reset();
+        self.mfi.reset();
+        self.cmf.reset();
+        candles.iter().for_each(|c| self.on_candle(c));
+    }
+
+    /// `WMA 2001.20 | ... | MACD +0.52 / +0.40 (+0.12) | Stoch 71.3 / 64.0 |
+    /// ATR 1.23 | ...`, leaving out readings still warming up. `None` while
+    /// none is ready.
+    pub fn summary(&self) -> Option<String> {
+        let readings = [
+            self.wma.value().map(|v| format!("WMA {:.2}", v)),
+            self.dema.value().map(|v| format!("DEMA {:.2}", v)),
+            self.macd.output().map(|m| format!("MACD {:+.2} / {:+.2} ({:+.2})", m.macd, m.signal, m.histogram)),
+            self.stochastic.output().map(|s| format!("Stoch {:.1} / {:.1}", s.k, s.d)),
+            self.atr.value().map(|v| format!("ATR {:.2}", v)),
+            self.obv.value().map(|v| format!("OBV {:+.3}", v)),
+            self.vwap.value().map(|v| format!("VWAP {:.2}", v)),
+            self.mfi.value().map(|v| format!("MFI {:.1}", v)),
+            self.cmf.value().map(|v| format!("CMF {:+.2}", v)),
+        ];
+        let ready: Vec<String> = readings.into_iter().flatten().collect();
+        if ready.is_empty() { None } else { Some(ready.join(" | ")) }
     }
 }
 
 // --- Strategy Implementation ---
 
+/// An order a strategy manages itself, outside the signal -> risk -> order path.
+#[derive(Debug, Clone)]
+pub enum OrderAction {
+    Place(OrderRequest),
+    Cancel { order_id: String },
+}
+
 pub trait Strategy {
     fn process_tick(&mut self, ticker: &Ticker) -> MResult<Option<Signal>>;
+
+    /// Orders to place or cancel this tick, for strategies that work resting
+    /// orders directly. Results come back through `observe_order`.
+    fn order_actions(&mut self, _ticker: &Ticker) -> Vec<OrderAction> { Vec::new() }
+
+    /// Receives the exchange's answer to an `OrderAction`, including rejections.
+    fn observe_order(&mut self, _order: &Order) {}
+
+    /// Receives every execution on the account's orders for the traded symbol.
+    fn observe_fill(&mut self, _fill: &Fill) {}
+
+    /// One line for the status report, e.g. inventory or PnL the strategy tracks.
+    fn status_line(&self) -> Option<String> { None }
+
+    /// Receives the engine's volume analysis for the tick about to be processed.
+    /// Called before `process_tick`; strategies that ignore volume can skip it.
+    fn observe_volume(&mut self, _spike: &VolumeSpike) {}
+
+    /// Receives aggressor-side trade flow for the tick about to be processed.
+    fn observe_flow(&mut self, _flow: &FlowWindow) {}
+
+    /// Receives the current session's volume profile levels (POC, value area, nodes).
+    fn observe_profile(&mut self, _profile: &ProfileSummary) {}
+
+    /// Receives the regime label for each newly classified bar.
+    fn observe_regime(&mut self, _reading: &RegimeReading) {}
+
+    /// Order book levels per side this strategy needs; 0 means the engine
+    /// does not fetch the book for it.
+    fn book_depth(&self) -> u32 { 0 }
+
+    /// Receives an order book snapshot taken for the tick about to be processed.
+    fn observe_book(&mut self, _book: &OrderBook) {}
+
+    /// Candle timeframes this strategy subscribes to. The engine only calls
+    /// `observe_candle` for bars of these timeframes.
+    fn timeframes(&self) -> Vec<Timeframe> { Vec::new() }
+
+    /// Receives each closed bar of a subscribed timeframe, before `process_tick`.
+    fn observe_candle(&mut self, _candle: &Candle) {}
+
+    /// Closed bars per subscribed timeframe needed before signals are meaningful.
+    fn warmup_bars(&self) -> usize { 0 }
+
+    /// Replays historical bars (oldest first) before live trading starts.
+    fn warm_up(&mut self, candles: &[Candle]) {
+        for candle in candles {
+            self.observe_candle(candle);
+        }
+    }
+
+    /// Internal state for restarts; `None` if the strategy does not support snapshots.
+    fn snapshot(&self) -> Option<JsonValue> { None }
+
+    /// Restores state produced by `snapshot`.
+    fn restore(&mut self, _state: &JsonValue) -> MResult<()> {
+        fail(snapshot::invalid("strategy does not support snapshots"))
+    }
 }
 
+const FAST_MA_PERIOD: usize = 5;
+const SLOW_MA_PERIOD: usize = 20;
+const RSI_PERIOD: usize = 14;
+
 pub struct VolumeBreakoutStrategy {
-    sma_short: SMA,
-    sma_long: SMA,
-    rsi: RSI,
-    volume_threshold: f64,
+    sma_short: TimeframeIndicator<SMA>,
+    sma_long: TimeframeIndicator<SMA>,
+    rsi: TimeframeIndicator<RSI>,
+    timeframe: Option<Timeframe>,
+    fresh_bar: bool, // A subscribed bar closed since the last evaluation
+    last_bar: Option<u64>, // Open time of the last bar fed to the indicators
+    min_severity: SpikeSeverity,
+    latest_spike: Option<VolumeSpike>,
+    regime: Option<MarketRegime>,
 }
 
 impl VolumeBreakoutStrategy {
-    pub fn new(vol_threshold: f64) -> Self {
+    /// With `timeframe` set, indicator periods count closed bars of that
+    /// timeframe and signals are evaluated once per bar; with `None` they
+    /// count engine polls as before.
+    pub fn new(min_severity: SpikeSeverity, timeframe: Option<Timeframe>) -> Self {
         VolumeBreakoutStrategy {
-            sma_short: SMA::new(5), // Fast MA
-            sma_long: SMA::new(20), // Slow MA
-            rsi: RSI::new(14),
-            volume_threshold: vol_threshold,
+            sma_short: TimeframeIndicator::new(SMA::new(FAST_MA_PERIOD), timeframe),
+            sma_long: TimeframeIndicator::new(SMA::new(SLOW_MA_PERIOD), timeframe),
+            rsi: TimeframeIndicator::new(RSI::new(RSI_PERIOD), timeframe),
+            timeframe,
+            fresh_bar: false,
+            last_bar: None,
+            min_severity,
+            latest_spike: None,
+            regime: None,
         }
     }
+
+    /// Swaps in an RSI with the given averaging; call before any data arrives.
+    pub fn with_rsi_smoothing(mut self, smoothing: RsiSmoothing) -> Self {
+        self.rsi = TimeframeIndicator::new(RSI::with_smoothing(RSI_PERIOD, smoothing), self.timeframe);
+        self
+    }
 }
 
 impl Strategy for VolumeBreakoutStrategy {
+    fn observe_volume(&mut self, spike: &VolumeSpike) {
+        self.latest_spike = Some(spike.clone());
+    }
+
+    fn observe_regime(&mut self, reading: &RegimeReading) {
+        self.regime = Some(reading.regime);
+    }
+
+    fn timeframes(&self) -> Vec<Timeframe> {
+        self.timeframe.into_iter().collect()
+    }
+
+    fn observe_candle(&mut self, candle: &Candle) {
+        let consumed = self.sma_short.on_candle(candle)
+            | self.sma_long.on_candle(candle)
+            | self.rsi.on_candle(candle);
+        if consumed {
+            self.fresh_bar = true;
+            self.last_bar = Some(candle.open_time);
+        }
+    }
+
+    fn warmup_bars(&self) -> usize {
+        SLOW_MA_PERIOD.max(RSI_PERIOD + 1) // RSI needs one extra price for its first change
+    }
+
+    /// Continues from the current (live or restored) state when the history
+    /// picks up right after the last bar seen; otherwise rebuilds from scratch
+    /// so a re-sync never double-counts or skips bars. The last historical bar
+    /// is not treated as fresh.
+    fn warm_up(&mut self, candles: &[Candle]) {
+        let step = self.timeframe.map_or(0, |tf| tf.secs());
+        let newer: Vec<&Candle> = candles.iter()
+            .filter(|c| self.last_bar.is_none_or(|last| c.open_time > last))
+            .collect();
+        let contiguous = match (self.last_bar, newer.first()) {
+            (Some(last), Some(first)) => first.open_time == last + step,
+            (Some(_), None) => true, // Already up to date
+            (None, _) => false,
+        };
+
+        if contiguous {
+            newer.into_iter().for_each(|c| self.observe_candle(c));
+        } else {
+            self.sma_short.reset();
+            self.sma_long.reset();
+            self.rsi.reset();
+            self.last_bar = None;
+            candles.iter().for_each(|c| self.observe_candle(c));
+        }
+        self.fresh_bar = false;
+    }
+
+    fn snapshot(&self) -> Option<JsonValue> {
+        Some(JsonValue::object()
+            .with("kind", "volume_breakout")
+            .with("timeframe", self.timeframe.map(|tf| tf.to_string()))
+            .with("last_bar", self.last_bar)
+            .with("sma_short", self.sma_short.snapshot()?)
+            .with("sma_long", self.sma_long.snapshot()?)
+            .with("rsi", self.rsi.snapshot()?))
+    }
+
+    /// All-or-nothing: a partially restored strategy is reset to cold.
+    fn restore(&mut self, state: &JsonValue) -> MResult<()> {
+        if state.get("kind").and_then(|v| v.as_str()) != Some("volume_breakout") {
+            return fail(snapshot::invalid("not a volume_breakout snapshot"));
+        }
+        let timeframe = self.timeframe.map(|tf| tf.to_string());
+        if state.get("timeframe").and_then(|v| v.as_str()) != timeframe.as_deref() {
+            return fail(snapshot::invalid("strategy timeframe does not match"));
+        }
+        let part = |key: &str| state.get(key).ok_or_else(|| snapshot::invalid(&format!("missing '{}'", key)));
+
+        let restored = part("sma_short").bind(|s| self.sma_short.restore(s))
+            .bind(|_| part("sma_long")).bind(|s| self.sma_long.restore(s))
+            .bind(|_| part("rsi")).bind(|s| self.rsi.restore(s))
+            .bind(|_| read_optional_f64(state, "last_bar"));
+
+        match restored {
+            Ok(last_bar) => {
+                self.last_bar = last_bar.map(|t| t as u64);
+                self.fresh_bar = false;
+                unit(())
+            }
+            Err(e) => {
+                self.sma_short.reset();
+                self.sma_long.reset();
+                self.rsi.reset();
+                self.last_bar = None;
+                fail(e)
+            }
+        }
+    }
+
     fn process_tick(&mut self, ticker: &Ticker) -> MResult<Option<Signal>> {
-        // Update Indicators
-        self.sma_short.update(ticker.price);
-        self.sma_long.update(ticker.price);
-        self.rsi.update(ticker.price);
+        // Update Indicators (no-op when they follow a candle timeframe)
+        self.sma_short.on_tick(ticker.price);
+        self.sma_long.on_tick(ticker.price);
+        self.rsi.on_tick(ticker.price);
+
+        // On a candle timeframe, evaluate once per closed bar
+        if self.timeframe.is_some() && !std::mem::replace(&mut self.fresh_bar, false) {
+            return unit(None);
+        }
 
         // Check if we have enough data
         let ma_short_val = match self.sma_short.value() { Some(v) => v, None => return unit(None) };
         let ma_long_val = match self.sma_long.value() { Some(v) => v, None => return unit(None) };
         let rsi_val = match self.rsi.value() { Some(v) => v, None => return unit(None) };
 
-        // 1. Volume Condition
-        if ticker.volume_1h < self.volume_threshold {
-            return unit(None); // Not enough liquidity
-        }
+        // 1. Volume Condition (spike must belong to this tick).
+        // Volatile markets produce spikes constantly, so demand a stronger one.
+        let min_severity = match self.regime {
+            Some(MarketRegime::Volatile) if self.min_severity < SpikeSeverity::High => SpikeSeverity::High,
+            _ => self.min_severity,
+        };
+        let spike = match &self.latest_spike {
+            Some(s) if s.timestamp == ticker.timestamp && s.severity >= min_severity => s,
+            _ => return unit(None), // No abnormal participation
+        };
 
         // 2. Trend Condition (Golden Cross)
         let is_uptrend = ma_short_val > ma_long_val;
@@ -193,26 +1531,59 @@ impl Strategy for VolumeBreakoutStrategy {
         let is_oversold = rsi_val < 30.0;
         let is_overbought = rsi_val > 70.0;
 
-        // Decision Logic
-        if is_uptrend && rsi_val > 50.0 && rsi_val < 70.0 {
+        // Decision Logic (no trend-following buys against a classified downtrend)
+        let against_trend = self.regime == Some(MarketRegime::Bearish);
+        let inputs = StrengthInputs {
+            volume_z: spike.score(),
+            fast_ma: ma_short_val,
+            slow_ma: ma_long_val,
+            rsi: rsi_val,
+            regime: self.regime,
+        };
+        // Every condition is recorded, including the other side's failed ones
+        let rsi_buy_zone = rsi_val > 50.0 && rsi_val < 70.0;
+        let context = SignalContext::new()
+            .indicator("sma_fast", ma_short_val)
+            .indicator("sma_slow", ma_long_val)
+            .indicator("rsi", rsi_val)
+            .indicator("volume_1h", ticker.volume_1h)
+            .indicator("volume_score", spike.score())
+            .indicator("relative_volume", spike.relative_volume)
+            .indicator("price", ticker.price)
+            .check("volume_spike", format!("severity >= {}", min_severity), spike.score(), true)
+            .check("buy_golden_cross", "sma_fast > sma_slow", ma_short_val - ma_long_val, is_uptrend)
+            .check("buy_regime_not_bearish", "regime != Bearish (1 = bearish)", if against_trend { 1.0 } else { 0.0 }, !against_trend)
+            .check("buy_rsi_zone", "50 < rsi < 70", rsi_val, rsi_buy_zone)
+            .check("sell_bearish_cross", "sma_fast <= sma_slow", ma_short_val - ma_long_val, !is_uptrend)
+            .check("sell_rsi_overbought", "rsi > 70", rsi_val, is_overbought);
+
+        if is_uptrend && !against_trend && rsi_buy_zone {
             // Trend following BUY
+            let (strength, components) = score_signal(&OrderSide::Buy, &inputs);
             unit(Some(Signal {
                 symbol: ticker.symbol.clone(),
                 side: OrderSide::Buy,
-                strength: 0.8,
-                regime: MarketRegime::Bullish,
+                strength,
+                quantity: None,
+                regime: self.regime.unwrap_or(MarketRegime::Sideways), // Unclassified: no trend assumed
                 timestamp: ticker.timestamp,
-                reason: format!("Golden Cross (S:{:.2}/L:{:.2}) + Vol {:.0}", ma_short_val, ma_long_val, ticker.volume_1h),
+                reason: format!("Golden Cross (S:{:.2}/L:{:.2}) + Vol {:.0} ({} z:{:.1})", ma_short_val, ma_long_val, ticker.volume_1h, spike.severity, spike.score()),
+                components,
+                context,
             }))
         } else if !is_uptrend && is_overbought {
             // Mean reversion SELL
+            let (strength, components) = score_signal(&OrderSide::Sell, &inputs);
             unit(Some(Signal {
                 symbol: ticker.symbol.clone(),
                 side: OrderSide::Sell,
-                strength: 0.6,
-                regime: MarketRegime::Bearish,
+                strength,
+                quantity: None,
+                regime: self.regime.unwrap_or(MarketRegime::Sideways),
                 timestamp: ticker.timestamp,
                 reason: format!("Bearish Cross + Overbought RSI {:.2}", rsi_val),
+                components,
+                context,
             }))
         } else {
             unit(None)
@@ -222,10 +1593,42 @@ impl Strategy for VolumeBreakoutStrategy {
 
 // --- Risk Management Implementation ---
 
+/// Per-regime scaling of the base risk parameters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RegimeAdjustment {
+    pub risk_multiplier: f64, // Applied to risk per trade
+    pub stop_multiplier: f64, // Applied to the stop-loss distance
+}
+
+impl RegimeAdjustment {
+    /// Full size in uptrends, smaller in downtrends and ranges, and smaller
+    /// with wider stops when volatility would otherwise stop positions out.
+    pub fn standard(regime: MarketRegime) -> Self {
+        let (risk_multiplier, stop_multiplier) = match regime {
+            MarketRegime::Bullish => (1.0, 1.0),
+            MarketRegime::Bearish => (0.75, 1.0),
+            MarketRegime::Sideways => (0.5, 0.8),
+            MarketRegime::Volatile => (0.5, 1.5),
+        };
+        RegimeAdjustment { risk_multiplier, stop_multiplier }
+    }
+
+    /// Risk can be cut but not raised; stops may widen up to 3x.
+    pub fn validate(&self, regime: MarketRegime) -> MResult<()> {
+        let valid = self.risk_multiplier > 0.0 && self.risk_multiplier <= 1.0
+            && self.stop_multiplier > 0.0 && self.stop_multiplier <= 3.0;
+        if !valid {
+            return fail(BotError::ConfigurationError(format!("Invalid {:?} adjustment: {:?}", regime, self)));
+        }
+        unit(())
+    }
+}
+
 pub struct RiskManager {
     max_account_risk_per_trade: f64, // e.g., 0.01 (1%)
     max_leverage: f64,
     stop_loss_pct: f64,
+    regime_adjustments: HashMap<MarketRegime, RegimeAdjustment>,
 }
 
 impl RiskManager {
@@ -234,7 +1637,45 @@ impl RiskManager {
             max_account_risk_per_trade: risk_per_trade,
             max_leverage: 1.0, // No leverage for spot
             stop_loss_pct: stop_loss,
+            regime_adjustments: MarketRegime::ALL.iter().map(|r| (*r, RegimeAdjustment::standard(*r))).collect(),
+        }
+    }
+
+    pub fn regime_adjustment(&self, regime: MarketRegime) -> RegimeAdjustment {
+        self.regime_adjustments.get(&regime).copied().unwrap_or_else(|| RegimeAdjustment::standard(regime))
+    }
+
+    /// Overrides the scaling for one regime. Risk can be cut but not raised.
+    pub fn set_regime_adjustment(&mut self, regime: MarketRegime, adjustment: RegimeAdjustment) -> MResult<()> {
+        adjustment.validate(regime)?;
+        self.regime_adjustments.insert(regime, adjustment);
+        unit(())
+    }
+
+    pub fn risk_per_trade(&self) -> f64 {
+        self.max_account_risk_per_trade
+    }
+
+    pub fn stop_loss_pct(&self) -> f64 {
+        self.stop_loss_pct
+    }
+
+    /// Runtime adjustment of risk per trade; capped at 10% of the account.
+    pub fn set_risk_per_trade(&mut self, value: f64) -> MResult<()> {
+        if !(value > 0.0 && value <= 0.10) {
+            return fail(BotError::ConfigurationError(format!("Risk per trade must be in (0, 0.10], got {}", value)));
+        }
+        self.max_account_risk_per_trade = value;
+        unit(())
+    }
+
+    /// Runtime adjustment of the stop-loss distance.
+    pub fn set_stop_loss_pct(&mut self, value: f64) -> MResult<()> {
+        if !(value > 0.0 && value < 0.5) {
+            return fail(BotError::ConfigurationError(format!("Stop loss must be in (0, 0.5), got {}", value)));
         }
+        self.stop_loss_pct = value;
+        unit(())
     }
 
     /// Calculates the position size based on account balance and risk parameters.
@@ -245,37 +1686,281 @@ impl RiskManager {
             return fail(BotError::RiskViolation("Insufficient free balance".to_string()));
         }
 
-        // Calculate maximum risk amount in Quote currency
-        let risk_amount = balance.free * self.max_account_risk_per_trade * signal.strength;
-        
-        // Ensure minimum trade size (simplified logic)
-        if risk_amount < 10.0 {
-             return fail(BotError::RiskViolation("Calculated position size below exchange minimum".to_string()));
-        }
+        // Scale risk and stop distance for the regime the signal was taken in
+        let adjustment = self.regime_adjustment(signal.regime);
+        let stop_loss_pct = (self.stop_loss_pct * adjustment.stop_multiplier).min(0.45);
 
-        // Calculate Quantity
-        let quantity = risk_amount / current_price;
+        // Calculate Quantity: as requested, or from the maximum risk amount in Quote currency
+        let quantity = match signal.quantity {
+            Some(quantity) if quantity > 0.0 => quantity,
+            Some(quantity) => return fail(BotError::RiskViolation(format!("Invalid signal quantity {}", quantity))),
+            None => {
+                let risk_amount = balance.free * self.max_account_risk_per_trade * adjustment.risk_multiplier * signal.strength;
+
+                // Ensure minimum trade size (simplified logic)
+                if risk_amount < 10.0 {
+                     return fail(BotError::RiskViolation("Calculated position size below exchange minimum".to_string()));
+                }
+                ((risk_amount / current_price) * 1000.0).round() / 1000.0 // Round to 3 decimals
+            }
+        };
 
         // Calculate Stop Loss & Take Profit
         let (sl, tp) = match signal.side {
             OrderSide::Buy => (
-                current_price * (1.0 - self.stop_loss_pct),
-                current_price * (1.0 + (self.stop_loss_pct * 2.0)) // 1:2 Risk/Reward
+                current_price * (1.0 - stop_loss_pct),
+                current_price * (1.0 + (stop_loss_pct * 2.0)) // 1:2 Risk/Reward
             ),
             OrderSide::Sell => (
-                current_price * (1.0 + self.stop_loss_pct),
-                current_price * (1.0 - (self.stop_loss_pct * 2.0))
+                current_price * (1.0 + stop_loss_pct),
+                current_price * (1.0 - (stop_loss_pct * 2.0))
             ),
         };
 
+        let explanation = signal.explain();
+
         // Construct final instruction
         unit(TradeInstruction {
             symbol: signal.symbol,
             side: signal.side,
-            amount: (quantity * 1000.0).round() / 1000.0, // Round to 3 decimals
-            limit_price: Some(current_price), // Assuming Limit entry at current price
+            amount: quantity,
+            // Entries take the market: a limit at the price read earlier in the tick
+            // would be refused as soon as the price moved against it
+            limit_price: None,
             stop_loss: Some(sl),
             take_profit: Some(tp),
+            explanation,
         })
     }
 }
+
+// =================================================================================
+// UNIT TESTS
+// =================================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar(high: f64, low: f64, close: f64, volume: f64, open_time: u64) -> Candle {
+        Candle {
+            symbol: "TEST".to_string(),
+            timeframe: Timeframe::M1,
+            open_time,
+            open: close,
+            high,
+            low,
+            close,
+            volume,
+            trade_count: 1,
+        }
+    }
+
+    fn assert_close(actual: Option<f64>, expected: f64) {
+        let value = actual.expect("indicator not ready");
+        assert!((value - expected).abs() < 1e-4, "expected {}, got {}", expected, value);
+    }
+
+    #[test]
+    fn test_ohlcv_indicators() {
+        let bars = [
+            bar(10.0, 8.0, 9.0, 100.0, 0),
+            bar(11.0, 9.0, 10.5, 200.0, 60),
+            bar(13.0, 10.0, 12.0, 150.0, 120),
+        ];
+        let mut atr = ATR::new(2);
+        let mut obv = OBV::new();
+        let mut vwap = VWAP::new(86_400);
+        let mut mfi = MFI::new(2);
+        let mut cmf = CMF::new(3);
+        for b in &bars {
+            atr.update_candle(b);
+            obv.update_candle(b);
+            vwap.update_candle(b);
+            mfi.update_candle(b);
+            cmf.update_candle(b);
+        }
+
+        assert_close(atr.value(), 2.5); // Seed (2 + 2) / 2, then (2 + 3) / 2
+        assert_close(obv.value(), 350.0);
+        assert_close(vwap.value(), 4683.3333 / 450.0);
+        assert_close(mfi.value(), 100.0); // Typical price rose on every bar
+        assert_close(cmf.value(), 150.0 / 450.0);
+
+        // Price-only updates still drive close-based indicators through the default
+        let mut sma = SMA::new(2);
+        sma.update_candle(&bars[1]);
+        sma.update_candle(&bars[2]);
+        assert_close(sma.value(), 11.25);
+    }
+
+    #[test]
+    fn test_indicator_panel_reports_ready_readings() {
+        let bars = dataset();
+        let mut panel = IndicatorPanel::new(Timeframe::M1);
+        assert_eq!(panel.summary(), None);
+
+        // OBV and VWAP read from the first bar; the windowed ones need more
+        panel.warm_up(&bars[..5]);
+        let early = panel.summary().unwrap();
+        assert!(early.starts_with("OBV ") && early.contains("VWAP ") && !early.contains("ATR"), "{}", early);
+
+        panel.warm_up(&bars[..panel.warmup_bars()]);
+        let ready = panel.summary().unwrap();
+        for name in ["WMA ", "DEMA ", "MACD ", "Stoch ", "ATR ", "OBV ", "VWAP ", "MFI ", "CMF "] {
+            assert!(ready.contains(name), "{} missing from {}", name, ready);
+        }
+
+        // Bars of other timeframes leave it untouched
+        let mut hourly = bars[0].clone();
+        hourly.timeframe = Timeframe::H1;
+        hourly.volume = 1e9;
+        panel.on_candle(&hourly);
+        assert_eq!(panel.summary().unwrap(), ready);
+    }
+
+    // 40 bars of daily closes (the classic Wilder RSI sample extended), with
+    // highs/lows offset from the close. References computed independently
+    // from the textbook definitions.
+    const CLOSES: [f64; 40] = [
+        44.34, 44.09, 44.15, 43.61, 44.33, 44.83, 45.10, 45.42, 45.84, 46.08,
+        45.89, 46.03, 45.61, 46.28, 46.28, 46.00, 46.03, 46.41, 46.22, 45.64,
+        46.21, 46.25, 45.71, 46.45, 45.78, 45.35, 44.03, 44.18, 44.22, 44.57,
+        43.42, 42.66, 43.13, 43.56, 44.01, 44.49, 44.91, 45.22, 44.78, 45.10,
+    ];
+
+    fn dataset() -> Vec<Candle> {
+        CLOSES.iter().enumerate().map(|(i, c)| {
+            let high = ((c + 0.30 + 0.05 * (i % 4) as f64) * 100.0).round() / 100.0;
+            let low = ((c - 0.25 - 0.04 * (i % 5) as f64) * 100.0).round() / 100.0;
+            bar(high, low, *c, 1000.0, i as u64 * 86_400)
+        }).collect()
+    }
+
+    #[test]
+    fn test_trend_and_momentum_reference_values() {
+        let mut ema = EMA::new(10);
+        let mut wma = WMA::new(10);
+        let mut dema = DEMA::new(10);
+        let mut macd = MACD::standard();
+        let mut bollinger = BollingerBands::new(20, 2.0);
+        let mut stochastic = Stochastic::new(14, 3);
+        let mut adx = ADX::new(14);
+        for b in dataset() {
+            ema.update_candle(&b);
+            wma.update_candle(&b);
+            dema.update_candle(&b);
+            macd.update_candle(&b);
+            bollinger.update_candle(&b);
+            stochastic.update_candle(&b);
+            adx.update_candle(&b);
+        }
+
+        assert_close(ema.value(), 44.601016);
+        assert_close(wma.value(), 44.536545);
+        assert_close(dema.value(), 44.696103);
+
+        let m = macd.output().unwrap();
+        assert_close(Some(m.macd), -0.122721);
+        assert_close(Some(m.signal), -0.219776);
+        assert_close(Some(m.histogram), 0.097055);
+
+        let bb = bollinger.output().unwrap();
+        assert_close(Some(bb.middle), 44.7015);
+        assert_close(Some(bb.upper), 46.793688);
+        assert_close(Some(bb.lower), 42.609312);
+        assert_close(Some(bb.bandwidth), 0.093607);
+        assert_close(Some(bb.percent_b), 0.595235);
+
+        let st = stochastic.output().unwrap();
+        assert_close(Some(st.k), 85.3125);
+        assert_close(Some(st.d), 78.168093);
+
+        let dmi = adx.output().unwrap();
+        assert_close(Some(dmi.adx), 17.387825);
+        assert_close(Some(dmi.plus_di), 28.357071);
+        assert_close(Some(dmi.minus_di), 23.644342);
+    }
+
+    #[test]
+    fn test_rsi_smoothing_modes_and_sma_stability() {
+        let mut wilder = RSI::new(14);
+        let mut simple = RSI::with_smoothing(14, RsiSmoothing::Simple);
+        for (i, price) in CLOSES.iter().enumerate() {
+            wilder.update(*price);
+            simple.update(*price);
+            if i == 14 {
+                assert_close(wilder.value(), 70.464135); // First Wilder value == simple average
+                assert_close(simple.value(), 70.464135);
+            }
+        }
+        assert_close(wilder.value(), 53.874920);
+        assert_close(simple.value(), 48.236953);
+        assert_eq!(RsiSmoothing::parse(" Simple ").unwrap(), RsiSmoothing::Simple);
+        assert!(RsiSmoothing::parse("ema").is_err());
+
+        // A million updates of large, alternating prices: the running sum must
+        // still match a fresh sum over the final window
+        let mut sma = SMA::new(50);
+        let price = |i: u64| 1.0e6 + (i % 7) as f64 * 0.1 + if i.is_multiple_of(2) { 1.0e-3 } else { 0.0 };
+        for i in 0..1_000_000 {
+            sma.update(price(i));
+        }
+        let exact = (1_000_000 - 50..1_000_000).map(price).sum::<f64>() / 50.0;
+        assert!((sma.value().unwrap() - exact).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_signal_strength_model() {
+        let textbook = StrengthInputs { volume_z: 6.0, fast_ma: 101.0, slow_ma: 100.0, rsi: 60.0, regime: Some(MarketRegime::Bullish) };
+        let (strength, components) = score_signal(&OrderSide::Buy, &textbook);
+        assert_close(Some(strength), 1.0);
+        assert_eq!(components.iter().map(|c| c.name).collect::<Vec<_>>(), vec!["volume", "trend", "momentum", "regime"]);
+
+        // 2-sigma spike, 0.1% MA gap, RSI 52, ranging market
+        let marginal = StrengthInputs { volume_z: 2.0, fast_ma: 100.1, slow_ma: 100.0, rsi: 52.0, regime: Some(MarketRegime::Sideways) };
+        let (strength, _) = score_signal(&OrderSide::Buy, &marginal);
+        assert_close(Some(strength), 0.35 / 3.0 + 0.30 * 0.1 + 0.20 * 0.2 + 0.15 * 0.5);
+
+        // The same gap scores zero trend for a sell, and a bullish regime opposes it
+        let (_, components) = score_signal(&OrderSide::Sell, &textbook);
+        assert_eq!(components[1].score, 0.0);
+        assert_eq!(components[3].score, 0.0);
+    }
+
+    #[test]
+    fn test_infinite_volume_score_saturates() {
+        // A spike over a flat history scores +inf and is the strongest volume reading
+        let flat_history = StrengthInputs { volume_z: f64::INFINITY, fast_ma: 101.0, slow_ma: 100.0, rsi: 60.0, regime: Some(MarketRegime::Bullish) };
+        let (strength, components) = score_signal(&OrderSide::Buy, &flat_history);
+        assert_eq!(components[0].score, 1.0);
+        assert_close(Some(strength), 1.0);
+
+        assert_eq!(clamp01(f64::NAN), 0.0);
+        assert_eq!(clamp01(f64::NEG_INFINITY), 0.0);
+    }
+
+    #[test]
+    fn test_signal_explanation_records_every_condition() {
+        let signal = Signal {
+            symbol: "BTCUSDT".to_string(),
+            side: OrderSide::Buy,
+            strength: 0.5,
+            quantity: None,
+            regime: MarketRegime::Sideways,
+            timestamp: 42,
+            reason: "test".to_string(),
+            components: Vec::new(),
+            context: SignalContext::new()
+                .indicator("rsi", 61.5)
+                .check("buy_rsi_zone", "50 < rsi < 70", 61.5, true)
+                .check("sell_rsi_overbought", "rsi > 70", 61.5, false),
+        };
+        let json = JsonValue::parse(&signal.explain().to_string()).unwrap();
+
+        assert_eq!(json.path(&["indicators", "rsi"]).and_then(|v| v.as_f64()), Some(61.5));
+        let conditions = json.get("conditions").and_then(|c| c.as_array()).unwrap();
+        assert_eq!(conditions.len(), 2);
+        assert_eq!(conditions[1].get("name").and_then(|v| v.as_str()), Some("sell_rsi_overbought"));
+        assert_eq!(conditions[1].get("passed").and_then(|v| v.as_bool()), Some(false));
+    }
+}
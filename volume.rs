@@ -0,0 +1,544 @@
+// =================================================================================
+// MODULE: Volume Analytics
+// DESCRIPTION:
+// Statistical detection of abnormal trading activity. Every observed volume
+// sample is scored against a rolling lookback window using three complementary
+// measures, then classified into a severity bucket that strategies and the
+// alerting path can react to.
+//
+// Key components:
+// - Rolling mean / standard deviation z-score
+// - Robust median / MAD (Median Absolute Deviation) score
+// - Relative volume versus the lookback average
+// - Severity classification (Normal -> Extreme)
+// - Seasonal (hour-of-day / weekday) baselines, persisted across restarts
+// =================================================================================
+
+use crate::monad::{MResult, unit, fail, BotError};
+use crate::exchange::Ticker;
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::fs;
+
+/// Minimum number of historical samples before a score is considered meaningful.
+const MIN_SAMPLES: usize = 5;
+
+/// Scales the MAD so the robust score is comparable to a z-score on normal data.
+const MAD_SCALE: f64 = 0.6745;
+
+/// Guards against division by a (numerically) zero dispersion.
+const EPSILON: f64 = 1e-12;
+
+/// Completed hours a seasonal bucket needs before it is trusted for scoring.
+const MIN_BUCKET_SAMPLES: u32 = 3;
+
+/// Header line of the persisted baseline file; bump on format changes.
+const BASELINE_FILE_HEADER: &str = "# seasonal-volume-baseline v1";
+
+// --- Spike Classification ---
+
+/// How unusual the current volume is relative to recent history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SpikeSeverity {
+    Normal,
+    Elevated,
+    High,
+    Extreme,
+}
+
+impl Display for SpikeSeverity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpikeSeverity::Normal => write!(f, "NORMAL"),
+            SpikeSeverity::Elevated => write!(f, "ELEVATED"),
+            SpikeSeverity::High => write!(f, "HIGH"),
+            SpikeSeverity::Extreme => write!(f, "EXTREME"),
+        }
+    }
+}
+
+/// Score cut-offs used to bucket a volume observation.
+/// A sample must clear `min_relative_volume` before any score is considered,
+/// which keeps tiny absolute moves on a very quiet market from firing.
+#[derive(Debug, Clone)]
+pub struct SpikeThresholds {
+    pub elevated: f64,
+    pub high: f64,
+    pub extreme: f64,
+    pub min_relative_volume: f64,
+}
+
+impl SpikeThresholds {
+    /// Conservative defaults: 2 / 3 / 5 sigma with at least 1.5x average volume.
+    pub fn standard() -> Self {
+        SpikeThresholds {
+            elevated: 2.0,
+            high: 3.0,
+            extreme: 5.0,
+            min_relative_volume: 1.5,
+        }
+    }
+
+    fn classify(&self, score: f64, relative_volume: f64) -> SpikeSeverity {
+        if relative_volume < self.min_relative_volume {
+            SpikeSeverity::Normal
+        } else if score >= self.extreme {
+            SpikeSeverity::Extreme
+        } else if score >= self.high {
+            SpikeSeverity::High
+        } else if score >= self.elevated {
+            SpikeSeverity::Elevated
+        } else {
+            SpikeSeverity::Normal
+        }
+    }
+}
+
+/// Full statistical breakdown of a single volume observation.
+#[derive(Debug, Clone)]
+pub struct VolumeSpike {
+    pub symbol: String,
+    pub volume: f64,
+    pub mean: f64,
+    pub std_dev: f64,
+    pub z_score: f64,
+    pub median: f64,
+    pub mad: f64,
+    pub robust_score: f64,
+    pub relative_volume: f64,
+    pub seasonal: Option<SeasonalScore>,
+    pub severity: SpikeSeverity,
+    pub timestamp: u64,
+}
+
+impl VolumeSpike {
+    /// True for anything above `Normal`.
+    pub fn is_spike(&self) -> bool {
+        self.severity > SpikeSeverity::Normal
+    }
+
+    /// The score used for classification: the seasonal score when a trusted
+    /// baseline exists for this time bucket, otherwise the stronger of the
+    /// classic and robust rolling scores.
+    pub fn score(&self) -> f64 {
+        match &self.seasonal {
+            Some(seasonal) => seasonal.z_score,
+            None => self.z_score.max(self.robust_score),
+        }
+    }
+}
+
+// --- Detector ---
+
+/// Rolling-window volume spike detector.
+/// Each sample is scored against the window *before* it is added, so a spike
+/// never dampens its own score.
+pub struct VolumeSpikeDetector {
+    lookback: usize,
+    thresholds: SpikeThresholds,
+    history: VecDeque<f64>,
+    seasonal: Option<SeasonalBaseline>,
+    last: Option<VolumeSpike>,
+}
+
+impl VolumeSpikeDetector {
+    pub fn new(lookback: usize, thresholds: SpikeThresholds) -> Self {
+        VolumeSpikeDetector {
+            lookback,
+            thresholds,
+            history: VecDeque::with_capacity(lookback),
+            seasonal: None,
+            last: None,
+        }
+    }
+
+    /// Enables time-of-day scoring. The baseline keeps learning from every
+    /// sample passed to `observe`.
+    pub fn set_seasonal_baseline(&mut self, baseline: SeasonalBaseline) {
+        self.seasonal = Some(baseline);
+    }
+
+    pub fn seasonal_baseline(&self) -> Option<&SeasonalBaseline> {
+        self.seasonal.as_ref()
+    }
+
+    pub fn seasonal_baseline_mut(&mut self) -> Option<&mut SeasonalBaseline> {
+        self.seasonal.as_mut()
+    }
+
+    pub fn lookback(&self) -> usize {
+        self.lookback
+    }
+
+    /// Replaces the lookback window with historical samples (oldest first)
+    /// without scoring them or teaching the seasonal baseline.
+    pub fn prime(&mut self, volumes: &[f64]) {
+        self.history.clear();
+        let skip = volumes.len().saturating_sub(self.lookback);
+        self.history.extend(volumes.iter().skip(skip).filter(|v| v.is_finite() && **v >= 0.0));
+    }
+
+    /// Scores the ticker's 1h volume and folds it into the lookback window.
+    /// Returns `None` while the window is still warming up.
+    pub fn update(&mut self, ticker: &Ticker) -> MResult<Option<VolumeSpike>> {
+        self.observe(&ticker.symbol, ticker.volume_1h, ticker.timestamp)
+    }
+
+    /// Same as `update` but for raw volume samples (e.g. historical candles).
+    pub fn observe(&mut self, symbol: &str, volume: f64, timestamp: u64) -> MResult<Option<VolumeSpike>> {
+        if !volume.is_finite() || volume < 0.0 {
+            return fail(BotError::StrategyError(format!("Invalid volume sample: {}", volume)));
+        }
+
+        let spike = self.score(symbol, volume, timestamp);
+
+        if self.history.len() >= self.lookback {
+            self.history.pop_front();
+        }
+        self.history.push_back(volume);
+
+        if let Some(baseline) = self.seasonal.as_mut() {
+            baseline.learn(volume, timestamp);
+        }
+
+        if spike.is_some() {
+            self.last = spike.clone();
+        }
+        unit(spike)
+    }
+
+    /// Scores a sample against the current window without mutating it.
+    pub fn score(&self, symbol: &str, volume: f64, timestamp: u64) -> Option<VolumeSpike> {
+        if self.history.len() < MIN_SAMPLES.min(self.lookback) {
+            return None;
+        }
+
+        let n = self.history.len() as f64;
+        let mean = self.history.iter().sum::<f64>() / n;
+        let variance = self.history.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+        let std_dev = variance.sqrt();
+
+        let median = median_of(self.history.iter().copied().collect());
+        let mad = median_of(self.history.iter().map(|v| (v - median).abs()).collect());
+
+        let z_score = dispersion_score(volume - mean, std_dev);
+        let robust_score = dispersion_score(MAD_SCALE * (volume - median), mad);
+        let relative_volume = if mean > EPSILON { volume / mean } else { 0.0 };
+
+        let seasonal = self.seasonal.as_ref().and_then(|b| b.score(volume, timestamp));
+        let severity = match &seasonal {
+            Some(s) => self.thresholds.classify(s.z_score, s.relative_volume),
+            None => self.thresholds.classify(z_score.max(robust_score), relative_volume),
+        };
+
+        Some(VolumeSpike {
+            symbol: symbol.to_string(),
+            volume,
+            mean,
+            std_dev,
+            z_score,
+            median,
+            mad,
+            robust_score,
+            relative_volume,
+            seasonal,
+            severity,
+            timestamp,
+        })
+    }
+
+    /// Most recent scored observation, if any.
+    pub fn last(&self) -> Option<&VolumeSpike> {
+        self.last.as_ref()
+    }
+}
+
+// --- Seasonal Baselines ---
+
+/// Volume relative to the learned baseline for the sample's time bucket.
+#[derive(Debug, Clone)]
+pub struct SeasonalScore {
+    pub hour: u8,
+    pub weekday: u8, // 0 = Monday
+    pub expected: f64,
+    pub z_score: f64,
+    pub relative_volume: f64,
+}
+
+/// Exponentially weighted mean/variance of one time bucket.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BucketStats {
+    pub count: u32,
+    pub mean: f64,
+    pub variance: f64,
+}
+
+impl BucketStats {
+    fn empty() -> Self {
+        BucketStats { count: 0, mean: 0.0, variance: 0.0 }
+    }
+
+    /// Uses a plain running average until `1 / count` drops below `alpha`,
+    /// so a fresh bucket is not dominated by its first observation.
+    fn update(&mut self, value: f64, alpha: f64) {
+        self.count = self.count.saturating_add(1);
+        let weight = (1.0 / self.count as f64).max(alpha);
+        let diff = value - self.mean;
+        let increment = weight * diff;
+        self.mean += increment;
+        self.variance = (1.0 - weight) * (self.variance + diff * increment);
+    }
+
+    fn is_trusted(&self) -> bool {
+        self.count >= MIN_BUCKET_SAMPLES
+    }
+}
+
+/// Per-hour-of-day and per-weekday volume profiles.
+///
+/// Samples arrive at poll frequency, but a bucket only learns once per
+/// completed clock hour: observations are averaged while the hour is open and
+/// committed when it rolls over. This keeps `alpha` meaningful in "hours"
+/// regardless of how often the engine polls.
+#[derive(Debug, Clone)]
+pub struct SeasonalBaseline {
+    alpha: f64,
+    hourly: [BucketStats; 24],
+    weekday: [BucketStats; 7],
+    overall: BucketStats,
+    open_hour: Option<(u64, f64, u32)>, // (hour index since epoch, sum, count)
+}
+
+impl SeasonalBaseline {
+    /// `alpha` is the EWMA weight given to each completed hour.
+    pub fn new(alpha: f64) -> Self {
+        SeasonalBaseline {
+            alpha,
+            hourly: [BucketStats::empty(); 24],
+            weekday: [BucketStats::empty(); 7],
+            overall: BucketStats::empty(),
+            open_hour: None,
+        }
+    }
+
+    /// True until the first hour has been committed.
+    pub fn is_empty(&self) -> bool {
+        self.overall.count == 0
+    }
+
+    /// Bulk-trains from historical `(timestamp, volume)` samples in time order.
+    pub fn train(&mut self, samples: &[(u64, f64)]) {
+        for (timestamp, volume) in samples {
+            self.learn(*volume, *timestamp);
+        }
+        self.flush();
+    }
+
+    /// Online update with a single observation.
+    pub fn learn(&mut self, volume: f64, timestamp: u64) {
+        let hour_index = timestamp / 3600;
+        match self.open_hour {
+            Some((index, ref mut sum, ref mut count)) if index == hour_index => {
+                *sum += volume;
+                *count += 1;
+            }
+            _ => {
+                self.flush();
+                self.open_hour = Some((hour_index, volume, 1));
+            }
+        }
+    }
+
+    /// Commits the currently open hour into the profiles.
+    pub fn flush(&mut self) {
+        if let Some((index, sum, count)) = self.open_hour.take() {
+            let mean = sum / count as f64;
+            let timestamp = index * 3600;
+            self.hourly[hour_of_day(timestamp) as usize].update(mean, self.alpha);
+            self.weekday[weekday_of(timestamp) as usize].update(mean, self.alpha);
+            self.overall.update(mean, self.alpha);
+        }
+    }
+
+    /// Scores `volume` against the hour-of-day baseline, scaled by how the
+    /// sample's weekday compares to the overall average.
+    /// Returns `None` until the hour bucket is trusted.
+    pub fn score(&self, volume: f64, timestamp: u64) -> Option<SeasonalScore> {
+        let hour = hour_of_day(timestamp);
+        let weekday = weekday_of(timestamp);
+        let hourly = &self.hourly[hour as usize];
+        if !hourly.is_trusted() {
+            return None;
+        }
+
+        let day = &self.weekday[weekday as usize];
+        let weekday_factor = if day.is_trusted() && self.overall.mean > EPSILON {
+            day.mean / self.overall.mean
+        } else {
+            1.0
+        };
+
+        let expected = hourly.mean * weekday_factor;
+        let std_dev = hourly.variance.sqrt() * weekday_factor;
+
+        Some(SeasonalScore {
+            hour,
+            weekday,
+            expected,
+            z_score: dispersion_score(volume - expected, std_dev),
+            relative_volume: if expected > EPSILON { volume / expected } else { 0.0 },
+        })
+    }
+
+    /// Loads a baseline from disk; a missing file yields a fresh baseline.
+    pub fn load(path: &str, alpha: f64) -> MResult<Self> {
+        let content = match fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return unit(SeasonalBaseline::new(alpha)),
+            Err(e) => return fail(BotError::InternalStateError(format!("Cannot read baseline {}: {}", path, e))),
+        };
+        Self::parse(&content, alpha)
+    }
+
+    /// Writes the committed profiles (the open hour is flushed first on a copy).
+    pub fn save(&self, path: &str) -> MResult<()> {
+        let mut snapshot = self.clone();
+        snapshot.flush();
+        fs::write(path, snapshot.serialize())
+            .map_err(|e| BotError::InternalStateError(format!("Cannot write baseline {}: {}", path, e)))
+    }
+
+    fn serialize(&self) -> String {
+        let mut out = String::new();
+        out.push_str(BASELINE_FILE_HEADER);
+        out.push('\n');
+        let line = |kind: &str, idx: usize, b: &BucketStats| format!("{} {} {} {} {}\n", kind, idx, b.count, b.mean, b.variance);
+        out.push_str(&line("overall", 0, &self.overall));
+        for (i, b) in self.hourly.iter().enumerate() {
+            out.push_str(&line("hour", i, b));
+        }
+        for (i, b) in self.weekday.iter().enumerate() {
+            out.push_str(&line("weekday", i, b));
+        }
+        out
+    }
+
+    fn parse(content: &str, alpha: f64) -> MResult<Self> {
+        let mut lines = content.lines();
+        if lines.next().map(str::trim) != Some(BASELINE_FILE_HEADER) {
+            return fail(BotError::ConfigurationError("Unrecognised seasonal baseline format".to_string()));
+        }
+
+        let mut baseline = SeasonalBaseline::new(alpha);
+        for (n, raw) in lines.enumerate() {
+            let line_no = n + 2;
+            let parts: Vec<&str> = raw.split_whitespace().collect();
+            if parts.is_empty() {
+                continue;
+            }
+            if parts.len() != 5 {
+                return fail(BotError::ConfigurationError(format!("Baseline line {}: expected 5 fields", line_no)));
+            }
+            let idx: usize = parts[1].parse()
+                .map_err(|_| BotError::ConfigurationError(format!("Baseline line {}: bad index", line_no)))?;
+            let stats = BucketStats {
+                count: parts[2].parse().map_err(|_| BotError::ConfigurationError(format!("Baseline line {}: bad count", line_no)))?,
+                mean: parts[3].parse().map_err(|_| BotError::ConfigurationError(format!("Baseline line {}: bad mean", line_no)))?,
+                variance: parts[4].parse().map_err(|_| BotError::ConfigurationError(format!("Baseline line {}: bad variance", line_no)))?,
+            };
+            let slot = match (parts[0], idx) {
+                ("overall", 0) => &mut baseline.overall,
+                ("hour", i) if i < 24 => &mut baseline.hourly[i],
+                ("weekday", i) if i < 7 => &mut baseline.weekday[i],
+                _ => return fail(BotError::ConfigurationError(format!("Baseline line {}: unknown bucket", line_no))),
+            };
+            *slot = stats;
+        }
+        unit(baseline)
+    }
+}
+
+// --- Helpers ---
+
+/// UTC hour of day (0-23) for a unix timestamp in seconds.
+pub fn hour_of_day(timestamp: u64) -> u8 {
+    ((timestamp / 3600) % 24) as u8
+}
+
+/// UTC weekday for a unix timestamp in seconds, 0 = Monday.
+/// The epoch (1970-01-01) was a Thursday.
+pub fn weekday_of(timestamp: u64) -> u8 {
+    (((timestamp / 86_400) + 3) % 7) as u8
+}
+
+/// Short English name for a `weekday_of` value.
+pub fn weekday_name(weekday: u8) -> &'static str {
+    ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"][weekday as usize % 7]
+}
+
+/// Deviation divided by dispersion. A flat history makes any increase
+/// infinitely unusual, which the relative-volume gate then keeps in check.
+fn dispersion_score(deviation: f64, dispersion: f64) -> f64 {
+    if dispersion > EPSILON {
+        deviation / dispersion
+    } else if deviation > EPSILON {
+        f64::INFINITY
+    } else {
+        0.0
+    }
+}
+
+fn median_of(mut values: Vec<f64>) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+// =================================================================================
+// UNIT TESTS
+// =================================================================================
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spike_classification() {
+        let mut detector = VolumeSpikeDetector::new(20, SpikeThresholds::standard());
+        for i in 0..20 {
+            let vol = 100.0 + (i % 5) as f64;
+            detector.observe("MON/USDT", vol, i).unwrap();
+        }
+
+        let quiet = detector.observe("MON/USDT", 102.0, 20).unwrap().unwrap();
+        assert_eq!(quiet.severity, SpikeSeverity::Normal);
+
+        let spike = detector.observe("MON/USDT", 1000.0, 21).unwrap().unwrap();
+        assert_eq!(spike.severity, SpikeSeverity::Extreme);
+        assert!(spike.relative_volume > 9.0);
+    }
+
+    #[test]
+    fn test_seasonal_baseline_round_trip() {
+        let mut baseline = SeasonalBaseline::new(0.1);
+        // Five days of history: 14:00 UTC is busy, everything else is quiet.
+        let samples: Vec<(u64, f64)> = (0..24 * 5)
+            .map(|h| (h * 3600, if h % 24 == 14 { 1000.0 } else { 100.0 }))
+            .collect();
+        baseline.train(&samples);
+
+        let busy_hour = 14 * 3600 + 24 * 3600 * 6;
+        let score = baseline.score(1000.0, busy_hour).unwrap();
+        assert!(score.relative_volume < 1.5, "busy hour volume should look normal");
+
+        let restored = SeasonalBaseline::parse(&baseline.serialize(), 0.1).unwrap();
+        assert_eq!(restored.hourly, baseline.hourly);
+        assert_eq!(restored.weekday, baseline.weekday);
+    }
+}